@@ -2,7 +2,7 @@ use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main
 use token_standard::*;
 
 fn benchmark_balance_of(c: &mut Criterion) {
-    let creator = "alice".to_string();
+    let creator = Address::new("alice").unwrap();
     let token = TokenState::new(creator.clone(), 1_000_000);
 
     // 1. 존재하는 주소 조회
@@ -11,15 +11,15 @@ fn benchmark_balance_of(c: &mut Criterion) {
     });
 
     // 2. 존재하지 않는 주소 조회
-    let unknown = "unknown".to_string();
+    let unknown = Address::new("unknown").unwrap();
     c.bench_function("balance_of non-existing address", |b| {
         b.iter(|| token.balance_of(black_box(&unknown)));
     });
 }
 
 fn benchmark_transfer(c: &mut Criterion) {
-    let creator = "alice".to_string();
-    let recipient = "bob".to_string();
+    let creator = Address::new("alice").unwrap();
+    let recipient = Address::new("bob").unwrap();
 
     // 성공 케이스
     c.bench_function("transfer success", |b| {
@@ -40,5 +40,26 @@ fn benchmark_transfer(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_balance_of, benchmark_transfer);
+fn benchmark_from_balances(c: &mut Criterion) {
+    let entries: Vec<(Address, Balance)> = addrgen::generate(1, 10_000)
+        .into_iter()
+        .map(|a| (a, 100))
+        .collect();
+
+    // 대량 상태 구성: fast path
+    c.bench_function("from_balances 10k accounts", |b| {
+        b.iter_batched(
+            || entries.clone(),
+            |entries| TokenState::from_balances(black_box(entries)),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+criterion_group!(
+    benches,
+    benchmark_balance_of,
+    benchmark_transfer,
+    benchmark_from_balances
+);
 criterion_main!(benches);
@@ -0,0 +1,287 @@
+//! Validated account identifiers.
+//!
+//! [`Address`] is a distinct type rather than a bare `String`, so arbitrary
+//! text (names, memos, error messages) can't be passed where an account is
+//! expected. Construction goes through an [`AddressValidator`]:
+//! [`Address::new`] and [`str::parse`] use the permissive
+//! [`DefaultValidator`], and [`Address::with_validator`] accepts a stricter
+//! one such as [`EvmValidator`] when a deployment has a fixed format.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Why a string was rejected as an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    /// The string was empty
+    Empty,
+    /// The string exceeded the validator's length limit
+    TooLong {
+        /// Length of the rejected string in bytes
+        len: usize,
+        /// Maximum length the validator accepts
+        max: usize,
+    },
+    /// A character outside the validator's alphabet
+    InvalidCharacter {
+        /// Byte offset of the offending character
+        index: usize,
+        /// The offending character
+        found: char,
+    },
+    /// The string didn't match the validator's required shape
+    InvalidFormat(&'static str),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "address is empty"),
+            Self::TooLong { len, max } => write!(f, "address is {len} bytes, limit is {max}"),
+            Self::InvalidCharacter { index, found } => {
+                write!(f, "invalid character {found:?} at byte {index}")
+            }
+            Self::InvalidFormat(expected) => write!(f, "expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+/// Decides which strings are acceptable addresses.
+pub trait AddressValidator {
+    fn validate(&self, raw: &str) -> Result<(), AddressError>;
+
+    /// Canonical spelling of an address that passed [`validate`], so that
+    /// spellings of the same account compare equal. Unchanged by default.
+    ///
+    /// [`validate`]: AddressValidator::validate
+    fn normalize(&self, raw: String) -> String {
+        raw
+    }
+}
+
+/// Accepts 1 to 128 bytes of printable, non-space ASCII.
+///
+/// Covers hex, base58, and bech32 encodings alike while still rejecting
+/// whitespace, control characters, and other obvious mistakes. Strings
+/// shaped like an EVM address are lowercased as [`EvmValidator`] does, so
+/// a checksummed `0x…dEaD` and the `0x…dead` an `H160` converts to are one
+/// account.
+pub struct DefaultValidator;
+
+impl DefaultValidator {
+    pub const MAX_LEN: usize = 128;
+}
+
+impl AddressValidator for DefaultValidator {
+    fn validate(&self, raw: &str) -> Result<(), AddressError> {
+        if raw.is_empty() {
+            return Err(AddressError::Empty);
+        }
+        if raw.len() > Self::MAX_LEN {
+            return Err(AddressError::TooLong {
+                len: raw.len(),
+                max: Self::MAX_LEN,
+            });
+        }
+        match raw.char_indices().find(|(_, c)| !c.is_ascii_graphic()) {
+            Some((index, found)) => Err(AddressError::InvalidCharacter { index, found }),
+            None => Ok(()),
+        }
+    }
+
+    fn normalize(&self, raw: String) -> String {
+        match EvmValidator.validate(&raw) {
+            Ok(()) => EvmValidator.normalize(raw),
+            Err(_) => raw,
+        }
+    }
+}
+
+/// Accepts EVM-style addresses: `0x` followed by exactly 40 hex digits.
+///
+/// Checksum casing is not verified. Hex digits are lowercased instead, so
+/// `0xAbC…` and `0xabc…` name the same account.
+pub struct EvmValidator;
+
+impl AddressValidator for EvmValidator {
+    fn validate(&self, raw: &str) -> Result<(), AddressError> {
+        let Some(hex) = raw.strip_prefix("0x") else {
+            return Err(AddressError::InvalidFormat("0x prefix"));
+        };
+        if hex.len() != 40 {
+            return Err(AddressError::InvalidFormat("40 hex digits after 0x"));
+        }
+        match hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+            Some((index, found)) => Err(AddressError::InvalidCharacter {
+                index: index + 2,
+                found,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn normalize(&self, raw: String) -> String {
+        raw.to_ascii_lowercase()
+    }
+}
+
+/// An account identifier that passed validation.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "String", try_from = "String")
+)]
+pub struct Address(String);
+
+impl Address {
+    /// Validates `raw` with [`DefaultValidator`].
+    pub fn new(raw: impl Into<String>) -> Result<Self, AddressError> {
+        Self::with_validator(raw, &DefaultValidator)
+    }
+
+    pub fn with_validator(
+        raw: impl Into<String>,
+        validator: &dyn AddressValidator,
+    ) -> Result<Self, AddressError> {
+        let raw = raw.into();
+        validator.validate(&raw)?;
+        Ok(Self(validator.normalize(raw)))
+    }
+
+    // 내부 생성기(addrgen, workload) 전용: 형식이 보장된 문자열만 전달
+    pub(crate) fn trusted(raw: String) -> Self {
+        debug_assert_eq!(DefaultValidator.validate(&raw), Ok(()));
+        Self(raw)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(raw: &str) -> Result<Self, AddressError> {
+        Self::new(raw)
+    }
+}
+
+impl TryFrom<String> for Address {
+    type Error = AddressError;
+
+    fn try_from(raw: String) -> Result<Self, AddressError> {
+        Self::new(raw)
+    }
+}
+
+impl From<Address> for String {
+    fn from(address: Address) -> String {
+        address.0
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// 로그/테스트 출력에서 문자열처럼 보이도록 newtype 래퍼 없이 출력
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl AsRef<str> for Address {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_validator_accepts_common_encodings() {
+        for raw in [
+            "alice",
+            "0x000000000000000000000000000000000000dead",
+            "cosmos1qpzry9x8gf2tvdw0s3jn54khce6mua7l",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+        ] {
+            assert_eq!(Address::new(raw).unwrap().as_str(), raw);
+        }
+    }
+
+    #[test]
+    fn test_default_validator_lowercases_evm_addresses() {
+        let checksummed = Address::new("0x000000000000000000000000000000000000dEaD").unwrap();
+
+        assert_eq!(
+            checksummed.as_str(),
+            "0x000000000000000000000000000000000000dead"
+        );
+        // EVM 형식이 아니면 대소문자 유지 (base58 등)
+        assert_eq!(Address::new("AbC").unwrap().as_str(), "AbC");
+    }
+
+    #[test]
+    fn test_default_validator_rejects_malformed() {
+        assert_eq!(Address::new(""), Err(AddressError::Empty));
+        assert_eq!(
+            Address::new("al ice"),
+            Err(AddressError::InvalidCharacter {
+                index: 2,
+                found: ' '
+            })
+        );
+        assert_eq!(
+            Address::new("a".repeat(129)),
+            Err(AddressError::TooLong { len: 129, max: 128 })
+        );
+    }
+
+    #[test]
+    fn test_evm_validator() {
+        let ok = "0x000000000000000000000000000000000000dEaD";
+        let lower = "0x000000000000000000000000000000000000dead";
+
+        assert_eq!(
+            Address::with_validator(ok, &EvmValidator),
+            Address::with_validator(lower, &EvmValidator)
+        );
+        assert_eq!(
+            Address::with_validator(ok, &EvmValidator).unwrap().as_str(),
+            lower
+        );
+        assert_eq!(
+            Address::with_validator("alice", &EvmValidator),
+            Err(AddressError::InvalidFormat("0x prefix"))
+        );
+        assert_eq!(
+            Address::with_validator("0x12", &EvmValidator),
+            Err(AddressError::InvalidFormat("40 hex digits after 0x"))
+        );
+        assert_eq!(
+            Address::with_validator(format!("0x{}", "g".repeat(40)), &EvmValidator),
+            Err(AddressError::InvalidCharacter {
+                index: 2,
+                found: 'g'
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_str_and_display_round_trip() {
+        let address: Address = "bob".parse().unwrap();
+
+        assert_eq!(address.to_string(), "bob");
+        assert_eq!(format!("{address:?}"), "\"bob\"");
+    }
+}
@@ -0,0 +1,276 @@
+//! Token owner and the emergency pause.
+//!
+//! A token built with [`TokenState::new`] or [`TokenState::with_config`] is
+//! owned by its creator; states assembled from balances have no owner until
+//! [`TokenState::with_owner`] assigns one. Pausing and unpausing need
+//! [`Role::Pauser`], which the owner holds implicitly (see the `roles`
+//! module). Each call names its caller so the check happens inside the
+//! ledger rather than in every integration.
+//!
+//! Ownership moves in two steps: the owner nominates a successor with
+//! [`TokenState::transfer_ownership`], and nothing changes until that
+//! address calls [`TokenState::accept_ownership`], so a mistyped address
+//! can't take the token out of reach. Nominating again replaces the
+//! pending owner. [`TokenState::renounce_ownership`] leaves the token
+//! ownerless for good; explicit role grants survive it.
+//!
+//! While paused, operations initiated by holders (transfers, approvals and
+//! allowance changes, burns, shielding) fail with [`TokenError::Paused`].
+//! Owner and admin operations such as minting and configuration updates
+//! still run, so an incident can be remediated before unpausing.
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, Role, TokenError, TokenState};
+
+impl TokenState {
+    /// Sets the owner of a state that was built without one.
+    pub fn with_owner(mut self, owner: Address) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn owner(&self) -> Option<&Address> {
+        self.owner.as_ref()
+    }
+
+    /// Address nominated by [`TokenState::transfer_ownership`] that has not
+    /// accepted yet.
+    pub fn pending_owner(&self) -> Option<&Address> {
+        self.pending_owner.as_ref()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self, caller: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::Pause {
+            caller: caller.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub fn unpause(&mut self, caller: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::Unpause {
+            caller: caller.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// Nominates `new_owner`; ownership moves once they accept.
+    pub fn transfer_ownership(
+        &mut self,
+        caller: &Address,
+        new_owner: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::TransferOwnership {
+            caller: caller.clone(),
+            new_owner: new_owner.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// Completes a transfer; `caller` must be the pending owner.
+    pub fn accept_ownership(&mut self, caller: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::AcceptOwnership {
+            caller: caller.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// Gives up ownership permanently and cancels any pending transfer.
+    pub fn renounce_ownership(&mut self, caller: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::RenounceOwnership {
+            caller: caller.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub(crate) fn apply_set_paused(
+        &mut self,
+        caller: &Address,
+        paused: bool,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Pauser)?;
+        // 이미 같은 상태면 이벤트 없이 성공 (멱등)
+        if self.paused != paused {
+            self.paused = paused;
+            let by = caller.clone();
+            self.emit(if paused {
+                TokenEvent::Paused { by }
+            } else {
+                TokenEvent::Unpaused { by }
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply_transfer_ownership(
+        &mut self,
+        caller: &Address,
+        new_owner: &Address,
+    ) -> Result<(), TokenError> {
+        self.check_owner(caller)?;
+        self.pending_owner = Some(new_owner.clone());
+        self.emit(TokenEvent::OwnershipTransferStarted {
+            owner: caller.clone(),
+            pending: new_owner.clone(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn apply_accept_ownership(&mut self, caller: &Address) -> Result<(), TokenError> {
+        if self.pending_owner.as_ref() != Some(caller) {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        self.pending_owner = None;
+        // 대기 중인 owner가 있으면 현재 owner도 반드시 있음 (renounce가 둘 다 비움)
+        if let Some(previous) = self.owner.replace(caller.clone()) {
+            self.emit(TokenEvent::OwnershipTransferred {
+                previous,
+                owner: caller.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn apply_renounce_ownership(&mut self, caller: &Address) -> Result<(), TokenError> {
+        self.check_owner(caller)?;
+        self.owner = None;
+        self.pending_owner = None;
+        self.emit(TokenEvent::OwnershipRenounced {
+            previous: caller.clone(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn check_owner(&self, caller: &Address) -> Result<(), TokenError> {
+        if self.owner.as_ref() == Some(caller) {
+            Ok(())
+        } else {
+            Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            })
+        }
+    }
+
+    pub(crate) fn check_not_paused(&self, op: &Operation) -> Result<(), TokenError> {
+        let holder_op = matches!(
+            op,
+            Operation::Transfer { .. }
+                | Operation::NoncedTransfer { .. }
+                | Operation::TransferFrom { .. }
+                | Operation::TransferFromBatch { .. }
+                | Operation::Approve { .. }
+                | Operation::Permit { .. }
+                | Operation::ApproveUntil { .. }
+                | Operation::ApproveIf { .. }
+                | Operation::IncreaseAllowance { .. }
+                | Operation::DecreaseAllowance { .. }
+                | Operation::GrantCapability { .. }
+                | Operation::RedeemCapability { .. }
+                | Operation::Burn { .. }
+                | Operation::BurnFrom { .. }
+                | Operation::Shield { .. }
+                | Operation::Unshield { .. }
+                | Operation::Lock { .. }
+                | Operation::Unlock { .. }
+                | Operation::EscrowCreate { .. }
+                | Operation::EscrowRelease { .. }
+                | Operation::EscrowRefund { .. }
+        );
+        if self.paused && holder_op {
+            return Err(TokenError::Paused);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_blocks_holder_operations() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 100).unwrap();
+
+        token.pause(&alice).unwrap();
+
+        assert!(token.is_paused());
+        assert_eq!(token.transfer(&alice, &bob, 1), Err(TokenError::Paused));
+        assert_eq!(
+            token.transfer_from(&bob, &alice, &bob, 1),
+            Err(TokenError::Paused)
+        );
+        assert_eq!(token.approve(&alice, &bob, 5), Err(TokenError::Paused));
+        token.mint(&alice, &bob, 10).unwrap();
+
+        token.unpause(&alice).unwrap();
+        token.transfer(&alice, &bob, 1).unwrap();
+        assert_eq!(token.balance_of(&bob), 11);
+    }
+
+    #[test]
+    fn test_only_owner_can_pause() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let mut ownerless = TokenState::from_balances([(alice.clone(), 10)]).unwrap();
+
+        let result = token.pause(&bob);
+
+        assert_eq!(
+            result,
+            Err(TokenError::Unauthorized {
+                caller: bob.clone()
+            })
+        );
+        assert!(!token.is_paused());
+        assert!(ownerless.pause(&alice).is_err());
+    }
+
+    #[test]
+    fn test_transfer_ownership_needs_acceptance() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.transfer_ownership(&alice, &bob).unwrap();
+        let owner_before_accept = token.owner().cloned();
+        let wrong_acceptor = token.accept_ownership(&carol);
+        token.accept_ownership(&bob).unwrap();
+
+        assert_eq!(owner_before_accept, Some(alice.clone()));
+        assert!(wrong_acceptor.is_err());
+        assert_eq!(token.owner(), Some(&bob));
+        assert_eq!(token.pending_owner(), None);
+        assert!(token.pause(&alice).is_err());
+        token.pause(&bob).unwrap();
+        assert_eq!(
+            token.events().last().unwrap().1,
+            TokenEvent::Paused { by: bob.clone() }
+        );
+    }
+
+    #[test]
+    fn test_renounce_ownership() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer_ownership(&alice, &bob).unwrap();
+
+        token.renounce_ownership(&alice).unwrap();
+
+        assert_eq!(token.owner(), None);
+        assert!(token.accept_ownership(&bob).is_err());
+        assert!(token.pause(&alice).is_err());
+        assert!(token.renounce_ownership(&alice).is_err());
+    }
+}
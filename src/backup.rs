@@ -0,0 +1,306 @@
+//! Verifiable point-in-time backups.
+//!
+//! [`TokenState::backup`] writes a directory holding the binary snapshot
+//! (`state.bin`) and a `MANIFEST` that records its SHA-256 checksum along
+//! with the state hash and balance root of the ledger it came from:
+//!
+//! ```text
+//! format_version = 2
+//! seq = 1042
+//! checksum = 9f86d081…
+//! state_hash = 2c26b46b…
+//! balance_root = fcde2b2e…
+//! ```
+//!
+//! [`verify_backup`] checks the snapshot against its checksum and the
+//! format version in its header against the manifest, without decoding
+//! it; [`TokenState::restore_backup`] also decodes the snapshot and
+//! confirms that the restored ledger reproduces both recorded roots. The
+//! manifest is written last, so a directory without one is an interrupted
+//! backup and fails verification.
+//!
+//! The `backup` binary wraps these for operators.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::TokenState;
+use crate::codec::{self, FORMAT_VERSION, SnapshotError};
+use crate::merkle::Hash;
+use crate::sha256::Sha256;
+
+const STATE_FILE: &str = "state.bin";
+const MANIFEST_FILE: &str = "MANIFEST";
+
+/// What a backup directory claims to contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Binary snapshot format of `state.bin`
+    pub format_version: u16,
+    /// Sequence number the ledger had reached
+    pub seq: u64,
+    /// SHA-256 of `state.bin`
+    pub checksum: Hash,
+    /// [`TokenState::state_hash`] at backup time
+    pub state_hash: Hash,
+    /// [`TokenState::balance_root`] at backup time
+    pub balance_root: Hash,
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "format_version = {}", self.format_version)?;
+        writeln!(f, "seq = {}", self.seq)?;
+        writeln!(f, "checksum = {}", hex(&self.checksum))?;
+        writeln!(f, "state_hash = {}", hex(&self.state_hash))?;
+        writeln!(f, "balance_root = {}", hex(&self.balance_root))
+    }
+}
+
+impl std::str::FromStr for Manifest {
+    type Err = BackupError;
+
+    fn from_str(text: &str) -> Result<Self, BackupError> {
+        let mut fields = std::collections::HashMap::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| BackupError::Manifest(format!("malformed line {line:?}")))?;
+            fields.insert(key.trim(), value.trim());
+        }
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .copied()
+                .ok_or_else(|| BackupError::Manifest(format!("missing {key}")))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .parse::<u64>()
+                .map_err(|e| BackupError::Manifest(format!("{key}: {e}")))
+        };
+        let hash = |key: &str| {
+            unhex(field(key)?).ok_or_else(|| BackupError::Manifest(format!("{key}: bad digest")))
+        };
+
+        Ok(Self {
+            format_version: u16::try_from(number("format_version")?)
+                .map_err(|e| BackupError::Manifest(format!("format_version: {e}")))?,
+            seq: number("seq")?,
+            checksum: hash("checksum")?,
+            state_hash: hash("state_hash")?,
+            balance_root: hash("balance_root")?,
+        })
+    }
+}
+
+/// Why a backup could not be written, verified, or restored.
+#[derive(Debug)]
+pub enum BackupError {
+    Io(io::Error),
+    /// The manifest is missing a field or could not be parsed
+    Manifest(String),
+    /// `state.bin` does not match the manifest's checksum
+    ChecksumMismatch,
+    /// `state.bin` is in a different format version than the manifest says
+    VersionMismatch {
+        manifest: u16,
+        snapshot: u16,
+    },
+    /// The snapshot decoded but does not reproduce the recorded roots
+    RootMismatch,
+    /// The snapshot matched its checksum but does not decode
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "backup i/o: {e}"),
+            Self::Manifest(message) => write!(f, "bad manifest: {message}"),
+            Self::ChecksumMismatch => f.write_str("snapshot does not match its checksum"),
+            Self::VersionMismatch { manifest, snapshot } => write!(
+                f,
+                "manifest says format version {manifest}, snapshot is version {snapshot}"
+            ),
+            Self::RootMismatch => f.write_str("restored state does not match the recorded roots"),
+            Self::Snapshot(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<io::Error> for BackupError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl TokenState {
+    /// Writes a verifiable backup of the current state into `dir`.
+    pub fn backup(&self, dir: impl AsRef<Path>) -> Result<Manifest, BackupError> {
+        let dir = dir.as_ref();
+        let bytes = self.to_bytes();
+        let manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            seq: self.next_seq(),
+            checksum: digest(&bytes),
+            state_hash: self.state_hash(),
+            balance_root: self.balance_root(),
+        };
+
+        fs::create_dir_all(dir)?;
+        // 매니페스트를 마지막에 써서 중단된 백업은 검증에 실패하게 함
+        let _ = fs::remove_file(dir.join(MANIFEST_FILE));
+        fs::write(dir.join(STATE_FILE), &bytes)?;
+        fs::write(dir.join(MANIFEST_FILE), manifest.to_string())?;
+        Ok(manifest)
+    }
+
+    /// Verifies the backup in `dir` and restores it into a new ledger.
+    pub fn restore_backup(dir: impl AsRef<Path>) -> Result<TokenState, BackupError> {
+        let (manifest, bytes) = read_verified(dir.as_ref())?;
+        let state = TokenState::from_bytes(&bytes).map_err(BackupError::Snapshot)?;
+        if state.state_hash() != manifest.state_hash
+            || state.balance_root() != manifest.balance_root
+        {
+            return Err(BackupError::RootMismatch);
+        }
+        Ok(state)
+    }
+}
+
+/// Checks the backup in `dir` against its manifest without restoring it.
+pub fn verify_backup(dir: impl AsRef<Path>) -> Result<Manifest, BackupError> {
+    read_verified(dir.as_ref()).map(|(manifest, _)| manifest)
+}
+
+fn read_verified(dir: &Path) -> Result<(Manifest, Vec<u8>), BackupError> {
+    let manifest: Manifest = fs::read_to_string(dir.join(MANIFEST_FILE))?.parse()?;
+    let bytes = fs::read(dir.join(STATE_FILE))?;
+    if digest(&bytes) != manifest.checksum {
+        return Err(BackupError::ChecksumMismatch);
+    }
+    let snapshot = codec::header_version(&bytes).map_err(BackupError::Snapshot)?;
+    if snapshot != manifest.format_version {
+        return Err(BackupError::VersionMismatch {
+            manifest: manifest.format_version,
+            snapshot,
+        });
+    }
+    Ok((manifest, bytes))
+}
+
+fn digest(bytes: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+fn hex(hash: &Hash) -> String {
+    hash.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Hash> {
+    if text.len() != 64 || !text.is_ascii() {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("token-standard-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_backup_verify_restore() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 10).unwrap();
+        let dir = scratch("backup");
+
+        let manifest = token.backup(&dir).unwrap();
+        let verified = verify_backup(&dir).unwrap();
+        let restored = TokenState::restore_backup(&dir).unwrap();
+
+        assert_eq!(verified, manifest);
+        assert_eq!(manifest.seq, 1);
+        assert_eq!(restored.state_hash(), token.state_hash());
+        assert_eq!(restored.balance_of(&bob), 10);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let alice = Address::new("alice").unwrap();
+        let token = TokenState::new(alice.clone(), 1000);
+        let dir = scratch("tampered");
+        token.backup(&dir).unwrap();
+        let mut bytes = fs::read(dir.join(STATE_FILE)).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(dir.join(STATE_FILE), bytes).unwrap();
+
+        let result = verify_backup(&dir);
+
+        assert!(matches!(result, Err(BackupError::ChecksumMismatch)));
+        fs::remove_file(dir.join(MANIFEST_FILE)).unwrap();
+        assert!(matches!(verify_backup(&dir), Err(BackupError::Io(_))));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_version_must_match_snapshot() {
+        let alice = Address::new("alice").unwrap();
+        let token = TokenState::new(alice, 1000);
+        let dir = scratch("version");
+        let mut manifest = token.backup(&dir).unwrap();
+        manifest.format_version = 1;
+        fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+
+        let result = verify_backup(&dir);
+
+        assert!(matches!(
+            result,
+            Err(BackupError::VersionMismatch {
+                manifest: 1,
+                snapshot: FORMAT_VERSION
+            })
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_text_round_trip() {
+        let manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            seq: 7,
+            checksum: [1; 32],
+            state_hash: [0xab; 32],
+            balance_root: [0; 32],
+        };
+
+        let parsed: Manifest = manifest.to_string().parse().unwrap();
+
+        assert_eq!(parsed, manifest);
+        assert!(matches!(
+            "seq = 7".parse::<Manifest>(),
+            Err(BackupError::Manifest(_))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! All-or-nothing batches of delegated transfers.
+//!
+//! A payment processor holding allowances from many customers can settle
+//! all of their debits in one [`TokenState::transfer_from_batch`] call.
+//! Each leg is checked exactly as a lone [`TokenState::transfer_from`]
+//! would be, in order, so two legs drawing on the same allowance or balance
+//! see each other's effects. If any leg fails the whole batch is rolled
+//! back, no events are delivered, and the error names the failing leg.
+//!
+//! The batch runs as one [`Operation`]: interceptors see it once, it takes
+//! one sequence number, and pause and freeze checks cover every leg.
+
+use crate::operation::Operation;
+use crate::{Address, Balance, Settlement, TokenError, TokenState};
+
+impl TokenState {
+    /// Applies `legs` of `(from, to, amount)` as `spender`, atomically.
+    ///
+    /// Fails with [`TokenError::BatchLegFailed`] carrying the index and
+    /// error of the first leg that could not be applied.
+    pub fn transfer_from_batch(
+        &mut self,
+        spender: &Address,
+        legs: &[(Address, Address, Balance)],
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::TransferFromBatch {
+            spender: spender.clone(),
+            legs: legs.to_vec(),
+        })
+        .map(|_| ())
+    }
+
+    pub(crate) fn apply_transfer_from_batch(
+        &mut self,
+        spender: &Address,
+        legs: &[(Address, Address, Balance)],
+    ) -> Result<Settlement, TokenError> {
+        let mut undo = self.begin_undo();
+        let mut total = Settlement::default();
+        for (index, (from, to, amount)) in legs.iter().enumerate() {
+            match undo.apply_transfer_from(spender, from, to, *amount) {
+                Ok(leg) => {
+                    total.moved = total.moved.saturating_add(leg.moved);
+                    total.credited = total.credited.saturating_add(leg.credited);
+                }
+                Err(error) => {
+                    undo.rollback();
+                    return Err(TokenError::BatchLegFailed {
+                        index,
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+        undo.commit()?;
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{addresses, record_events};
+
+    fn setup() -> (TokenState, [Address; 4]) {
+        let [alice, bob, carol, processor] = addresses(["alice", "bob", "carol", "processor"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 300).unwrap();
+        token.approve(&alice, &processor, 100).unwrap();
+        token.approve(&bob, &processor, 100).unwrap();
+        (token, [alice, bob, carol, processor])
+    }
+
+    #[test]
+    fn test_batch_settles_every_leg() {
+        let (mut token, [alice, bob, carol, processor]) = setup();
+
+        token
+            .transfer_from_batch(
+                &processor,
+                &[
+                    (alice.clone(), carol.clone(), 60),
+                    (bob.clone(), carol.clone(), 70),
+                    (alice.clone(), carol.clone(), 40),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(token.balance_of(&carol), 170);
+        assert_eq!(token.allowance(&alice, &processor), 0);
+        assert_eq!(token.allowance(&bob, &processor), 30);
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_failed_leg_rolls_back_the_batch() {
+        let (mut token, [alice, bob, carol, processor]) = setup();
+        let heard = record_events(&mut token);
+        let events_before = token.events().len();
+        let seq_before = token.next_seq();
+
+        // 세 번째 leg에서 alice의 allowance(100)가 부족해짐
+        let result = token.transfer_from_batch(
+            &processor,
+            &[
+                (alice.clone(), carol.clone(), 60),
+                (bob.clone(), carol.clone(), 70),
+                (alice.clone(), carol.clone(), 41),
+            ],
+        );
+
+        assert_eq!(
+            result,
+            Err(TokenError::BatchLegFailed {
+                index: 2,
+                error: Box::new(TokenError::InsufficientAllowance {
+                    required: 41,
+                    available: 40
+                }),
+            })
+        );
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.balance_of(&bob), 300);
+        assert_eq!(token.balance_of(&carol), 0);
+        assert_eq!(token.allowance(&alice, &processor), 100);
+        assert_eq!(token.events().len(), events_before);
+        assert_eq!(token.next_seq(), seq_before);
+        assert!(heard.borrow().is_empty());
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+}
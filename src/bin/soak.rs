@@ -0,0 +1,78 @@
+//! Unattended consistency soak.
+//!
+//! ```text
+//! cargo run --release --bin soak -- --seed 7 --secs 3600
+//! ```
+//!
+//! Options: `--seed`, `--accounts`, `--ops`, `--secs`, `--check-every`,
+//! `--reconcile-every`. On failure the reproducing operation prefix is
+//! minimized and written to `soak-<seed>.rs` as a regression test ready to
+//! drop into `tests/`, and the process exits with status 1.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use token_standard::shrink;
+use token_standard::soak::{self, SoakConfig};
+
+fn main() -> ExitCode {
+    let config = match parse_args() {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("soak: {message}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match soak::run(&config) {
+        Ok(report) => {
+            println!(
+                "seed {}: {} ops, {} succeeded, no violations",
+                config.seed, report.ops, report.succeeded
+            );
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            eprintln!(
+                "seed {}: violation after {} ops: {}",
+                config.seed, failure.ops, failure.detail
+            );
+            let ops = shrink::shrink_soak(&config, failure.reproducer());
+            eprintln!("minimized to {} ops", ops.len());
+            let name = format!("soak_regression_seed_{}", config.seed);
+            let path = format!("soak-{}.rs", config.seed);
+            match std::fs::write(&path, shrink::regression_test(&name, &config, &ops)) {
+                Ok(()) => eprintln!("regression test written to {path}"),
+                Err(e) => eprintln!("could not write {path}: {e}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args() -> Result<SoakConfig, String> {
+    let mut config = SoakConfig {
+        max_ops: u64::MAX,
+        ..SoakConfig::default()
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+        let number: u64 = value
+            .parse()
+            .map_err(|_| format!("{flag}: not a number: {value}"))?;
+        match flag.as_str() {
+            "--seed" => config.seed = number,
+            "--accounts" => config.accounts = number as usize,
+            "--ops" => config.max_ops = number,
+            "--secs" => config.duration = Some(Duration::from_secs(number)),
+            "--check-every" => config.check_every = number,
+            "--reconcile-every" => config.reconcile_every = number,
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+    if config.max_ops == u64::MAX && config.duration.is_none() {
+        config.max_ops = SoakConfig::default().max_ops;
+    }
+    Ok(config)
+}
@@ -0,0 +1,11 @@
+//! Prints the ledger's transition-system description as JSON.
+//!
+//! ```text
+//! cargo run --bin spec > token-spec.json
+//! ```
+
+use token_standard::spec;
+
+fn main() {
+    print!("{}", spec::to_json(&spec::transition_system()));
+}
@@ -0,0 +1,102 @@
+//! Introspection of which optional behaviors a token has switched on.
+//!
+//! Wallets and API layers use this to adapt to a token's configuration
+//! without reaching into [`TokenConfig`](crate::TokenConfig) field by field.
+//! Only behaviors the crate actually implements are reported; new fields are
+//! added as features land.
+
+use crate::TokenState;
+
+/// Snapshot of the optional behaviors active on a [`TokenState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    /// A proof verifier is registered, so shielded notes can be redeemed
+    pub shielding: bool,
+    /// An existential deposit is enforced and dust accounts are reaped
+    pub existential_deposit: bool,
+    /// Transfers that would leave dust sweep the whole balance instead
+    pub dust_sweep: bool,
+    /// At least one burn address destroys incoming transfers
+    pub burn_addresses: bool,
+    /// Per-address volume counters are compiled in (`account-stats`)
+    pub account_stats: bool,
+    /// Interceptors are registered and may veto operations
+    pub interceptors: bool,
+    /// Transfer hooks are registered and may veto transfers
+    pub transfer_hooks: bool,
+    /// Tokens cannot be transferred, only minted and burned
+    pub soulbound: bool,
+    /// Configuration updates are queued and wait out a delay
+    pub config_timelock: bool,
+}
+
+impl TokenState {
+    pub fn capabilities(&self) -> Capabilities {
+        let dust_rules = self.config.existential_deposit > 0;
+        Capabilities {
+            shielding: self.proof_verifier.is_some(),
+            existential_deposit: dust_rules,
+            dust_sweep: dust_rules && self.config.dust_policy == crate::DustPolicy::Sweep,
+            burn_addresses: !self.config.burn_addresses.is_empty(),
+            account_stats: cfg!(feature = "account-stats"),
+            interceptors: !self.interceptors.is_empty(),
+            transfer_hooks: !self.transfer_hooks.is_empty(),
+            soulbound: self.config.soulbound,
+            config_timelock: self.config.update_delay > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AcceptAllVerifier, Address, DustPolicy, TokenConfig};
+
+    #[test]
+    fn test_default_token_has_no_optional_behaviors() {
+        let token = TokenState::new(Address::new("alice").unwrap(), 1000);
+
+        let caps = token.capabilities();
+
+        assert_eq!(
+            caps,
+            Capabilities {
+                account_stats: cfg!(feature = "account-stats"),
+                ..Capabilities::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_capabilities_follow_configuration() {
+        let config = TokenConfig {
+            existential_deposit: 10,
+            dust_policy: DustPolicy::Sweep,
+            burn_addresses: [Address::new(crate::BURN_ADDRESS).unwrap()].into(),
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(Address::new("alice").unwrap(), 1000, config);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+
+        let caps = token.capabilities();
+
+        assert!(caps.shielding);
+        assert!(caps.existential_deposit);
+        assert!(caps.dust_sweep);
+        assert!(caps.burn_addresses);
+        assert!(!caps.interceptors);
+        assert!(!caps.transfer_hooks);
+    }
+
+    #[test]
+    fn test_sweep_without_deposit_is_not_reported() {
+        let config = TokenConfig {
+            dust_policy: DustPolicy::Sweep,
+            ..TokenConfig::default()
+        };
+        let token = TokenState::with_config(Address::new("alice").unwrap(), 1000, config);
+
+        assert!(!token.capabilities().dust_sweep);
+    }
+}
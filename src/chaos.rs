@@ -0,0 +1,257 @@
+//! Fault-injection wrapper for resilience testing.
+//!
+//! [`Chaos`] wraps any [`FungibleToken`] and, driven by a seeded RNG, makes
+//! mutating operations fail with [`TokenError::InjectedFault`], stall for a
+//! configured delay, or get deferred and later applied in shuffled order.
+//! Reads always pass straight through to the inner token.
+
+use std::thread;
+use std::time::Duration;
+
+use crate::rng::Rng;
+use crate::{Address, Balance, FungibleToken, TokenError};
+
+/// Probabilities (0.0 ..= 1.0) of each kind of misbehavior per mutation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChaosConfig {
+    /// Chance the operation is rejected with `InjectedFault`
+    pub fail_rate: f64,
+    /// Chance the operation sleeps for `delay` before running
+    pub delay_rate: f64,
+    pub delay: Duration,
+    /// Chance the operation is acknowledged but deferred to the next flush
+    pub reorder_rate: f64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            fail_rate: 0.0,
+            delay_rate: 0.0,
+            delay: Duration::from_millis(10),
+            reorder_rate: 0.0,
+        }
+    }
+}
+
+enum Deferred {
+    Transfer(Address, Address, Balance),
+    Approve(Address, Address, Balance),
+    TransferFrom(Address, Address, Address, Balance),
+}
+
+pub struct Chaos<T: FungibleToken> {
+    inner: T,
+    config: ChaosConfig,
+    rng: Rng,
+    pending: Vec<Deferred>,
+    deferred_errors: Vec<TokenError>,
+}
+
+impl<T: FungibleToken> Chaos<T> {
+    pub fn new(inner: T, config: ChaosConfig, seed: u64) -> Self {
+        Self {
+            inner,
+            config,
+            rng: Rng::new(seed),
+            pending: Vec::new(),
+            deferred_errors: Vec::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(mut self) -> T {
+        self.flush();
+        self.inner
+    }
+
+    /// Number of acknowledged operations not yet applied.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Applies deferred operations in shuffled order.
+    ///
+    /// Their callers already saw `Ok(())`; failures are collected and can be
+    /// read with [`Chaos::take_deferred_errors`].
+    pub fn flush(&mut self) {
+        let mut pending = std::mem::take(&mut self.pending);
+        self.rng.shuffle(&mut pending);
+
+        for op in pending {
+            let result = match op {
+                Deferred::Transfer(from, to, amount) => self.inner.transfer(&from, &to, amount),
+                Deferred::Approve(owner, spender, amount) => {
+                    self.inner.approve(&owner, &spender, amount)
+                }
+                Deferred::TransferFrom(spender, from, to, amount) => {
+                    self.inner.transfer_from(&spender, &from, &to, amount)
+                }
+            };
+            if let Err(e) = result {
+                self.deferred_errors.push(e);
+            }
+        }
+    }
+
+    pub fn take_deferred_errors(&mut self) -> Vec<TokenError> {
+        std::mem::take(&mut self.deferred_errors)
+    }
+
+    // 실패/지연/재정렬 중 무엇을 할지 결정. Some(result)면 inner를 호출하지 않음
+    fn disrupt(&mut self, op: impl FnOnce() -> Deferred) -> Option<Result<(), TokenError>> {
+        if self.rng.next_f64() < self.config.fail_rate {
+            return Some(Err(TokenError::InjectedFault));
+        }
+        if self.rng.next_f64() < self.config.delay_rate {
+            thread::sleep(self.config.delay);
+        }
+        if self.rng.next_f64() < self.config.reorder_rate {
+            self.pending.push(op());
+            return Some(Ok(()));
+        }
+        None
+    }
+}
+
+impl<T: FungibleToken> FungibleToken for Chaos<T> {
+    fn total_supply(&self) -> Balance {
+        self.inner.total_supply()
+    }
+
+    fn balance_of(&self, address: &Address) -> Balance {
+        self.inner.balance_of(address)
+    }
+
+    fn transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if let Some(result) = self.disrupt(|| Deferred::Transfer(from.clone(), to.clone(), amount))
+        {
+            return result;
+        }
+        self.inner.transfer(from, to, amount)
+    }
+
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if let Some(result) =
+            self.disrupt(|| Deferred::Approve(owner.clone(), spender.clone(), amount))
+        {
+            return result;
+        }
+        self.inner.approve(owner, spender, amount)
+    }
+
+    fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        self.inner.allowance(owner, spender)
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if let Some(result) = self
+            .disrupt(|| Deferred::TransferFrom(spender.clone(), from.clone(), to.clone(), amount))
+        {
+            return result;
+        }
+        self.inner.transfer_from(spender, from, to, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenState;
+
+    #[test]
+    fn test_no_chaos_passes_through() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = Chaos::new(
+            TokenState::new(alice.clone(), 1000),
+            ChaosConfig::default(),
+            1,
+        );
+
+        token.transfer(&alice, &bob, 100).unwrap();
+
+        assert_eq!(token.balance_of(&bob), 100);
+        assert_eq!(token.pending(), 0);
+    }
+
+    #[test]
+    fn test_always_fail() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = ChaosConfig {
+            fail_rate: 1.0,
+            ..ChaosConfig::default()
+        };
+        let mut token = Chaos::new(TokenState::new(alice.clone(), 1000), config, 1);
+
+        let result = token.transfer(&alice, &bob, 100);
+
+        assert_eq!(result.unwrap_err(), TokenError::InjectedFault);
+        assert_eq!(token.balance_of(&alice), 1000);
+    }
+
+    #[test]
+    fn test_reorder_defers_until_flush() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = ChaosConfig {
+            reorder_rate: 1.0,
+            ..ChaosConfig::default()
+        };
+        let mut token = Chaos::new(TokenState::new(alice.clone(), 1000), config, 1);
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.transfer(&alice, &bob, 5000).unwrap();
+        assert_eq!(token.pending(), 2);
+        assert_eq!(token.balance_of(&bob), 0);
+
+        token.flush();
+
+        assert_eq!(token.balance_of(&bob), 100);
+        assert_eq!(
+            token.take_deferred_errors(),
+            vec![TokenError::InsufficientBalance {
+                required: 5000,
+                available: 900
+            }]
+        );
+    }
+
+    #[test]
+    fn test_fault_pattern_is_seeded() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = ChaosConfig {
+            fail_rate: 0.5,
+            ..ChaosConfig::default()
+        };
+        let run = |seed| {
+            let mut token = Chaos::new(TokenState::new(alice.clone(), 1000), config.clone(), seed);
+            (0..32)
+                .map(|_| token.transfer(&alice, &bob, 1).is_ok())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(11), run(11));
+    }
+}
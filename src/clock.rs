@@ -0,0 +1,116 @@
+//! Time source for deadlines and expiries.
+//!
+//! The ledger reads the current time only through a [`Clock`], injected
+//! with [`TokenState::with_clock`] or [`TokenState::set_clock`]. Permit
+//! deadlines, capability and allowance expiries all go through it. The unit
+//! is whatever the clock says it is: [`SystemClock`], the default, counts
+//! Unix seconds, while a chain integration can report block heights
+//! instead, as long as deadlines are written in the same unit.
+//!
+//! [`FixedClock`] keeps tests and simulations deterministic. A
+//! [`ManualClock`] is moved by its owner after the ledger has taken it, so
+//! a host can advance it once per block. Like other runtime attachments,
+//! the clock is not saved in snapshots.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::TokenState;
+
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+/// Seconds since the Unix epoch, from the system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// A clock stopped at one instant.
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A clock set from outside, such as by a host chain's block number.
+///
+/// Clones share one time, so keep a clone after handing one to the ledger.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Rc<Cell<u64>>);
+
+impl ManualClock {
+    pub fn new(now: u64) -> Self {
+        Self(Rc::new(Cell::new(now)))
+    }
+
+    pub fn set(&self, now: u64) {
+        self.0.set(now);
+    }
+
+    /// Moves the clock forward by `by`, saturating at `u64::MAX`.
+    pub fn advance(&self, by: u64) {
+        self.0.set(self.0.get().saturating_add(by));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl TokenState {
+    /// Builder form of [`TokenState::set_clock`].
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, TokenError};
+
+    #[test]
+    fn test_host_advances_shared_clock() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let height = ManualClock::new(10);
+        let mut token = TokenState::new(alice.clone(), 1000).with_clock(Box::new(height.clone()));
+        token.approve_until(&alice, &bob, 50, 12).unwrap();
+
+        height.advance(2);
+        let at_deadline = token.allowance(&alice, &bob);
+        height.set(13);
+        let late = token.transfer_from(&bob, &alice, &bob, 1);
+
+        assert_eq!(at_deadline, 50);
+        assert_eq!(token.now(), 13);
+        assert_eq!(
+            late,
+            Err(TokenError::AllowanceExpired {
+                expires_at: 12,
+                now: 13
+            })
+        );
+    }
+}
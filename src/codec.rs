@@ -0,0 +1,1380 @@
+//! Compact binary snapshots for runtimes that can't afford JSON.
+//!
+//! [`TokenState::to_bytes`] writes the same image as the serde impls (see
+//! the `snapshot` module) in a hand-rolled little-endian format:
+//!
+//! ```text
+//! magic    b"TKST"
+//! version  u16
+//! width    u8      bytes per Balance value (8 or 16)
+//! body     see `encode_body`
+//! ```
+//!
+//! Recording the balance width lets a `u128-balance` build read snapshots
+//! written by a 64-bit build, and the reverse as long as every value fits.
+//! The body layout is identical with and without optional features; data
+//! for a feature that is compiled out is read and discarded.
+//!
+//! Every change to the body layout bumps [`FORMAT_VERSION`], and the
+//! reader keeps decoding each older version, upgrading it to the current
+//! state with defaults for what that version did not record (a v1
+//! approval event reads as replacing an allowance of 0):
+//!
+//! ```text
+//! 1  config, balances, allowances, shielded pool, non-circulating set,
+//!    events, metadata, account stats
+//! 2  adds the previous allowance to approval events, fee policy and
+//!    soulbound flag to the config, new event kinds, then ownership,
+//!    pause, freezes, roles, supply cap, nonces, capabilities, allowance
+//!    expiries, locks and escrows after the stats section
+//! 3  adds the update delay to the config, events for queued config
+//!    updates, then the queued updates themselves after the escrows
+//! ```
+//!
+//! A snapshot with a version outside that range is rejected with
+//! [`SnapshotError::UnsupportedVersion`] rather than guessed at. Images
+//! from development builds that extended the body while still writing
+//! version 1 are not readable and fail to decode.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use crate::events::TokenEvent;
+use crate::ocap::{CapabilityGrant, CapabilityId};
+use crate::snapshot::{AllowanceRecord, Snapshot};
+use crate::{
+    Address, AddressError, Balance, ConfigUpdateId, DustPolicy, EdgeCaseMode, Escrow, EscrowId,
+    FeeDestination, FeePolicy, FeeTier, Lock, QueuedConfig, Role, TokenConfig, TokenMetadata,
+    TokenState,
+};
+
+const MAGIC: &[u8; 4] = b"TKST";
+
+/// Version written by [`TokenState::to_bytes`].
+pub const FORMAT_VERSION: u16 = 3;
+
+/// Oldest version [`TokenState::from_bytes`] still reads.
+pub const OLDEST_FORMAT_VERSION: u16 = 1;
+
+/// Why a byte string could not be restored as a [`TokenState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The input does not start with the snapshot magic
+    BadMagic,
+    /// Written by a different format version
+    UnsupportedVersion(u16),
+    /// The header's balance width is neither 8 nor 16
+    UnsupportedBalanceWidth(u8),
+    /// A stored amount does not fit this build's [`Balance`]
+    BalanceOverflow,
+    /// The input ended in the middle of a field
+    Truncated,
+    /// An enum discriminant outside the known range
+    InvalidTag(u8),
+    /// A stored address failed validation
+    InvalidAddress(AddressError),
+    /// A stored string is not valid UTF-8
+    InvalidText,
+    /// Bytes left over after the body
+    TrailingBytes,
+    /// The decoded state violates a ledger invariant
+    Inconsistent(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "not a token snapshot"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {v}"),
+            Self::UnsupportedBalanceWidth(w) => write!(f, "unsupported balance width {w}"),
+            Self::BalanceOverflow => write!(f, "amount does not fit this build's balance type"),
+            Self::Truncated => write!(f, "snapshot is truncated"),
+            Self::InvalidTag(tag) => write!(f, "invalid tag {tag}"),
+            Self::InvalidAddress(e) => write!(f, "invalid address: {e}"),
+            Self::InvalidText => write!(f, "invalid UTF-8 text"),
+            Self::TrailingBytes => write!(f, "trailing bytes after snapshot"),
+            Self::Inconsistent(detail) => write!(f, "{detail}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl TokenState {
+    /// Encodes the full ledger state in the current binary format.
+    ///
+    /// Interceptors, event sinks and the proof verifier are not included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut enc = Encoder(Vec::new());
+        enc.0.extend_from_slice(MAGIC);
+        enc.u16(FORMAT_VERSION);
+        enc.u8(size_of::<Balance>() as u8);
+        encode_body(&mut enc, &self.snapshot());
+        enc.0
+    }
+
+    /// Decodes a snapshot written by [`TokenState::to_bytes`] in this or
+    /// any older supported version, verifying every invariant before
+    /// returning.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TokenState, SnapshotError> {
+        let mut dec = Decoder {
+            bytes,
+            width: 0,
+            version: 0,
+        };
+        if dec.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        dec.version = dec.u16()?;
+        if !(OLDEST_FORMAT_VERSION..=FORMAT_VERSION).contains(&dec.version) {
+            return Err(SnapshotError::UnsupportedVersion(dec.version));
+        }
+        dec.width = match dec.u8()? {
+            w @ (8 | 16) => w as usize,
+            w => return Err(SnapshotError::UnsupportedBalanceWidth(w)),
+        };
+
+        let image = decode_body(&mut dec)?;
+        if !dec.bytes.is_empty() {
+            return Err(SnapshotError::TrailingBytes);
+        }
+        image.restore().map_err(SnapshotError::Inconsistent)
+    }
+}
+
+/// Format version recorded in a snapshot's header, without decoding it.
+pub(crate) fn header_version(bytes: &[u8]) -> Result<u16, SnapshotError> {
+    let mut dec = Decoder {
+        bytes,
+        width: 0,
+        version: 0,
+    };
+    if dec.take(MAGIC.len())? != MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+    dec.u16()
+}
+
+// 본문: encode_body와 decode_body의 섹션 순서는 항상 같아야 함
+fn encode_body(enc: &mut Encoder, image: &Snapshot) {
+    encode_config(enc, &image.config);
+
+    enc.balance(image.total_supply);
+    enc.len(image.balances.len());
+    for (address, balance) in &image.balances {
+        enc.address(address);
+        enc.balance(*balance);
+    }
+    enc.len(image.allowances.len());
+    for record in &image.allowances {
+        enc.address(&record.owner);
+        enc.address(&record.spender);
+        enc.balance(record.amount);
+        enc.balance(record.granted);
+        enc.balance(record.spent);
+    }
+
+    enc.balance(image.shielded_pool);
+    enc.u64(image.next_note_id);
+    enc.len(image.spent_notes.len());
+    image.spent_notes.iter().for_each(|id| enc.u64(*id));
+    enc.len(image.open_notes.len());
+    for (id, amount) in &image.open_notes {
+        enc.u64(*id);
+        enc.balance(*amount);
+    }
+    enc.len(image.non_circulating.len());
+    image.non_circulating.iter().for_each(|a| enc.address(a));
+
+    enc.u64(image.next_seq);
+    enc.len(image.events.len());
+    for (seq, event) in &image.events {
+        enc.u64(*seq);
+        encode_event(enc, event);
+    }
+
+    match &image.metadata {
+        Some(metadata) => {
+            enc.u8(1);
+            enc.string(&metadata.name);
+            enc.string(&metadata.symbol);
+            enc.u8(metadata.decimals);
+        }
+        None => enc.u8(0),
+    }
+
+    // 통계 섹션은 기능 비활성 시에도 빈 값으로 기록 → 형식이 기능과 무관
+    #[cfg(feature = "account-stats")]
+    {
+        enc.u64(image.stats.epoch);
+        enc.len(image.stats.accounts.len());
+        for (address, (epoch, stats)) in &image.stats.accounts {
+            enc.address(address);
+            enc.u64(*epoch);
+            enc.balance(stats.sent);
+            enc.balance(stats.received);
+            enc.balance(stats.epoch_sent);
+            enc.balance(stats.epoch_received);
+        }
+    }
+    #[cfg(not(feature = "account-stats"))]
+    {
+        enc.u64(0);
+        enc.len(0);
+    }
+
+    enc.optional_address(image.owner.as_ref());
+    enc.u8(u8::from(image.paused));
+
+    enc.len(image.frozen.len());
+    image.frozen.iter().for_each(|a| enc.address(a));
+
+    enc.len(image.roles.len());
+    for (account, role) in &image.roles {
+        enc.address(account);
+        enc.u8(role_tag(*role));
+    }
+
+    enc.optional_address(image.pending_owner.as_ref());
+
+    match image.max_supply {
+        Some(cap) => {
+            enc.u8(1);
+            enc.balance(cap);
+        }
+        None => enc.u8(0),
+    }
+
+    enc.len(image.nonces.len());
+    for (address, nonce) in &image.nonces {
+        enc.address(address);
+        enc.u64(*nonce);
+    }
+    match &image.domain_separator {
+        Some(separator) => {
+            enc.u8(1);
+            enc.bytes(separator);
+        }
+        None => enc.u8(0),
+    }
+
+    enc.u64(image.next_capability_id);
+    enc.len(image.capabilities.len());
+    for (id, grant) in &image.capabilities {
+        enc.u64(*id);
+        enc.address(&grant.issuer);
+        enc.balance(grant.remaining);
+        enc.len(grant.recipients.len());
+        grant.recipients.iter().for_each(|a| enc.address(a));
+        match grant.expires_at {
+            Some(expires_at) => {
+                enc.u8(1);
+                enc.u64(expires_at);
+            }
+            None => enc.u8(0),
+        }
+        enc.bytes(&grant.digest);
+    }
+
+    let expiring: Vec<_> = image
+        .allowances
+        .iter()
+        .filter_map(|r| Some((r, r.expires_at?)))
+        .collect();
+    enc.len(expiring.len());
+    for (record, expires_at) in expiring {
+        enc.address(&record.owner);
+        enc.address(&record.spender);
+        enc.u64(expires_at);
+    }
+
+    enc.len(image.locks.len());
+    for (owner, locks) in &image.locks {
+        enc.address(owner);
+        enc.len(locks.len());
+        for lock in locks {
+            enc.balance(lock.amount);
+            enc.u64(lock.unlock_at);
+        }
+    }
+
+    enc.u64(image.next_escrow_id);
+    enc.len(image.escrows.len());
+    for (id, escrow) in &image.escrows {
+        enc.u64(*id);
+        enc.address(&escrow.payer);
+        enc.address(&escrow.payee);
+        enc.balance(escrow.amount);
+    }
+
+    enc.u64(image.next_config_update_id);
+    enc.len(image.config_updates.len());
+    for (id, queued) in &image.config_updates {
+        enc.u64(*id);
+        enc.u64(queued.eta);
+        encode_config(enc, &queued.config);
+    }
+}
+
+fn decode_body(dec: &mut Decoder) -> Result<Snapshot, SnapshotError> {
+    let config = decode_config(dec)?;
+
+    let total_supply = dec.balance()?;
+    let mut balances = BTreeMap::new();
+    for _ in 0..dec.len()? {
+        balances.insert(dec.address()?, dec.balance()?);
+    }
+    let mut allowances = Vec::new();
+    for _ in 0..dec.len()? {
+        allowances.push(AllowanceRecord {
+            owner: dec.address()?,
+            spender: dec.address()?,
+            amount: dec.balance()?,
+            granted: dec.balance()?,
+            spent: dec.balance()?,
+            expires_at: None,
+        });
+    }
+
+    let shielded_pool = dec.balance()?;
+    let next_note_id = dec.u64()?;
+    let mut spent_notes = BTreeSet::new();
+    for _ in 0..dec.len()? {
+        spent_notes.insert(dec.u64()?);
+    }
+    let mut open_notes = BTreeMap::new();
+    for _ in 0..dec.len()? {
+        open_notes.insert(dec.u64()?, dec.balance()?);
+    }
+    let mut non_circulating = BTreeSet::new();
+    for _ in 0..dec.len()? {
+        non_circulating.insert(dec.address()?);
+    }
+
+    let next_seq = dec.u64()?;
+    let mut events = Vec::new();
+    for _ in 0..dec.len()? {
+        events.push((dec.u64()?, decode_event(dec)?));
+    }
+
+    let metadata = match dec.u8()? {
+        0 => None,
+        1 => Some(TokenMetadata {
+            name: dec.string()?,
+            symbol: dec.string()?,
+            decimals: dec.u8()?,
+        }),
+        tag => return Err(SnapshotError::InvalidTag(tag)),
+    };
+
+    let stats_epoch = dec.u64()?;
+    let mut stats_accounts = BTreeMap::new();
+    for _ in 0..dec.len()? {
+        let address = dec.address()?;
+        let epoch = dec.u64()?;
+        let stats = [
+            dec.balance()?,
+            dec.balance()?,
+            dec.balance()?,
+            dec.balance()?,
+        ];
+        stats_accounts.insert(address, (epoch, stats));
+    }
+    #[cfg(not(feature = "account-stats"))]
+    let _ = (stats_epoch, stats_accounts);
+
+    let mut image = Snapshot {
+        config,
+        total_supply,
+        balances,
+        allowances,
+        shielded_pool,
+        next_note_id,
+        spent_notes,
+        open_notes,
+        non_circulating,
+        next_seq,
+        events,
+        metadata,
+        #[cfg(feature = "account-stats")]
+        stats: crate::stats::StatsSnapshot {
+            epoch: stats_epoch,
+            accounts: stats_accounts
+                .into_iter()
+                .map(
+                    |(address, (epoch, [sent, received, epoch_sent, epoch_received]))| {
+                        let stats = crate::stats::AccountStats {
+                            sent,
+                            received,
+                            epoch_sent,
+                            epoch_received,
+                        };
+                        (address, (epoch, stats))
+                    },
+                )
+                .collect(),
+        },
+        owner: None,
+        pending_owner: None,
+        max_supply: None,
+        paused: false,
+        frozen: BTreeSet::new(),
+        roles: BTreeSet::new(),
+        nonces: BTreeMap::new(),
+        capabilities: BTreeMap::new(),
+        domain_separator: None,
+        next_capability_id: 0,
+        locks: BTreeMap::new(),
+        next_escrow_id: 0,
+        escrows: BTreeMap::new(),
+        next_config_update_id: 0,
+        config_updates: BTreeMap::new(),
+    };
+    // v1 본문은 통계 섹션에서, v2 본문은 에스크로 섹션에서 끝남:
+    // 이후 상태는 위의 기본값으로 올림
+    if dec.version >= 2 {
+        decode_v2_sections(dec, &mut image)?;
+    }
+    if dec.version >= 3 {
+        image.next_config_update_id = dec.u64()?;
+        for _ in 0..dec.len()? {
+            let id = dec.u64()?;
+            let eta = dec.u64()?;
+            let config = decode_config(dec)?;
+            image
+                .config_updates
+                .insert(id, QueuedConfig { config, eta });
+        }
+    }
+    Ok(image)
+}
+
+// v2에서 통계 섹션 뒤에 추가된 섹션들
+fn decode_v2_sections(dec: &mut Decoder, image: &mut Snapshot) -> Result<(), SnapshotError> {
+    image.owner = dec.optional_address()?;
+    image.paused = dec.flag()?;
+
+    for _ in 0..dec.len()? {
+        image.frozen.insert(dec.address()?);
+    }
+
+    for _ in 0..dec.len()? {
+        image.roles.insert((dec.address()?, role(dec.u8()?)?));
+    }
+
+    image.pending_owner = dec.optional_address()?;
+
+    image.max_supply = if dec.flag()? {
+        Some(dec.balance()?)
+    } else {
+        None
+    };
+
+    for _ in 0..dec.len()? {
+        image.nonces.insert(dec.address()?, dec.u64()?);
+    }
+    image.domain_separator = if dec.flag()? {
+        Some(dec.array()?)
+    } else {
+        None
+    };
+
+    image.next_capability_id = dec.u64()?;
+    for _ in 0..dec.len()? {
+        let id = dec.u64()?;
+        let issuer = dec.address()?;
+        let remaining = dec.balance()?;
+        let mut recipients = BTreeSet::new();
+        for _ in 0..dec.len()? {
+            recipients.insert(dec.address()?);
+        }
+        let expires_at = if dec.flag()? { Some(dec.u64()?) } else { None };
+        let grant = CapabilityGrant {
+            issuer,
+            remaining,
+            recipients,
+            expires_at,
+            digest: dec.array()?,
+        };
+        image.capabilities.insert(id, grant);
+    }
+
+    for _ in 0..dec.len()? {
+        let (owner, spender) = (dec.address()?, dec.address()?);
+        let expires_at = dec.u64()?;
+        let record = image
+            .allowances
+            .iter_mut()
+            .find(|r| r.owner == owner && r.spender == spender)
+            .ok_or_else(|| {
+                SnapshotError::Inconsistent(format!(
+                    "expiry for unknown allowance {owner}->{spender}"
+                ))
+            })?;
+        record.expires_at = Some(expires_at);
+    }
+
+    for _ in 0..dec.len()? {
+        let owner = dec.address()?;
+        let mut held = Vec::new();
+        for _ in 0..dec.len()? {
+            held.push(Lock {
+                amount: dec.balance()?,
+                unlock_at: dec.u64()?,
+            });
+        }
+        image.locks.insert(owner, held);
+    }
+
+    image.next_escrow_id = dec.u64()?;
+    for _ in 0..dec.len()? {
+        let id = dec.u64()?;
+        let escrow = Escrow {
+            payer: dec.address()?,
+            payee: dec.address()?,
+            amount: dec.balance()?,
+        };
+        image.escrows.insert(id, escrow);
+    }
+    Ok(())
+}
+
+fn encode_config(enc: &mut Encoder, config: &TokenConfig) {
+    enc.u8(edge_case_tag(config.self_transfer));
+    enc.u8(edge_case_tag(config.zero_amount));
+    enc.balance(config.existential_deposit);
+    enc.u8(match config.dust_policy {
+        DustPolicy::Reject => 0,
+        DustPolicy::Sweep => 1,
+    });
+    let burn: BTreeSet<_> = config.burn_addresses.iter().collect();
+    enc.len(burn.len());
+    burn.into_iter().for_each(|a| enc.address(a));
+    match &config.fee {
+        FeePolicy::None => enc.u8(0),
+        FeePolicy::Flat(fee) => {
+            enc.u8(1);
+            enc.balance(*fee);
+        }
+        FeePolicy::BasisPoints(bps) => {
+            enc.u8(2);
+            enc.u16(*bps);
+        }
+        FeePolicy::Tiered(tiers) => {
+            enc.u8(3);
+            enc.len(tiers.len());
+            for tier in tiers {
+                enc.balance(tier.min_amount);
+                enc.u16(tier.basis_points);
+            }
+        }
+    }
+    match &config.fee_destination {
+        FeeDestination::Burn => enc.u8(0),
+        FeeDestination::Treasury(treasury) => {
+            enc.u8(1);
+            enc.address(treasury);
+        }
+    }
+    enc.u8(u8::from(config.soulbound));
+    enc.u64(config.update_delay);
+}
+
+fn decode_config(dec: &mut Decoder) -> Result<TokenConfig, SnapshotError> {
+    let self_transfer = edge_case(dec.u8()?)?;
+    let zero_amount = edge_case(dec.u8()?)?;
+    let existential_deposit = dec.balance()?;
+    let dust_policy = match dec.u8()? {
+        0 => DustPolicy::Reject,
+        1 => DustPolicy::Sweep,
+        tag => return Err(SnapshotError::InvalidTag(tag)),
+    };
+    let mut burn_addresses = HashSet::new();
+    for _ in 0..dec.len()? {
+        burn_addresses.insert(dec.address()?);
+    }
+    if dec.version < 2 {
+        return Ok(TokenConfig {
+            self_transfer,
+            zero_amount,
+            existential_deposit,
+            dust_policy,
+            burn_addresses,
+            ..TokenConfig::default()
+        });
+    }
+    let fee = match dec.u8()? {
+        0 => FeePolicy::None,
+        1 => FeePolicy::Flat(dec.balance()?),
+        2 => FeePolicy::BasisPoints(dec.u16()?),
+        3 => {
+            let mut tiers = Vec::new();
+            for _ in 0..dec.len()? {
+                tiers.push(FeeTier {
+                    min_amount: dec.balance()?,
+                    basis_points: dec.u16()?,
+                });
+            }
+            FeePolicy::Tiered(tiers)
+        }
+        tag => return Err(SnapshotError::InvalidTag(tag)),
+    };
+    let fee_destination = match dec.u8()? {
+        0 => FeeDestination::Burn,
+        1 => FeeDestination::Treasury(dec.address()?),
+        tag => return Err(SnapshotError::InvalidTag(tag)),
+    };
+    let soulbound = dec.flag()?;
+    let update_delay = if dec.version >= 3 { dec.u64()? } else { 0 };
+    Ok(TokenConfig {
+        self_transfer,
+        zero_amount,
+        existential_deposit,
+        dust_policy,
+        burn_addresses,
+        fee,
+        fee_destination,
+        soulbound,
+        update_delay,
+    })
+}
+
+fn edge_case_tag(mode: EdgeCaseMode) -> u8 {
+    match mode {
+        EdgeCaseMode::Reject => 0,
+        EdgeCaseMode::NoOp => 1,
+    }
+}
+
+fn edge_case(tag: u8) -> Result<EdgeCaseMode, SnapshotError> {
+    match tag {
+        0 => Ok(EdgeCaseMode::Reject),
+        1 => Ok(EdgeCaseMode::NoOp),
+        tag => Err(SnapshotError::InvalidTag(tag)),
+    }
+}
+
+fn role_tag(role: Role) -> u8 {
+    match role {
+        Role::Minter => 0,
+        Role::Pauser => 1,
+        Role::Freezer => 2,
+        Role::Admin => 3,
+    }
+}
+
+fn role(tag: u8) -> Result<Role, SnapshotError> {
+    match tag {
+        0 => Ok(Role::Minter),
+        1 => Ok(Role::Pauser),
+        2 => Ok(Role::Freezer),
+        3 => Ok(Role::Admin),
+        tag => Err(SnapshotError::InvalidTag(tag)),
+    }
+}
+
+fn encode_event(enc: &mut Encoder, event: &TokenEvent) {
+    match event {
+        TokenEvent::Transfer { from, to, amount } => {
+            enc.u8(0);
+            enc.address(from);
+            enc.address(to);
+            enc.balance(*amount);
+        }
+        TokenEvent::Approval {
+            owner,
+            spender,
+            previous,
+            amount,
+        } => {
+            enc.u8(1);
+            enc.address(owner);
+            enc.address(spender);
+            enc.balance(*previous);
+            enc.balance(*amount);
+        }
+        TokenEvent::Mint { to, amount } => {
+            enc.u8(2);
+            enc.address(to);
+            enc.balance(*amount);
+        }
+        TokenEvent::Burn { from, amount } => {
+            enc.u8(3);
+            enc.address(from);
+            enc.balance(*amount);
+        }
+        TokenEvent::Shield { from, amount } => {
+            enc.u8(4);
+            enc.address(from);
+            enc.balance(*amount);
+        }
+        TokenEvent::Unshield { to, amount } => {
+            enc.u8(5);
+            enc.address(to);
+            enc.balance(*amount);
+        }
+        TokenEvent::ConfigChanged { config } => {
+            enc.u8(6);
+            encode_config(enc, config);
+        }
+        TokenEvent::Paused { by } => {
+            enc.u8(7);
+            enc.address(by);
+        }
+        TokenEvent::Unpaused { by } => {
+            enc.u8(8);
+            enc.address(by);
+        }
+        TokenEvent::OwnershipTransferred { previous, owner } => {
+            enc.u8(9);
+            enc.address(previous);
+            enc.address(owner);
+        }
+        TokenEvent::AccountFrozen { address } => {
+            enc.u8(10);
+            enc.address(address);
+        }
+        TokenEvent::AccountUnfrozen { address } => {
+            enc.u8(11);
+            enc.address(address);
+        }
+        TokenEvent::RoleGranted { role, account, by } => {
+            enc.u8(12);
+            enc.u8(role_tag(*role));
+            enc.address(account);
+            enc.address(by);
+        }
+        TokenEvent::RoleRevoked { role, account, by } => {
+            enc.u8(13);
+            enc.u8(role_tag(*role));
+            enc.address(account);
+            enc.address(by);
+        }
+        TokenEvent::OwnershipTransferStarted { owner, pending } => {
+            enc.u8(14);
+            enc.address(owner);
+            enc.address(pending);
+        }
+        TokenEvent::OwnershipRenounced { previous } => {
+            enc.u8(15);
+            enc.address(previous);
+        }
+        TokenEvent::AllowanceConsumed {
+            owner,
+            spender,
+            amount,
+            remaining,
+        } => {
+            enc.u8(16);
+            enc.address(owner);
+            enc.address(spender);
+            enc.balance(*amount);
+            enc.balance(*remaining);
+        }
+        TokenEvent::AllowanceRevoked {
+            owner,
+            spender,
+            previous,
+        } => {
+            enc.u8(17);
+            enc.address(owner);
+            enc.address(spender);
+            enc.balance(*previous);
+        }
+        TokenEvent::CapabilityGranted { id, issuer, limit } => {
+            enc.u8(18);
+            enc.u64(id.0);
+            enc.address(issuer);
+            enc.balance(*limit);
+        }
+        TokenEvent::CapabilityRedeemed {
+            id,
+            to,
+            amount,
+            remaining,
+        } => {
+            enc.u8(19);
+            enc.u64(id.0);
+            enc.address(to);
+            enc.balance(*amount);
+            enc.balance(*remaining);
+        }
+        TokenEvent::CapabilityRevoked { id, issuer } => {
+            enc.u8(20);
+            enc.u64(id.0);
+            enc.address(issuer);
+        }
+        TokenEvent::Locked {
+            owner,
+            amount,
+            unlock_at,
+        } => {
+            enc.u8(21);
+            enc.address(owner);
+            enc.balance(*amount);
+            enc.u64(*unlock_at);
+        }
+        TokenEvent::Unlocked { owner, amount } => {
+            enc.u8(22);
+            enc.address(owner);
+            enc.balance(*amount);
+        }
+        TokenEvent::EscrowCreated {
+            id,
+            payer,
+            payee,
+            amount,
+        } => {
+            enc.u8(23);
+            enc.u64(id.0);
+            enc.address(payer);
+            enc.address(payee);
+            enc.balance(*amount);
+        }
+        TokenEvent::EscrowReleased { id, to, amount } => {
+            enc.u8(24);
+            enc.u64(id.0);
+            enc.address(to);
+            enc.balance(*amount);
+        }
+        TokenEvent::EscrowRefunded { id, to, amount } => {
+            enc.u8(25);
+            enc.u64(id.0);
+            enc.address(to);
+            enc.balance(*amount);
+        }
+        TokenEvent::ConfigUpdateQueued { id, eta, config } => {
+            enc.u8(26);
+            enc.u64(id.0);
+            enc.u64(*eta);
+            encode_config(enc, config);
+        }
+        TokenEvent::ConfigUpdateExecuted { id } => {
+            enc.u8(27);
+            enc.u64(id.0);
+        }
+        TokenEvent::ConfigUpdateCancelled { id } => {
+            enc.u8(28);
+            enc.u64(id.0);
+        }
+    }
+}
+
+fn decode_event(dec: &mut Decoder) -> Result<TokenEvent, SnapshotError> {
+    let tag = dec.u8()?;
+    // v1에는 0~5번, v2에는 0~25번 이벤트만 있음
+    if (dec.version < 2 && tag > 5) || (dec.version < 3 && tag > 25) {
+        return Err(SnapshotError::InvalidTag(tag));
+    }
+    Ok(match tag {
+        0 => TokenEvent::Transfer {
+            from: dec.address()?,
+            to: dec.address()?,
+            amount: dec.balance()?,
+        },
+        1 => TokenEvent::Approval {
+            owner: dec.address()?,
+            spender: dec.address()?,
+            // v1은 이전 한도를 기록하지 않음
+            previous: if dec.version < 2 { 0 } else { dec.balance()? },
+            amount: dec.balance()?,
+        },
+        2 => TokenEvent::Mint {
+            to: dec.address()?,
+            amount: dec.balance()?,
+        },
+        3 => TokenEvent::Burn {
+            from: dec.address()?,
+            amount: dec.balance()?,
+        },
+        4 => TokenEvent::Shield {
+            from: dec.address()?,
+            amount: dec.balance()?,
+        },
+        5 => TokenEvent::Unshield {
+            to: dec.address()?,
+            amount: dec.balance()?,
+        },
+        6 => TokenEvent::ConfigChanged {
+            config: decode_config(dec)?,
+        },
+        7 => TokenEvent::Paused { by: dec.address()? },
+        8 => TokenEvent::Unpaused { by: dec.address()? },
+        9 => TokenEvent::OwnershipTransferred {
+            previous: dec.address()?,
+            owner: dec.address()?,
+        },
+        10 => TokenEvent::AccountFrozen {
+            address: dec.address()?,
+        },
+        11 => TokenEvent::AccountUnfrozen {
+            address: dec.address()?,
+        },
+        12 => TokenEvent::RoleGranted {
+            role: role(dec.u8()?)?,
+            account: dec.address()?,
+            by: dec.address()?,
+        },
+        13 => TokenEvent::RoleRevoked {
+            role: role(dec.u8()?)?,
+            account: dec.address()?,
+            by: dec.address()?,
+        },
+        14 => TokenEvent::OwnershipTransferStarted {
+            owner: dec.address()?,
+            pending: dec.address()?,
+        },
+        15 => TokenEvent::OwnershipRenounced {
+            previous: dec.address()?,
+        },
+        16 => TokenEvent::AllowanceConsumed {
+            owner: dec.address()?,
+            spender: dec.address()?,
+            amount: dec.balance()?,
+            remaining: dec.balance()?,
+        },
+        17 => TokenEvent::AllowanceRevoked {
+            owner: dec.address()?,
+            spender: dec.address()?,
+            previous: dec.balance()?,
+        },
+        18 => TokenEvent::CapabilityGranted {
+            id: CapabilityId(dec.u64()?),
+            issuer: dec.address()?,
+            limit: dec.balance()?,
+        },
+        19 => TokenEvent::CapabilityRedeemed {
+            id: CapabilityId(dec.u64()?),
+            to: dec.address()?,
+            amount: dec.balance()?,
+            remaining: dec.balance()?,
+        },
+        20 => TokenEvent::CapabilityRevoked {
+            id: CapabilityId(dec.u64()?),
+            issuer: dec.address()?,
+        },
+        21 => TokenEvent::Locked {
+            owner: dec.address()?,
+            amount: dec.balance()?,
+            unlock_at: dec.u64()?,
+        },
+        22 => TokenEvent::Unlocked {
+            owner: dec.address()?,
+            amount: dec.balance()?,
+        },
+        23 => TokenEvent::EscrowCreated {
+            id: EscrowId(dec.u64()?),
+            payer: dec.address()?,
+            payee: dec.address()?,
+            amount: dec.balance()?,
+        },
+        24 => TokenEvent::EscrowReleased {
+            id: EscrowId(dec.u64()?),
+            to: dec.address()?,
+            amount: dec.balance()?,
+        },
+        25 => TokenEvent::EscrowRefunded {
+            id: EscrowId(dec.u64()?),
+            to: dec.address()?,
+            amount: dec.balance()?,
+        },
+        26 => TokenEvent::ConfigUpdateQueued {
+            id: ConfigUpdateId(dec.u64()?),
+            eta: dec.u64()?,
+            config: decode_config(dec)?,
+        },
+        27 => TokenEvent::ConfigUpdateExecuted {
+            id: ConfigUpdateId(dec.u64()?),
+        },
+        28 => TokenEvent::ConfigUpdateCancelled {
+            id: ConfigUpdateId(dec.u64()?),
+        },
+        tag => return Err(SnapshotError::InvalidTag(tag)),
+    })
+}
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn balance(&mut self, v: Balance) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn len(&mut self, n: usize) {
+        self.u32(u32::try_from(n).expect("collection exceeds u32::MAX entries"));
+    }
+
+    fn address(&mut self, address: &Address) {
+        self.string(address.as_str());
+    }
+
+    fn string(&mut self, s: &str) {
+        self.len(s.len());
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    fn optional_address(&mut self, address: Option<&Address>) {
+        match address {
+            Some(address) => {
+                self.u8(1);
+                self.address(address);
+            }
+            None => self.u8(0),
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    /// Bytes per balance, from the header
+    width: usize,
+    /// Format version, from the header
+    version: u16,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() < n {
+            return Err(SnapshotError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().expect("take returned N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SnapshotError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    // 기록 시 폭(8/16)으로 읽고 현재 빌드의 Balance로 좁힘
+    fn balance(&mut self) -> Result<Balance, SnapshotError> {
+        let mut wide = [0u8; 16];
+        wide[..self.width].copy_from_slice(self.take(self.width)?);
+        Balance::try_from(u128::from_le_bytes(wide)).map_err(|_| SnapshotError::BalanceOverflow)
+    }
+
+    fn flag(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(SnapshotError::InvalidTag(tag)),
+        }
+    }
+
+    fn len(&mut self) -> Result<usize, SnapshotError> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn address(&mut self) -> Result<Address, SnapshotError> {
+        let len = self.len()?;
+        let raw = std::str::from_utf8(self.take(len)?)
+            .map_err(|_| SnapshotError::InvalidAddress(AddressError::InvalidFormat("UTF-8")))?;
+        Address::new(raw).map_err(SnapshotError::InvalidAddress)
+    }
+
+    fn optional_address(&mut self) -> Result<Option<Address>, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.address()?)),
+            tag => Err(SnapshotError::InvalidTag(tag)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SnapshotError> {
+        let len = self.len()?;
+        let raw = std::str::from_utf8(self.take(len)?).map_err(|_| SnapshotError::InvalidText)?;
+        Ok(raw.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TokenState {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let treasury = Address::new("treasury").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 5,
+            burn_addresses: [Address::new(crate::BURN_ADDRESS).unwrap()].into(),
+            ..TokenConfig::default()
+        };
+        let balances = [(alice.clone(), 1_000), (treasury.clone(), 500)].into();
+        let mut token = TokenState::from_parts(balances, 1_500, config).with_owner(alice.clone());
+        token.approve(&alice, &bob, 300).unwrap();
+        token.transfer_from(&bob, &alice, &bob, 100).unwrap();
+        token.set_proof_verifier(Box::new(crate::AcceptAllVerifier));
+        let note = token.shield(&alice, 50).unwrap();
+        token.unshield(&bob, &note, &[]).unwrap();
+        token.shield(&alice, 20).unwrap();
+        token.mark_non_circulating(&alice, &treasury).unwrap();
+        token
+    }
+
+    fn decode_err(bytes: &[u8]) -> SnapshotError {
+        TokenState::from_bytes(bytes)
+            .err()
+            .expect("decoding should fail")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let token = sample();
+        let bytes = token.to_bytes();
+
+        let restored = TokenState::from_bytes(&bytes).unwrap();
+
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.total_supply(), token.total_supply());
+        assert_eq!(restored.events(), token.events());
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        assert_eq!(restored.allowance(&alice, &bob), 200);
+        assert_eq!(restored.next_seq(), token.next_seq());
+    }
+
+    #[test]
+    fn test_open_notes_and_metadata_round_trip() {
+        let mut token = sample();
+        token.metadata = Some(TokenMetadata::new("Réseau", "RSX", 18));
+        let open = crate::Note { id: 1, amount: 20 };
+
+        let mut restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+        restored.set_proof_verifier(Box::new(crate::AcceptAllVerifier));
+
+        assert_eq!(restored.metadata(), token.metadata());
+        assert_eq!(restored.shielded_supply(), 20);
+        let bob = Address::new("bob").unwrap();
+        assert_eq!(restored.unshield(&bob, &open, &[]), Ok(()));
+    }
+
+    #[test]
+    fn test_shielding_and_bulk_credit_states_reload() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 10,
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        token.set_proof_verifier(Box::new(crate::AcceptAllVerifier));
+        let reloads = |token: &TokenState| {
+            let restored = TokenState::from_bytes(&token.to_bytes());
+            assert!(restored.is_ok(), "{:?}", restored.err());
+        };
+
+        let note = token.shield(&alice, 300).unwrap();
+        reloads(&token);
+        token.unshield(&bob, &note, b"proof").unwrap();
+        reloads(&token);
+        token.bulk_credit(&alice, [(carol.clone(), 10)]).unwrap();
+        reloads(&token);
+        // 만들어낸 노트는 거부되고 상태를 건드리지 않음
+        let made_up = crate::Note { id: 42, amount: 5 };
+        assert!(token.unshield(&alice, &made_up, b"proof").is_err());
+        reloads(&token);
+        // 먼지를 남기는 shield와 bulk_credit은 거부됨
+        let dave = Address::new("dave").unwrap();
+        assert!(token.shield(&bob, 295).is_err());
+        assert!(token.bulk_credit(&alice, [(dave, 1)]).is_err());
+        reloads(&token);
+    }
+
+    #[test]
+    fn test_admin_state_round_trip() {
+        let treasury = Address::new("treasury").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = sample()
+            .with_owner(treasury.clone())
+            .with_max_supply(2_000)
+            .unwrap();
+        token.grant_role(&treasury, Role::Pauser, &bob).unwrap();
+        token.freeze_account(&treasury, &bob).unwrap();
+        token.pause(&bob).unwrap();
+        token.transfer_ownership(&treasury, &bob).unwrap();
+
+        let restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+
+        assert_eq!(restored.owner(), Some(&treasury));
+        assert_eq!(restored.pending_owner(), Some(&bob));
+        assert_eq!(restored.max_supply(), Some(2_000));
+        assert!(restored.is_paused());
+        assert!(restored.is_frozen(&bob));
+        assert!(restored.has_role(Role::Pauser, &bob));
+        assert_eq!(restored.events(), token.events());
+    }
+
+    #[test]
+    fn test_header_is_checked() {
+        let bytes = sample().to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        let mut future = bytes.clone();
+        future[4..6].copy_from_slice(&99u16.to_le_bytes());
+        let mut bad_width = bytes.clone();
+        bad_width[6] = 4;
+
+        assert_eq!(decode_err(&bad_magic), SnapshotError::BadMagic);
+        assert_eq!(decode_err(&future), SnapshotError::UnsupportedVersion(99));
+        assert_eq!(
+            decode_err(&bad_width),
+            SnapshotError::UnsupportedBalanceWidth(4)
+        );
+    }
+
+    #[test]
+    fn test_truncated_and_padded_input_rejected() {
+        let bytes = sample().to_bytes();
+
+        for len in 0..bytes.len() {
+            assert!(TokenState::from_bytes(&bytes[..len]).is_err(), "{len}");
+        }
+        let mut padded = bytes;
+        padded.push(0);
+        assert_eq!(decode_err(&padded), SnapshotError::TrailingBytes);
+    }
+
+    #[test]
+    fn test_tampered_supply_rejected() {
+        let mut bytes = sample().to_bytes();
+        // 헤더(7) + 설정(1+1+폭+1+4+주소+수수료 1+1+soulbound 1+지연 8) 뒤가 total_supply
+        let width = size_of::<Balance>();
+        let at = 7 + 3 + width + 4 + 4 + crate::BURN_ADDRESS.len() + 3 + 8;
+        bytes[at] ^= 1;
+
+        let err = decode_err(&bytes);
+
+        assert!(matches!(err, SnapshotError::Inconsistent(_)), "{err:?}");
+    }
+
+    fn hex(lines: &[&str]) -> Vec<u8> {
+        let text = lines.concat();
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// alice가 1000을 발행받아 bob에게 250을 보내고 40을 승인한 상태
+    fn golden_scenario() -> TokenState {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1_000);
+        token.transfer(&alice, &bob, 250).unwrap();
+        token.approve(&alice, &bob, 40).unwrap();
+        token
+    }
+
+    // 레이아웃을 고정: 이 바이트가 바뀌면 FORMAT_VERSION을 올리고
+    // 이전 버전 디코더를 남겨야 함
+    #[cfg(not(feature = "u128-balance"))]
+    #[test]
+    fn test_current_layout_is_frozen() {
+        let golden = hex(&[
+            "544b535403000800000000000000000000000000000000000000000000000000",
+            "00e8030000000000000200000005000000616c696365ee020000000000000300",
+            "0000626f62fa000000000000000100000005000000616c69636503000000626f",
+            "6228000000000000002800000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000200000000000000020000",
+            "0000000000000000000005000000616c69636503000000626f62fa0000000000",
+            "000001000000000000000105000000616c69636503000000626f620000000000",
+            "0000002800000000000000000000000000000000000000000105000000616c69",
+            "6365000000000000000000000000000000000000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000000000000000",
+        ]);
+
+        assert_eq!(golden_scenario().to_bytes(), golden);
+    }
+
+    #[test]
+    fn test_v2_image_upgrades() {
+        let v2 = hex(&[
+            "544b5354020008000000000000000000000000000000000000e8030000000000",
+            "000200000005000000616c696365ee0200000000000003000000626f62fa0000",
+            "00000000000100000005000000616c69636503000000626f6228000000000000",
+            "0028000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000002000000000000000200000000000000000000",
+            "000005000000616c69636503000000626f62fa00000000000000010000000000",
+            "00000105000000616c69636503000000626f6200000000000000002800000000",
+            "000000000000000000000000000000000105000000616c696365000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000",
+        ]);
+
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+
+        let token = TokenState::from_bytes(&v2).unwrap();
+
+        assert_eq!(token.balance_of(&bob), 250);
+        assert_eq!(token.allowance(&alice, &bob), 40);
+        assert_eq!(token.events(), golden_scenario().events());
+        assert_eq!(token.owner(), Some(&alice));
+        assert_eq!(token.config(), &TokenConfig::default());
+        assert!(token.queued_config_updates().is_empty());
+    }
+
+    #[test]
+    fn test_v1_image_upgrades() {
+        let v1 = hex(&[
+            "544b5354010008000000000000000000000000000000e8030000000000000200",
+            "000005000000616c696365ee0200000000000003000000626f62fa0000000000",
+            "00000100000005000000616c69636503000000626f6228000000000000002800",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000002000000000000000200000000000000000000000005",
+            "000000616c69636503000000626f62fa00000000000000010000000000000001",
+            "05000000616c69636503000000626f6228000000000000000000000000000000",
+            "0000000000",
+        ]);
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+
+        let token = TokenState::from_bytes(&v1).unwrap();
+
+        assert_eq!(token.balance_of(&alice), 750);
+        assert_eq!(token.balance_of(&bob), 250);
+        assert_eq!(token.allowance(&alice, &bob), 40);
+        assert_eq!(token.events(), golden_scenario().events());
+        assert_eq!(token.owner(), None);
+        assert_eq!(token.config().fee, FeePolicy::None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Confidential balances kept as commitments (experimental simulation).
+//!
+//! A [`ConfidentialLedger`] never stores an amount in the clear. Each
+//! account holds a Pedersen-style [`Commitment`] to its balance, and a
+//! transfer carries a commitment to the amount moved: the ledger subtracts
+//! it from the sender and adds it to the recipient, relying on the
+//! scheme's homomorphic addition. Two range proofs guard every transfer,
+//! one that the amount is non-negative and one that the sender's remaining
+//! balance is. Only the initial supply and burns use public amounts.
+//!
+//! Because transfers add and subtract the same commitment, the sum of all
+//! balances always commits to the public supply with a zero blinding
+//! factor. [`ConfidentialLedger::check_conservation`] checks exactly that,
+//! without opening any balance.
+//!
+//! The curve arithmetic and range proofs sit behind [`CommitmentScheme`],
+//! so the ledger is the same whichever scheme backs it. The bundled
+//! [`SimulatedPedersen`] is for prototyping the bookkeeping only. Its
+//! commitments carry the value in the clear, and it ignores proof bytes,
+//! so a ledger using it hides nothing.
+
+use std::collections::HashMap;
+
+use crate::{Address, Balance, TokenError};
+
+/// Opaque commitment, sized for a compressed curve point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment(pub [u8; 32]);
+
+/// Commitment arithmetic and range-proof checks.
+pub trait CommitmentScheme {
+    /// Commitment to `value` under blinding factor `blinding`.
+    ///
+    /// Fails with [`TokenError::BalanceOverFlow`] if the scheme can't
+    /// represent `value`.
+    fn commit(&self, value: Balance, blinding: &[u8; 32]) -> Result<Commitment, TokenError>;
+    fn add(&self, a: &Commitment, b: &Commitment) -> Commitment;
+    fn sub(&self, a: &Commitment, b: &Commitment) -> Commitment;
+    /// Whether `proof` shows that `commitment` opens to a value in
+    /// `0..=Balance::MAX`.
+    fn verify_range(&self, commitment: &Commitment, proof: &[u8]) -> bool;
+}
+
+/// Transparent stand-in for a real scheme. For tests and simulations only.
+///
+/// The first 16 bytes hold the value and the last 16 the blinding factor,
+/// both as little-endian integers with wrapping arithmetic. Range checks
+/// read the value directly and ignore the proof. The value lane is signed,
+/// so `u128-balance` amounts above `i128::MAX` can't be committed to.
+pub struct SimulatedPedersen;
+
+impl SimulatedPedersen {
+    fn lanes(c: &Commitment) -> (i128, u128) {
+        let value = i128::from_le_bytes(c.0[..16].try_into().unwrap());
+        let blinding = u128::from_le_bytes(c.0[16..].try_into().unwrap());
+        (value, blinding)
+    }
+
+    fn join(value: i128, blinding: u128) -> Commitment {
+        let mut bytes = [0; 32];
+        bytes[..16].copy_from_slice(&value.to_le_bytes());
+        bytes[16..].copy_from_slice(&blinding.to_le_bytes());
+        Commitment(bytes)
+    }
+}
+
+impl CommitmentScheme for SimulatedPedersen {
+    // u128-balance에서는 캐스트가 불필요하지만 폭과 무관하게 같은 코드를 유지
+    #[allow(clippy::unnecessary_cast)]
+    fn commit(&self, value: Balance, blinding: &[u8; 32]) -> Result<Commitment, TokenError> {
+        // 음수로 감기면 범위 검사에서 잔액 부족으로 오인됨
+        let value = i128::try_from(value as u128).map_err(|_| TokenError::BalanceOverFlow)?;
+        let blinding = u128::from_le_bytes(blinding[..16].try_into().unwrap());
+        Ok(Self::join(value, blinding))
+    }
+
+    fn add(&self, a: &Commitment, b: &Commitment) -> Commitment {
+        let (av, ab) = Self::lanes(a);
+        let (bv, bb) = Self::lanes(b);
+        Self::join(av.wrapping_add(bv), ab.wrapping_add(bb))
+    }
+
+    fn sub(&self, a: &Commitment, b: &Commitment) -> Commitment {
+        let (av, ab) = Self::lanes(a);
+        let (bv, bb) = Self::lanes(b);
+        Self::join(av.wrapping_sub(bv), ab.wrapping_sub(bb))
+    }
+
+    fn verify_range(&self, commitment: &Commitment, _proof: &[u8]) -> bool {
+        let (value, _) = Self::lanes(commitment);
+        u128::try_from(value).is_ok_and(|v| Balance::try_from(v).is_ok())
+    }
+}
+
+/// A transfer whose amount is hidden in a commitment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfidentialTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: Commitment,
+    /// Range proof for `amount`
+    pub amount_proof: Vec<u8>,
+    /// Range proof for the sender's balance after the transfer
+    pub remaining_proof: Vec<u8>,
+}
+
+/// Balances held as commitments, with a public total supply.
+pub struct ConfidentialLedger {
+    scheme: Box<dyn CommitmentScheme>,
+    balances: HashMap<Address, Commitment>,
+    supply: Balance,
+    zero: Commitment,
+}
+
+impl ConfidentialLedger {
+    /// A ledger whose whole `initial_supply` belongs to `creator`.
+    ///
+    /// Fails if `scheme` can't commit to `initial_supply`.
+    pub fn new(
+        creator: Address,
+        initial_supply: Balance,
+        scheme: Box<dyn CommitmentScheme>,
+    ) -> Result<Self, TokenError> {
+        let mut balances = HashMap::new();
+        balances.insert(creator, scheme.commit(initial_supply, &[0; 32])?);
+        Ok(Self {
+            zero: scheme.commit(0, &[0; 32])?,
+            scheme,
+            balances,
+            supply: initial_supply,
+        })
+    }
+
+    /// Sum of all balances; public, since only burns change it.
+    pub fn total_supply(&self) -> Balance {
+        self.supply
+    }
+
+    /// Commitment to `address`'s balance; a commitment to zero if it has
+    /// never held tokens.
+    pub fn commitment_of(&self, address: &Address) -> Commitment {
+        self.balances.get(address).copied().unwrap_or(self.zero)
+    }
+
+    /// Destroys `amount` public tokens of `from`'s; `proof` must show the
+    /// remaining balance is non-negative.
+    pub fn burn(
+        &mut self,
+        from: &Address,
+        amount: Balance,
+        proof: &[u8],
+    ) -> Result<(), TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        let burned = self.scheme.commit(amount, &[0; 32])?;
+        let remaining = self.scheme.sub(&self.commitment_of(from), &burned);
+        if !self.scheme.verify_range(&remaining, proof) {
+            return Err(TokenError::InvalidProof);
+        }
+        // 잔액 증명이 통과했으면 supply에서도 뺄 수 있음
+        let supply = self
+            .supply
+            .checked_sub(amount)
+            .ok_or(TokenError::InvalidProof)?;
+
+        self.balances.insert(from.clone(), remaining);
+        self.supply = supply;
+        Ok(())
+    }
+
+    /// Moves the committed amount from `tx.from` to `tx.to` once both range
+    /// proofs verify.
+    pub fn transfer(&mut self, tx: &ConfidentialTransfer) -> Result<(), TokenError> {
+        if tx.from == tx.to {
+            return Err(TokenError::SelfTransfer);
+        }
+        if !self.scheme.verify_range(&tx.amount, &tx.amount_proof) {
+            return Err(TokenError::InvalidProof);
+        }
+        let remaining = self.scheme.sub(&self.commitment_of(&tx.from), &tx.amount);
+        if !self.scheme.verify_range(&remaining, &tx.remaining_proof) {
+            return Err(TokenError::InvalidProof);
+        }
+
+        let credited = self.scheme.add(&self.commitment_of(&tx.to), &tx.amount);
+        self.balances.insert(tx.from.clone(), remaining);
+        self.balances.insert(tx.to.clone(), credited);
+        Ok(())
+    }
+
+    /// Checks that the balances add up to a zero-blinded commitment to the
+    /// public supply.
+    pub fn check_conservation(&self) -> Result<(), String> {
+        let sum = self
+            .balances
+            .values()
+            .fold(self.zero, |acc, c| self.scheme.add(&acc, c));
+        // 공급량은 생성 시 커밋되었고 소각으로만 줄어들므로 항상 커밋 가능
+        let supply = self.scheme.commit(self.supply, &[0; 32]);
+        if supply != Ok(sum) {
+            return Err(format!(
+                "balance commitments do not add up to supply {}",
+                self.supply
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (ConfidentialLedger, Address) {
+        let alice = Address::new("alice").unwrap();
+        let ledger =
+            ConfidentialLedger::new(alice.clone(), 100, Box::new(SimulatedPedersen)).unwrap();
+        (ledger, alice)
+    }
+
+    fn send(from: &Address, to: &Address, amount: Balance, blinding: u8) -> ConfidentialTransfer {
+        ConfidentialTransfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount: SimulatedPedersen.commit(amount, &[blinding; 32]).unwrap(),
+            amount_proof: Vec::new(),
+            remaining_proof: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_transfer_conserves_supply() {
+        let (mut ledger, alice) = setup();
+        let bob = Address::new("bob").unwrap();
+        let tx = send(&alice, &bob, 30, 9);
+
+        ledger.transfer(&tx).unwrap();
+
+        assert_eq!(ledger.commitment_of(&bob), tx.amount);
+        // 블라인딩은 양쪽에서 상쇄되어 합계는 공개 supply와 일치
+        assert_ne!(
+            ledger.commitment_of(&alice),
+            SimulatedPedersen.commit(70, &[0; 32]).unwrap()
+        );
+        assert_eq!(ledger.check_conservation(), Ok(()));
+        assert_eq!(ledger.total_supply(), 100);
+    }
+
+    #[test]
+    fn test_overdraft_fails_range_proof() {
+        let (mut ledger, alice) = setup();
+        let bob = Address::new("bob").unwrap();
+        let before = ledger.commitment_of(&alice);
+
+        let overdraft = ledger.transfer(&send(&alice, &bob, 150, 1));
+        let burned_too_much = ledger.burn(&alice, 101, &[]);
+
+        assert_eq!(overdraft, Err(TokenError::InvalidProof));
+        assert_eq!(burned_too_much, Err(TokenError::InvalidProof));
+        assert_eq!(ledger.commitment_of(&alice), before);
+        assert_eq!(ledger.commitment_of(&bob), ledger.zero);
+    }
+
+    #[test]
+    fn test_burn_moves_public_supply() {
+        let (mut ledger, alice) = setup();
+
+        ledger.burn(&alice, 40, &[]).unwrap();
+
+        assert_eq!(ledger.burn(&alice, 0, &[]), Err(TokenError::ZeroAmount));
+        assert_eq!(ledger.total_supply(), 60);
+        assert_eq!(ledger.check_conservation(), Ok(()));
+    }
+
+    #[cfg(feature = "u128-balance")]
+    #[test]
+    fn test_amount_beyond_i128_is_refused_not_wrapped() {
+        let alice = Address::new("alice").unwrap();
+        let huge = i128::MAX as Balance + 1;
+
+        let ledger = ConfidentialLedger::new(alice, huge, Box::new(SimulatedPedersen));
+
+        assert_eq!(
+            SimulatedPedersen.commit(huge, &[0; 32]),
+            Err(TokenError::BalanceOverFlow)
+        );
+        assert!(matches!(ledger, Err(TokenError::BalanceOverFlow)));
+    }
+}
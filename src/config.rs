@@ -0,0 +1,626 @@
+//! Runtime configuration reloads.
+//!
+//! Every [`TokenConfig`] field can be changed on a live token, so tuning a
+//! rule doesn't need a restart. Only the owner or an account holding
+//! [`Role::Admin`] may do so. The new configuration is validated against
+//! the current state first and then swapped in whole: either every field
+//! changes or none does. Success emits [`TokenEvent::ConfigChanged`].
+//!
+//! How soon a change lands depends on [`TokenConfig::update_delay`]. At 0,
+//! the default, [`TokenState::update_config`] applies it for the very next
+//! operation. Otherwise changes are timelocked: `update_config` is refused
+//! with [`TokenError::ConfigTimelocked`], and a change must be queued with
+//! [`TokenState::queue_config_update`], which announces it with
+//! [`TokenEvent::ConfigUpdateQueued`]. Once `update_delay` seconds have
+//! passed on the token's clock [`TokenState::execute_config_update`]
+//! applies it; until then [`TokenState::cancel_config_update`] can drop it.
+//! The delay is part of the configuration, so shortening it waits out the
+//! current delay too. Queued updates are ledger state and are saved in
+//! snapshots.
+//!
+//! Raising the existential deposit is refused while some balance would
+//! fall below it, checked when the change is applied rather than queued,
+//! and raising it from zero drops the entries of accounts left holding
+//! nothing, which a non-zero minimum does not allow.
+//!
+//! [`TokenConfig`] also parses from a small `key = value` text format, for
+//! reloading from a file:
+//!
+//! ```text
+//! # comments and blank lines are ignored; omitted keys take defaults
+//! self_transfer = noop             # reject | noop
+//! zero_amount = reject             # reject | noop
+//! existential_deposit = 10
+//! dust_policy = sweep              # reject | sweep
+//! burn_addresses = 0x000000000000000000000000000000000000dEaD, sink
+//! fee = tiered 0:30, 10000:10      # none | flat N | bps N | tiered MIN:BPS, ...
+//! fee_destination = treasury vault # burn | treasury ADDRESS
+//! soulbound = false                # true | false
+//! update_delay = 86400             # seconds; 0 applies updates at once
+//! ```
+//!
+//! Policy scripts reload separately, through
+//! `script::ScriptHandle` (`scripting` feature).
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{
+    Address, DustPolicy, EdgeCaseMode, FeeDestination, FeePolicy, FeeTier, Role, TokenConfig,
+    TokenError, TokenState,
+};
+
+/// A configuration file that could not be read or parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Identifies a queued configuration update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigUpdateId(pub u64);
+
+impl fmt::Display for ConfigUpdateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "config update #{}", self.0)
+    }
+}
+
+/// A configuration waiting out the update delay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueuedConfig {
+    pub config: TokenConfig,
+    /// First instant, by the token's clock, the update can be executed
+    pub eta: u64,
+}
+
+impl TokenConfig {
+    /// Reads and parses a configuration file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|e| ConfigError(format!("{}: {e}", path.display())))?
+            .parse()
+    }
+}
+
+impl FromStr for TokenConfig {
+    type Err = ConfigError;
+
+    fn from_str(text: &str) -> Result<Self, ConfigError> {
+        let mut config = TokenConfig::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |message: String| ConfigError(format!("line {}: {message}", index + 1));
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `key = value`".into()))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "self_transfer" => config.self_transfer = edge_case(value).map_err(err)?,
+                "zero_amount" => config.zero_amount = edge_case(value).map_err(err)?,
+                "existential_deposit" => {
+                    config.existential_deposit = value
+                        .parse()
+                        .map_err(|e| err(format!("existential_deposit: {e}")))?;
+                }
+                "dust_policy" => {
+                    config.dust_policy = match value {
+                        "reject" => DustPolicy::Reject,
+                        "sweep" => DustPolicy::Sweep,
+                        other => return Err(err(format!("unknown dust policy {other:?}"))),
+                    };
+                }
+                "burn_addresses" => {
+                    config.burn_addresses = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|raw| !raw.is_empty())
+                        .map(|raw| Address::new(raw).map_err(|e| err(format!("{raw:?}: {e}"))))
+                        .collect::<Result<_, _>>()?;
+                }
+                "soulbound" => {
+                    config.soulbound = value.parse().map_err(|e| err(format!("soulbound: {e}")))?;
+                }
+                "update_delay" => {
+                    config.update_delay = value
+                        .parse()
+                        .map_err(|e| err(format!("update_delay: {e}")))?;
+                }
+                "fee" => {
+                    let fee = fee_policy(value).map_err(err)?;
+                    fee.validate().map_err(err)?;
+                    config.fee = fee;
+                }
+                "fee_destination" => {
+                    config.fee_destination = match value.split_once(' ') {
+                        _ if value == "burn" => FeeDestination::Burn,
+                        Some(("treasury", raw)) => {
+                            let raw = raw.trim();
+                            let treasury =
+                                Address::new(raw).map_err(|e| err(format!("{raw:?}: {e}")))?;
+                            FeeDestination::Treasury(treasury)
+                        }
+                        _ => return Err(err(format!("unknown fee destination {value:?}"))),
+                    };
+                }
+                other => return Err(err(format!("unknown key {other:?}"))),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn edge_case(value: &str) -> Result<EdgeCaseMode, String> {
+    match value {
+        "reject" => Ok(EdgeCaseMode::Reject),
+        "noop" => Ok(EdgeCaseMode::NoOp),
+        other => Err(format!("unknown mode {other:?}")),
+    }
+}
+
+fn fee_policy(value: &str) -> Result<FeePolicy, String> {
+    let (kind, args) = value.split_once(' ').unwrap_or((value, ""));
+    let args = args.trim();
+    match kind {
+        "none" if args.is_empty() => Ok(FeePolicy::None),
+        "flat" => Ok(FeePolicy::Flat(number(args)?)),
+        "bps" => Ok(FeePolicy::BasisPoints(number(args)?)),
+        "tiered" => args
+            .split(',')
+            .map(|tier| {
+                let (min, bps) = tier
+                    .split_once(':')
+                    .ok_or_else(|| format!("fee tier {tier:?}: expected `MIN:BPS`"))?;
+                Ok(FeeTier {
+                    min_amount: number(min)?,
+                    basis_points: number(bps)?,
+                })
+            })
+            .collect::<Result<_, String>>()
+            .map(FeePolicy::Tiered),
+        _ => Err(format!("unknown fee policy {value:?}")),
+    }
+}
+
+fn number<T: FromStr<Err: fmt::Display>>(raw: &str) -> Result<T, String> {
+    raw.trim().parse().map_err(|e| format!("fee {raw:?}: {e}"))
+}
+
+impl TokenState {
+    /// Replaces the configuration after checking it against current state.
+    ///
+    /// `caller` must hold [`Role::Admin`]. Fails with
+    /// [`TokenError::ConfigTimelocked`] if the current configuration has an
+    /// update delay, with [`TokenError::BelowExistentialDeposit`] if a
+    /// raised existential deposit would strand an existing non-zero
+    /// balance, and with [`TokenError::InvalidFeePolicy`] if the fee policy
+    /// does not pass [`FeePolicy::validate`]. Accounts holding zero are
+    /// dropped once the minimum is non-zero.
+    pub fn update_config(
+        &mut self,
+        caller: &Address,
+        config: TokenConfig,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::UpdateConfig {
+            caller: caller.clone(),
+            config,
+        })
+        .map(|_| ())
+    }
+
+    /// Queues `config` to replace the configuration once the update delay
+    /// has passed; `caller` must hold [`Role::Admin`].
+    pub fn queue_config_update(
+        &mut self,
+        caller: &Address,
+        config: TokenConfig,
+    ) -> Result<ConfigUpdateId, TokenError> {
+        let id = ConfigUpdateId(self.next_config_update_id);
+        self.execute(Operation::QueueConfigUpdate {
+            caller: caller.clone(),
+            config,
+        })?;
+        Ok(id)
+    }
+
+    /// Applies queued update `id`, with the checks of
+    /// [`TokenState::update_config`].
+    ///
+    /// Fails with [`TokenError::ConfigUpdateNotReady`] before its `eta`.
+    pub fn execute_config_update(
+        &mut self,
+        caller: &Address,
+        id: ConfigUpdateId,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::ExecuteConfigUpdate {
+            caller: caller.clone(),
+            id,
+        })
+        .map(|_| ())
+    }
+
+    /// Drops queued update `id` without applying it.
+    pub fn cancel_config_update(
+        &mut self,
+        caller: &Address,
+        id: ConfigUpdateId,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::CancelConfigUpdate {
+            caller: caller.clone(),
+            id,
+        })
+        .map(|_| ())
+    }
+
+    /// The update queued as `id`, if it is still pending.
+    pub fn queued_config_update(&self, id: ConfigUpdateId) -> Option<&QueuedConfig> {
+        self.config_updates.get(&id)
+    }
+
+    /// Every pending update, in id order.
+    pub fn queued_config_updates(&self) -> Vec<(ConfigUpdateId, &QueuedConfig)> {
+        self.config_updates.iter().map(|(id, q)| (*id, q)).collect()
+    }
+
+    pub(crate) fn apply_update_config(
+        &mut self,
+        caller: &Address,
+        config: &TokenConfig,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        let delay = self.config.update_delay;
+        if delay > 0 {
+            return Err(TokenError::ConfigTimelocked { delay });
+        }
+        self.swap_config(config)
+    }
+
+    pub(crate) fn apply_queue_config_update(
+        &mut self,
+        caller: &Address,
+        config: &TokenConfig,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        // 수수료 형식은 대기열에 넣을 때 바로 검사 (잔액 조건은 실행 시점)
+        config
+            .fee
+            .validate()
+            .map_err(TokenError::InvalidFeePolicy)?;
+        let id = ConfigUpdateId(self.next_config_update_id);
+        let eta = self.now().saturating_add(self.config.update_delay);
+        self.config_updates.insert(
+            id,
+            QueuedConfig {
+                config: config.clone(),
+                eta,
+            },
+        );
+        self.next_config_update_id += 1;
+        self.emit(TokenEvent::ConfigUpdateQueued {
+            id,
+            eta,
+            config: config.clone(),
+        });
+        Ok(())
+    }
+
+    pub(crate) fn apply_execute_config_update(
+        &mut self,
+        caller: &Address,
+        id: ConfigUpdateId,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        let queued = self
+            .config_updates
+            .get(&id)
+            .ok_or(TokenError::UnknownConfigUpdate)?;
+        let (eta, now) = (queued.eta, self.now());
+        if now < eta {
+            return Err(TokenError::ConfigUpdateNotReady { eta, now });
+        }
+        let config = queued.config.clone();
+        self.swap_config(&config)?;
+        self.config_updates.remove(&id);
+        self.emit(TokenEvent::ConfigUpdateExecuted { id });
+        Ok(())
+    }
+
+    pub(crate) fn apply_cancel_config_update(
+        &mut self,
+        caller: &Address,
+        id: ConfigUpdateId,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        self.config_updates
+            .remove(&id)
+            .ok_or(TokenError::UnknownConfigUpdate)?;
+        self.emit(TokenEvent::ConfigUpdateCancelled { id });
+        Ok(())
+    }
+
+    // 검증 후 통째로 교체: 실패하면 아무것도 바뀌지 않음
+    fn swap_config(&mut self, config: &TokenConfig) -> Result<(), TokenError> {
+        config
+            .fee
+            .validate()
+            .map_err(TokenError::InvalidFeePolicy)?;
+        let minimum = config.existential_deposit;
+        if minimum > self.config.existential_deposit {
+            let stranded = self.balances.values().find(|b| **b > 0 && **b < minimum);
+            if let Some(&balance) = stranded {
+                return Err(TokenError::BelowExistentialDeposit { balance, minimum });
+            }
+        }
+        self.config = config.clone();
+        // 최소 잔액이 생기면 0 잔액 항목은 남아 있을 수 없으므로 정리
+        if minimum > 0 {
+            let empty: Vec<_> = self
+                .balances
+                .iter()
+                .filter(|(_, b)| **b == 0)
+                .map(|(a, _)| a.clone())
+                .collect();
+            for address in &empty {
+                self.write_balance(address, 0);
+            }
+        }
+        self.emit(TokenEvent::ConfigChanged {
+            config: config.clone(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_parse_config_text() {
+        let text = "
+            # tuned for mainnet replay
+            self_transfer = noop
+            existential_deposit = 10   # dust floor
+            dust_policy = sweep
+            burn_addresses = 0x000000000000000000000000000000000000dEaD, sink
+            fee = tiered 0:30, 10000:10
+            fee_destination = treasury vault
+            soulbound = true
+            update_delay = 86400
+        ";
+
+        let config: TokenConfig = text.parse().unwrap();
+
+        assert_eq!(config.self_transfer, EdgeCaseMode::NoOp);
+        assert_eq!(config.zero_amount, EdgeCaseMode::Reject);
+        assert_eq!(config.existential_deposit, 10);
+        assert_eq!(config.dust_policy, DustPolicy::Sweep);
+        assert_eq!(config.burn_addresses.len(), 2);
+        assert!(
+            config
+                .burn_addresses
+                .contains(&Address::new("sink").unwrap())
+        );
+        assert_eq!(
+            config.fee,
+            FeePolicy::Tiered(vec![
+                FeeTier {
+                    min_amount: 0,
+                    basis_points: 30
+                },
+                FeeTier {
+                    min_amount: 10_000,
+                    basis_points: 10
+                },
+            ])
+        );
+        assert_eq!(
+            config.fee_destination,
+            FeeDestination::Treasury(Address::new("vault").unwrap())
+        );
+        assert!(config.soulbound);
+        assert_eq!(config.update_delay, 86_400);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let err = "zero_amount = noop\nfee_bps = 30".parse::<TokenConfig>();
+
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 2: unknown key \"fee_bps\""
+        );
+        assert!("dust_policy = burn".parse::<TokenConfig>().is_err());
+        assert!("existential_deposit".parse::<TokenConfig>().is_err());
+        assert!("fee = bps 70000".parse::<TokenConfig>().is_err());
+        assert!("fee = bps 10001".parse::<TokenConfig>().is_err());
+        assert!("fee = tiered 100:10, 50:20".parse::<TokenConfig>().is_err());
+        assert!("fee_destination = vault".parse::<TokenConfig>().is_err());
+    }
+
+    #[test]
+    fn test_update_config_swaps_and_emits() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let config = TokenConfig::erc20_compatible();
+
+        token.update_config(&alice, config.clone()).unwrap();
+        token.transfer(&alice, &bob, 0).unwrap();
+
+        assert_eq!(token.config(), &config);
+        assert_eq!(token.events()[0].1, TokenEvent::ConfigChanged { config });
+    }
+
+    #[test]
+    fn test_update_config_requires_admin() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mallory = Address::new("mallory").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.grant_role(&alice, Role::Admin, &bob).unwrap();
+
+        let result = token.update_config(&mallory, TokenConfig::erc20_compatible());
+        token
+            .update_config(&bob, TokenConfig::erc20_compatible())
+            .unwrap();
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::Unauthorized { caller: mallory }
+        );
+        assert_eq!(token.config(), &TokenConfig::erc20_compatible());
+    }
+
+    #[test]
+    fn test_update_config_rejects_stranding_balances() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 5).unwrap();
+        let config = TokenConfig {
+            existential_deposit: 10,
+            ..TokenConfig::default()
+        };
+
+        let result = token.update_config(&alice, config);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+        assert_eq!(token.config(), &TokenConfig::default());
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_raising_deposit_drops_empty_accounts() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 20).unwrap();
+        token.transfer(&bob, &alice, 20).unwrap();
+        let config = TokenConfig {
+            existential_deposit: 10,
+            ..TokenConfig::default()
+        };
+
+        token.update_config(&alice, config).unwrap();
+        let restored = TokenState::from_bytes(&token.to_bytes());
+
+        assert_eq!(token.account_count(), 1);
+        assert_eq!(token.check_invariants(), Ok(()));
+        assert!(restored.is_ok(), "{:?}", restored.err());
+    }
+
+    fn timelocked(owner: &Address, clock: &ManualClock) -> TokenState {
+        let config = TokenConfig {
+            update_delay: 100,
+            ..TokenConfig::default()
+        };
+        TokenState::with_config(owner.clone(), 1000, config).with_clock(Box::new(clock.clone()))
+    }
+
+    #[test]
+    fn test_timelocked_update_waits_out_the_delay() {
+        let alice = Address::new("alice").unwrap();
+        let clock = ManualClock::new(1_000);
+        let mut token = timelocked(&alice, &clock);
+        let config = TokenConfig::erc20_compatible();
+
+        let instant = token.update_config(&alice, config.clone());
+        let id = token.queue_config_update(&alice, config.clone()).unwrap();
+        clock.advance(99);
+        let early = token.execute_config_update(&alice, id);
+        let restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+        clock.advance(1);
+        token.execute_config_update(&alice, id).unwrap();
+
+        assert_eq!(instant, Err(TokenError::ConfigTimelocked { delay: 100 }));
+        assert_eq!(
+            early,
+            Err(TokenError::ConfigUpdateNotReady {
+                eta: 1_100,
+                now: 1_099
+            })
+        );
+        assert_eq!(
+            restored.queued_config_updates(),
+            [(
+                id,
+                &QueuedConfig {
+                    config: config.clone(),
+                    eta: 1_100
+                }
+            )]
+        );
+        assert_eq!(token.config(), &config);
+        assert_eq!(token.queued_config_update(id), None);
+        let events: Vec<_> = token.events().iter().map(|(_, e)| e.clone()).collect();
+        assert_eq!(
+            events,
+            [
+                TokenEvent::ConfigUpdateQueued {
+                    id,
+                    eta: 1_100,
+                    config: config.clone()
+                },
+                TokenEvent::ConfigChanged { config },
+                TokenEvent::ConfigUpdateExecuted { id },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancelled_update_is_never_applied() {
+        let alice = Address::new("alice").unwrap();
+        let mallory = Address::new("mallory").unwrap();
+        let clock = ManualClock::new(0);
+        let mut token = timelocked(&alice, &clock);
+        // 지연을 줄이는 변경도 현재 지연을 기다려야 함
+        let no_delay = TokenConfig::default();
+
+        let queued = token.queue_config_update(&mallory, no_delay.clone());
+        let id = token.queue_config_update(&alice, no_delay).unwrap();
+        let stranger = token.cancel_config_update(&mallory, id);
+        token.cancel_config_update(&alice, id).unwrap();
+        clock.advance(100);
+
+        assert_eq!(
+            queued,
+            Err(TokenError::Unauthorized {
+                caller: mallory.clone()
+            })
+        );
+        assert_eq!(stranger, Err(TokenError::Unauthorized { caller: mallory }));
+        assert_eq!(
+            token.execute_config_update(&alice, id),
+            Err(TokenError::UnknownConfigUpdate)
+        );
+        assert_eq!(token.config().update_delay, 100);
+        assert_eq!(
+            token.events().last().map(|(_, e)| e),
+            Some(&TokenEvent::ConfigUpdateCancelled { id })
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! Reusable conformance suite for [`FungibleToken`] implementations.
+//!
+//! Every case runs the same operations against the implementation under
+//! test and against a small, independent reference model, and compares
+//! results and resulting balances/allowances. Alternative backends call
+//! [`run`] to show they behave exactly like [`TokenState`].
+//!
+//! Balance overflow is not covered: with supply conserved, no sequence of
+//! transfers can push a single balance past `total_supply <= Balance::MAX`.
+
+use std::collections::BTreeMap;
+
+use crate::rng::Rng;
+use crate::{Address, Balance, FungibleToken, TokenError, TokenState};
+
+/// Implementations the suite can construct on its own.
+pub trait TokenFactory: FungibleToken + Sized {
+    fn create(creator: Address, initial_supply: Balance) -> Self;
+}
+
+impl TokenFactory for TokenState {
+    fn create(creator: Address, initial_supply: Balance) -> Self {
+        TokenState::new(creator, initial_supply)
+    }
+}
+
+/// A case whose outcome diverged from the reference model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub case: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn record(&mut self, case: String, outcome: Result<(), String>) {
+        match outcome {
+            Ok(()) => self.passed += 1,
+            Err(detail) => self.failures.push(Failure { case, detail }),
+        }
+    }
+}
+
+/// Runs every conformance case against `T`.
+pub fn run<T: TokenFactory>() -> Report {
+    let mut report = Report::default();
+    transfer_grid::<T>(&mut report);
+    allowance_grid::<T>(&mut report);
+    allowance_exhaustion::<T>(&mut report);
+    for seed in 0..16 {
+        let case = format!("random sequence seed={seed}");
+        let outcome = random_sequence::<T>(seed, 250);
+        report.record(case, outcome);
+    }
+    report
+}
+
+const ADDRS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+const SUPPLIES: [Balance; 4] = [1, 2, 1000, Balance::MAX];
+
+fn amounts(supply: Balance) -> [Balance; 6] {
+    [
+        0,
+        1,
+        supply / 2,
+        supply.saturating_sub(1),
+        supply,
+        supply.saturating_add(1),
+    ]
+}
+
+fn addr(i: usize) -> Address {
+    Address::trusted(ADDRS[i].to_string())
+}
+
+// 자기 자신 전송 포함 모든 (from, to) 조합 × 공급량 × 금액
+fn transfer_grid<T: TokenFactory>(report: &mut Report) {
+    for supply in SUPPLIES {
+        for amount in amounts(supply) {
+            for (from, to) in [(0, 1), (0, 0), (1, 0)] {
+                let case = format!("transfer {from}->{to} amount={amount} supply={supply}");
+                let mut pair = Pair::<T>::new(supply);
+                let outcome = pair.transfer(&addr(from), &addr(to), amount);
+                report.record(case, outcome);
+            }
+        }
+    }
+}
+
+fn allowance_grid<T: TokenFactory>(report: &mut Report) {
+    for supply in SUPPLIES {
+        for allowance in amounts(supply) {
+            for amount in amounts(supply) {
+                let case =
+                    format!("transfer_from allowance={allowance} amount={amount} supply={supply}");
+                let mut pair = Pair::<T>::new(supply);
+                let outcome = pair
+                    .approve(&addr(0), &addr(1), allowance)
+                    .and_then(|_| pair.transfer_from(&addr(1), &addr(0), &addr(2), amount));
+                report.record(case, outcome);
+            }
+        }
+        let case = format!("self approval supply={supply}");
+        let mut pair = Pair::<T>::new(supply);
+        let outcome = pair.approve(&addr(0), &addr(0), 1);
+        report.record(case, outcome);
+    }
+}
+
+fn allowance_exhaustion<T: TokenFactory>(report: &mut Report) {
+    for step in [1, 3, 7, 10] {
+        let case = format!("allowance exhaustion step={step}");
+        let mut pair = Pair::<T>::new(1000);
+        let mut outcome = pair.approve(&addr(0), &addr(1), 10);
+        for _ in 0..12 {
+            outcome = outcome.and_then(|_| pair.transfer_from(&addr(1), &addr(0), &addr(2), step));
+        }
+        report.record(case, outcome);
+    }
+}
+
+fn random_sequence<T: TokenFactory>(seed: u64, ops: usize) -> Result<(), String> {
+    let mut rng = Rng::new(seed);
+    let supply = [1000, Balance::MAX][seed as usize % 2];
+    let mut pair = Pair::<T>::new(supply);
+
+    for _ in 0..ops {
+        let a = addr(rng.below(4) as usize);
+        let b = addr(rng.below(4) as usize);
+        let c = addr(rng.below(4) as usize);
+        // Balance 폭은 feature에 따라 u64/u128이므로 cast가 항등일 수 있음
+        #[allow(clippy::unnecessary_cast)]
+        let amount = match rng.below(3) {
+            0 => rng.below(4) as Balance,
+            1 => rng.below((supply / 4).min(u64::MAX as Balance) as u64) as Balance,
+            _ => rng.next_u64() as Balance,
+        };
+        match rng.below(3) {
+            0 => pair.transfer(&a, &b, amount)?,
+            1 => pair.approve(&a, &b, amount)?,
+            _ => pair.transfer_from(&a, &b, &c, amount)?,
+        }
+    }
+    Ok(())
+}
+
+/// Runs each operation against both the implementation and the model.
+struct Pair<T> {
+    subject: T,
+    model: Model,
+}
+
+impl<T: TokenFactory> Pair<T> {
+    fn new(supply: Balance) -> Self {
+        Self {
+            subject: T::create(addr(0), supply),
+            model: Model::new(addr(0), supply),
+        }
+    }
+
+    fn transfer(&mut self, from: &Address, to: &Address, amount: Balance) -> Result<(), String> {
+        let got = self.subject.transfer(from, to, amount);
+        let want = self.model.transfer(from, to, amount);
+        self.compare("transfer", got, want)
+    }
+
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), String> {
+        let got = self.subject.approve(owner, spender, amount);
+        let want = self.model.approve(owner, spender, amount);
+        self.compare("approve", got, want)
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), String> {
+        let got = self.subject.transfer_from(spender, from, to, amount);
+        let want = self.model.transfer_from(spender, from, to, amount);
+        self.compare("transfer_from", got, want)
+    }
+
+    fn compare(
+        &self,
+        op: &str,
+        got: Result<(), TokenError>,
+        want: Result<(), TokenError>,
+    ) -> Result<(), String> {
+        if got != want {
+            return Err(format!("{op}: got {got:?}, expected {want:?}"));
+        }
+        if self.subject.total_supply() != self.model.supply {
+            return Err(format!(
+                "{op}: total_supply {} != {}",
+                self.subject.total_supply(),
+                self.model.supply
+            ));
+        }
+        for i in 0..ADDRS.len() {
+            let a = addr(i);
+            let (got, want) = (self.subject.balance_of(&a), self.model.balance_of(&a));
+            if got != want {
+                return Err(format!("{op}: balance_of({a}) {got} != {want}"));
+            }
+            for j in 0..ADDRS.len() {
+                let s = addr(j);
+                let (got, want) = (self.subject.allowance(&a, &s), self.model.allowance(&a, &s));
+                if got != want {
+                    return Err(format!("{op}: allowance({a}, {s}) {got} != {want}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Straight-line reference semantics, deliberately independent of TokenState.
+struct Model {
+    balances: BTreeMap<Address, Balance>,
+    allowances: BTreeMap<(Address, Address), Balance>,
+    supply: Balance,
+}
+
+impl Model {
+    fn new(creator: Address, supply: Balance) -> Self {
+        Self {
+            balances: BTreeMap::from([(creator, supply)]),
+            allowances: BTreeMap::new(),
+            supply,
+        }
+    }
+
+    fn balance_of(&self, a: &Address) -> Balance {
+        *self.balances.get(a).unwrap_or(&0)
+    }
+
+    fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        *self
+            .allowances
+            .get(&(owner.clone(), spender.clone()))
+            .unwrap_or(&0)
+    }
+
+    fn transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if from == to {
+            return Err(TokenError::SelfTransfer);
+        }
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        let available = self.balance_of(from);
+        if available < amount {
+            return Err(TokenError::InsufficientBalance {
+                required: amount,
+                available,
+            });
+        }
+        let credited = self
+            .balance_of(to)
+            .checked_add(amount)
+            .ok_or(TokenError::BalanceOverFlow)?;
+        self.balances.insert(from.clone(), available - amount);
+        self.balances.insert(to.clone(), credited);
+        Ok(())
+    }
+
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if owner == spender {
+            return Err(TokenError::SelfApproval);
+        }
+        self.allowances
+            .insert((owner.clone(), spender.clone()), amount);
+        Ok(())
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if from == to {
+            return Err(TokenError::SelfTransfer);
+        }
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        let allowed = self.allowance(from, spender);
+        if allowed < amount {
+            return Err(TokenError::InsufficientAllowance {
+                required: amount,
+                available: allowed,
+            });
+        }
+        self.transfer(from, to, amount)?;
+        self.allowances
+            .insert((from.clone(), spender.clone()), allowed - amount);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Forgets to consume allowance on transfer_from.
+    struct LeakyAllowance(TokenState);
+
+    impl FungibleToken for LeakyAllowance {
+        fn total_supply(&self) -> Balance {
+            self.0.total_supply()
+        }
+        fn balance_of(&self, a: &Address) -> Balance {
+            self.0.balance_of(a)
+        }
+        fn transfer(&mut self, f: &Address, t: &Address, n: Balance) -> Result<(), TokenError> {
+            self.0.transfer(f, t, n)
+        }
+        fn approve(&mut self, o: &Address, s: &Address, n: Balance) -> Result<(), TokenError> {
+            self.0.approve(o, s, n)
+        }
+        fn allowance(&self, o: &Address, s: &Address) -> Balance {
+            self.0.allowance(o, s)
+        }
+        fn transfer_from(
+            &mut self,
+            s: &Address,
+            f: &Address,
+            t: &Address,
+            n: Balance,
+        ) -> Result<(), TokenError> {
+            if self.0.allowance(f, s) < n {
+                return Err(TokenError::InsufficientAllowance {
+                    required: n,
+                    available: self.0.allowance(f, s),
+                });
+            }
+            self.0.transfer(f, t, n)
+        }
+    }
+
+    impl TokenFactory for LeakyAllowance {
+        fn create(creator: Address, initial_supply: Balance) -> Self {
+            Self(TokenState::new(creator, initial_supply))
+        }
+    }
+
+    #[test]
+    fn test_token_state_conforms() {
+        let report = run::<TokenState>();
+
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert!(report.passed > 200);
+    }
+
+    #[test]
+    fn test_broken_implementation_is_caught() {
+        let report = run::<LeakyAllowance>();
+
+        assert!(!report.is_ok());
+        assert!(
+            report
+                .failures
+                .iter()
+                .any(|f| f.case.starts_with("allowance exhaustion"))
+        );
+    }
+}
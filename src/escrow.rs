@@ -0,0 +1,329 @@
+//! Two-party escrow held by the ledger itself.
+//!
+//! [`TokenState::escrow_create`] moves `amount` out of the payer's
+//! available balance into an escrow record, so marketplaces no longer
+//! need an intermediate address to park funds. The escrow is settled
+//! exactly once, by either party giving way: the payer releases it to the
+//! payee with [`TokenState::escrow_release`], or the payee refunds it to
+//! the payer with [`TokenState::escrow_refund`]. Once the payee is frozen
+//! the escrow can no longer be released, so a [`Role::Freezer`] may then
+//! refund it in the payee's place rather than leave the funds stuck.
+//!
+//! Escrowed tokens stay in `total_supply` but leave the public balances,
+//! as shielded ones do. Creating an escrow follows the debit rules of a
+//! transfer (locks, dust, freezes) and settling one the credit rules, so a
+//! frozen recipient cannot be paid out of escrow and a payout to a new
+//! account counts against a tenant's account quota. A payee that is frozen
+//! or a burn address is refused at creation: a release would otherwise be
+//! stuck or credit a balance that a transfer would have burned. Open
+//! escrows are ledger state and are saved in snapshots.
+
+use std::fmt;
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, Balance, Role, TokenError, TokenState};
+
+/// Identifies an escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscrowId(pub u64);
+
+impl fmt::Display for EscrowId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "escrow #{}", self.0)
+    }
+}
+
+/// Funds held until the payer releases or the payee refunds them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Escrow {
+    pub payer: Address,
+    pub payee: Address,
+    pub amount: Balance,
+}
+
+impl TokenState {
+    /// Moves `amount` of `payer`'s tokens into escrow for `payee`.
+    pub fn escrow_create(
+        &mut self,
+        payer: &Address,
+        payee: &Address,
+        amount: Balance,
+    ) -> Result<EscrowId, TokenError> {
+        let id = EscrowId(self.next_escrow_id);
+        self.execute(Operation::EscrowCreate {
+            payer: payer.clone(),
+            payee: payee.clone(),
+            amount,
+        })?;
+        Ok(id)
+    }
+
+    /// Pays escrow `id` out to its payee; only the payer may.
+    pub fn escrow_release(&mut self, caller: &Address, id: EscrowId) -> Result<(), TokenError> {
+        self.execute(Operation::EscrowRelease {
+            caller: caller.clone(),
+            id,
+        })
+        .map(|_| ())
+    }
+
+    /// Returns escrow `id` to its payer; only the payee may, or a
+    /// [`Role::Freezer`] once the payee is frozen.
+    pub fn escrow_refund(&mut self, caller: &Address, id: EscrowId) -> Result<(), TokenError> {
+        self.execute(Operation::EscrowRefund {
+            caller: caller.clone(),
+            id,
+        })
+        .map(|_| ())
+    }
+
+    /// The open escrow `id`, if any.
+    pub fn escrow(&self, id: EscrowId) -> Option<&Escrow> {
+        self.escrows.get(&id)
+    }
+
+    /// Tokens held in open escrows.
+    pub fn escrowed_supply(&self) -> Balance {
+        self.escrows.values().map(|escrow| escrow.amount).sum()
+    }
+
+    pub(crate) fn apply_escrow_create(
+        &mut self,
+        payer: &Address,
+        payee: &Address,
+        amount: Balance,
+    ) -> Result<Balance, TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        if payer == payee {
+            return Err(TokenError::SelfTransfer);
+        }
+        if self.frozen.contains(payee) {
+            return Err(TokenError::AccountFrozen {
+                address: payee.clone(),
+            });
+        }
+        // 소각 주소로의 지급은 공급량을 줄이지 않으므로 생성 단계에서 거부
+        if self.config.burn_addresses.contains(payee) {
+            return Err(TokenError::RecipientNotPermitted { to: payee.clone() });
+        }
+        let payer_bal = self.check_spendable(payer, amount)?;
+        let remaining = payer_bal - amount;
+        let minimum = self.config.existential_deposit;
+        if remaining > 0 && remaining < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: remaining,
+                minimum,
+            });
+        }
+
+        let id = EscrowId(self.next_escrow_id);
+        self.write_balance(payer, remaining);
+        self.journal_escrow(id);
+        self.escrows.insert(
+            id,
+            Escrow {
+                payer: payer.clone(),
+                payee: payee.clone(),
+                amount,
+            },
+        );
+        self.next_escrow_id += 1;
+        self.emit(TokenEvent::EscrowCreated {
+            id,
+            payer: payer.clone(),
+            payee: payee.clone(),
+            amount,
+        });
+        Ok(amount)
+    }
+
+    pub(crate) fn apply_escrow_settle(
+        &mut self,
+        caller: &Address,
+        id: EscrowId,
+        release: bool,
+    ) -> Result<Balance, TokenError> {
+        let escrow = self.escrows.get(&id).ok_or(TokenError::UnknownEscrow)?;
+        let (authority, to) = if release {
+            (&escrow.payer, &escrow.payee)
+        } else {
+            (&escrow.payee, &escrow.payer)
+        };
+        // 동결된 수령인에게 묶인 자금은 Freezer가 지급인에게 돌려줄 수 있음
+        let forced_refund =
+            !release && self.frozen.contains(&escrow.payee) && self.has_role(Role::Freezer, caller);
+        if caller != authority && !forced_refund {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        // 수령인 동결 여부는 dispatch의 check_not_frozen이 recipients로 검사
+        let (to, amount) = (to.clone(), escrow.amount);
+
+        let to_bal = self
+            .balance_of(&to)
+            .checked_add(amount)
+            .ok_or(TokenError::BalanceOverFlow)?;
+        let minimum = self.config.existential_deposit;
+        if to_bal < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: to_bal,
+                minimum,
+            });
+        }
+
+        self.journal_escrow(id);
+        self.escrows.remove(&id);
+        self.write_balance(&to, to_bal);
+        let event = if release {
+            TokenEvent::EscrowReleased { id, to, amount }
+        } else {
+            TokenEvent::EscrowRefunded { id, to, amount }
+        };
+        self.emit(event);
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::addresses;
+
+    #[test]
+    fn test_release_pays_payee_and_refund_returns_to_payer() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let first = token.escrow_create(&alice, &bob, 300).unwrap();
+        let second = token.escrow_create(&alice, &bob, 200).unwrap();
+        let held = (token.balance_of(&alice), token.escrowed_supply());
+        token.escrow_release(&alice, first).unwrap();
+        token.escrow_refund(&bob, second).unwrap();
+
+        assert_eq!(held, (500, 500));
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.balance_of(&bob), 300);
+        assert_eq!(token.escrowed_supply(), 0);
+        assert_eq!(
+            token.escrow_release(&alice, first),
+            Err(TokenError::UnknownEscrow)
+        );
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_only_the_yielding_party_may_settle() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let id = token.escrow_create(&alice, &bob, 300).unwrap();
+
+        let payee_release = token.escrow_release(&bob, id);
+        let payer_refund = token.escrow_refund(&alice, id);
+
+        assert_eq!(
+            payee_release,
+            Err(TokenError::Unauthorized {
+                caller: bob.clone()
+            })
+        );
+        assert_eq!(
+            payer_refund,
+            Err(TokenError::Unauthorized {
+                caller: alice.clone()
+            })
+        );
+        assert_eq!(
+            token.escrow(id),
+            Some(&Escrow {
+                payer: alice,
+                payee: bob,
+                amount: 300
+            })
+        );
+    }
+
+    #[test]
+    fn test_freezer_refunds_escrow_of_frozen_payee() {
+        let [alice, bob, carol, dave] = addresses(["alice", "bob", "carol", "dave"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.grant_role(&alice, Role::Freezer, &carol).unwrap();
+        token.transfer(&alice, &bob, 400).unwrap();
+        let id = token.escrow_create(&bob, &dave, 300).unwrap();
+        token.freeze_account(&carol, &dave).unwrap();
+
+        let released = token.escrow_release(&bob, id);
+        let payer_refund = token.escrow_refund(&bob, id);
+        token.escrow_refund(&carol, id).unwrap();
+
+        assert_eq!(
+            released,
+            Err(TokenError::AccountFrozen {
+                address: dave.clone()
+            })
+        );
+        assert_eq!(
+            payer_refund,
+            Err(TokenError::Unauthorized {
+                caller: bob.clone()
+            })
+        );
+        assert_eq!(token.balance_of(&bob), 400);
+        assert_eq!(token.balance_of(&dave), 0);
+        assert_eq!(token.escrowed_supply(), 0);
+    }
+
+    #[test]
+    fn test_frozen_or_burn_payee_is_refused_at_creation() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let dead = Address::new(crate::BURN_ADDRESS).unwrap();
+        let mut token = TokenState::with_config(
+            alice.clone(),
+            1000,
+            crate::TokenConfig {
+                burn_addresses: [dead.clone()].into(),
+                ..Default::default()
+            },
+        );
+        token.freeze_account(&alice, &bob).unwrap();
+
+        let to_frozen = token.escrow_create(&alice, &bob, 100);
+        let to_burn = token.escrow_create(&alice, &dead, 100);
+
+        assert_eq!(
+            to_frozen,
+            Err(TokenError::AccountFrozen {
+                address: bob.clone()
+            })
+        );
+        assert_eq!(to_burn, Err(TokenError::RecipientNotPermitted { to: dead }));
+        assert_eq!(token.balance_of(&alice), 1000);
+        assert_eq!(token.escrowed_supply(), 0);
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_escrows_survive_snapshot_and_revert() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let kept = token.escrow_create(&alice, &bob, 100).unwrap();
+        let checkpoint = token.checkpoint();
+        token.escrow_release(&alice, kept).unwrap();
+        token.escrow_create(&alice, &bob, 50).unwrap();
+        token.revert_to(checkpoint).unwrap();
+
+        let mut restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+        let next = restored.escrow_create(&alice, &bob, 1).unwrap();
+        restored.escrow_release(&alice, kept).unwrap();
+
+        assert_eq!(token.escrowed_supply(), 100);
+        assert_eq!(next, EscrowId(kept.0 + 1));
+        assert_eq!(restored.balance_of(&bob), 100);
+        assert_eq!(restored.check_invariants(), Ok(()));
+    }
+}
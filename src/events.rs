@@ -0,0 +1,352 @@
+//! Log of balance and allowance changes for indexers and auditors.
+//!
+//! Every successful mutation appends the events it caused, tagged with the
+//! [`Receipt::seq`](crate::Receipt::seq) of the operation that emitted them.
+//! Operations that fail, or succeed as no-ops, emit nothing. The log grows
+//! until drained, so long-running consumers should call
+//! [`TokenState::drain_events`] regularly.
+//!
+//! Integrators that want a push model register an [`EventSink`]; sinks are
+//! called in registration order as each event is emitted, in addition to
+//! the event being logged.
+
+use crate::{
+    Address, Balance, CapabilityId, ConfigUpdateId, EscrowId, Role, TokenConfig, TokenState,
+};
+
+/// A single observable state change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenEvent {
+    /// Tokens moved between two public balances
+    Transfer {
+        from: Address,
+        to: Address,
+        amount: Balance,
+    },
+    /// An allowance was set (also emitted when it is lowered or revoked)
+    Approval {
+        owner: Address,
+        spender: Address,
+        /// Allowance before the change
+        previous: Balance,
+        amount: Balance,
+    },
+    /// An allowance was deleted by `revoke_allowance` or `revoke_all`
+    AllowanceRevoked {
+        owner: Address,
+        spender: Address,
+        previous: Balance,
+    },
+    /// `spender` used `amount` of its allowance from `owner`, by
+    /// `transfer_from` or `burn_from`
+    AllowanceConsumed {
+        owner: Address,
+        spender: Address,
+        amount: Balance,
+        remaining: Balance,
+    },
+    /// `issuer` granted capability `id`, worth up to `limit`
+    CapabilityGranted {
+        id: CapabilityId,
+        issuer: Address,
+        limit: Balance,
+    },
+    /// The bearer of capability `id` sent `amount` to `to`
+    CapabilityRedeemed {
+        id: CapabilityId,
+        to: Address,
+        amount: Balance,
+        remaining: Balance,
+    },
+    /// `issuer` withdrew capability `id`
+    CapabilityRevoked { id: CapabilityId, issuer: Address },
+    /// New tokens were created, by `mint` or `bulk_credit`
+    Mint { to: Address, amount: Balance },
+    /// Tokens were destroyed, by `burn` or a send to a burn address
+    Burn { from: Address, amount: Balance },
+    /// `amount` of `owner`'s balance was locked until `unlock_at`
+    Locked {
+        owner: Address,
+        amount: Balance,
+        unlock_at: u64,
+    },
+    /// Matured locks worth `amount` were removed
+    Unlocked { owner: Address, amount: Balance },
+    /// `amount` left `payer`'s balance for escrow `id`
+    EscrowCreated {
+        id: EscrowId,
+        payer: Address,
+        payee: Address,
+        amount: Balance,
+    },
+    /// The payer paid escrow `id` out to the payee `to`
+    EscrowReleased {
+        id: EscrowId,
+        to: Address,
+        amount: Balance,
+    },
+    /// The payee returned escrow `id` to the payer `to`
+    EscrowRefunded {
+        id: EscrowId,
+        to: Address,
+        amount: Balance,
+    },
+    /// Tokens left the public balances for the shielded pool
+    Shield { from: Address, amount: Balance },
+    /// A shielded note was released to a public balance
+    Unshield { to: Address, amount: Balance },
+    /// The configuration was replaced; carries the new configuration
+    ConfigChanged { config: TokenConfig },
+    /// `config` was queued as update `id`, executable from `eta`
+    ConfigUpdateQueued {
+        id: ConfigUpdateId,
+        eta: u64,
+        config: TokenConfig,
+    },
+    /// Queued update `id` was applied
+    ConfigUpdateExecuted { id: ConfigUpdateId },
+    /// Queued update `id` was dropped without being applied
+    ConfigUpdateCancelled { id: ConfigUpdateId },
+    /// The owner suspended holder operations
+    Paused { by: Address },
+    /// The owner resumed holder operations
+    Unpaused { by: Address },
+    /// Ownership passed from `previous` to `owner`
+    OwnershipTransferred { previous: Address, owner: Address },
+    /// `owner` nominated `pending` as its successor
+    OwnershipTransferStarted { owner: Address, pending: Address },
+    /// The token was left without an owner
+    OwnershipRenounced { previous: Address },
+    /// The account can no longer send or receive
+    AccountFrozen { address: Address },
+    /// A freeze was lifted
+    AccountUnfrozen { address: Address },
+    /// `by` granted `role` to `account`
+    RoleGranted {
+        role: Role,
+        account: Address,
+        by: Address,
+    },
+    /// `by` revoked `role` from `account`
+    RoleRevoked {
+        role: Role,
+        account: Address,
+        by: Address,
+    },
+}
+
+/// Receives every event as it is emitted.
+pub trait EventSink {
+    fn on_event(&mut self, event: &TokenEvent);
+}
+
+impl TokenState {
+    pub fn add_event_sink(&mut self, sink: Box<dyn EventSink>) {
+        self.event_sinks.push(sink);
+    }
+
+    pub fn clear_event_sinks(&mut self) {
+        self.event_sinks.clear();
+    }
+
+    /// Events emitted since the log was last drained, oldest first, each
+    /// paired with the sequence number of its operation.
+    pub fn events(&self) -> &[(u64, TokenEvent)] {
+        &self.events
+    }
+
+    /// Takes all logged events, leaving the log empty.
+    pub fn drain_events(&mut self) -> Vec<(u64, TokenEvent)> {
+        std::mem::take(&mut self.events)
+    }
+
+    // 현재 실행 중인 operation의 seq를 붙여 기록 (성공 경로에서만 호출)
+    pub(crate) fn emit(&mut self, event: TokenEvent) {
+        for sink in self.event_sinks.iter_mut() {
+            sink.on_event(&event);
+        }
+        self.events.push((self.next_seq, event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::record_events;
+    use crate::{AcceptAllVerifier, BURN_ADDRESS, TokenConfig, TokenError};
+
+    #[test]
+    fn test_transfer_and_approval_events() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&bob, &charlie, 50).unwrap();
+        token.transfer_from(&charlie, &bob, &alice, 30).unwrap();
+
+        assert_eq!(
+            token.events(),
+            &[
+                (
+                    0,
+                    TokenEvent::Transfer {
+                        from: alice.clone(),
+                        to: bob.clone(),
+                        amount: 100
+                    }
+                ),
+                (
+                    1,
+                    TokenEvent::Approval {
+                        owner: bob.clone(),
+                        spender: charlie.clone(),
+                        previous: 0,
+                        amount: 50
+                    }
+                ),
+                (
+                    2,
+                    TokenEvent::Transfer {
+                        from: bob.clone(),
+                        to: alice.clone(),
+                        amount: 30
+                    }
+                ),
+                (
+                    2,
+                    TokenEvent::AllowanceConsumed {
+                        owner: bob.clone(),
+                        spender: charlie.clone(),
+                        amount: 30,
+                        remaining: 20
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_and_noop_operations_emit_nothing() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 100, TokenConfig::erc20_compatible());
+
+        token.transfer(&alice, &bob, 500).unwrap_err();
+        token.transfer(&alice, &bob, 0).unwrap();
+        token.transfer(&alice, &alice, 10).unwrap();
+
+        assert!(token.events().is_empty());
+    }
+
+    #[test]
+    fn test_supply_changes_emit_mint_and_burn() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let dead = Address::new(BURN_ADDRESS).unwrap();
+        let config = TokenConfig {
+            burn_addresses: [dead.clone()].into(),
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+
+        token.mint(&alice, &bob, 10).unwrap();
+        token.burn(&alice, 20).unwrap();
+        token.transfer(&alice, &dead, 30).unwrap();
+        token.bulk_credit(&alice, [(alice.clone(), 5)]).unwrap();
+
+        let events: Vec<_> = token.drain_events().into_iter().map(|(_, e)| e).collect();
+        assert_eq!(
+            events,
+            vec![
+                TokenEvent::Mint {
+                    to: bob.clone(),
+                    amount: 10
+                },
+                TokenEvent::Burn {
+                    from: alice.clone(),
+                    amount: 20
+                },
+                TokenEvent::Burn {
+                    from: alice.clone(),
+                    amount: 30
+                },
+                TokenEvent::Mint {
+                    to: alice.clone(),
+                    amount: 5
+                },
+            ]
+        );
+        assert!(token.events().is_empty());
+    }
+
+    #[test]
+    fn test_shield_round_trip_events() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+
+        let note = token.shield(&alice, 40).unwrap();
+        token.unshield(&bob, &note, b"proof").unwrap();
+        let replay = token.unshield(&bob, &note, b"proof");
+
+        assert_eq!(replay.unwrap_err(), TokenError::NoteAlreadySpent);
+        assert_eq!(
+            token.events(),
+            &[
+                (
+                    0,
+                    TokenEvent::Shield {
+                        from: alice.clone(),
+                        amount: 40
+                    }
+                ),
+                (
+                    1,
+                    TokenEvent::Unshield {
+                        to: bob.clone(),
+                        amount: 40
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sinks_receive_events_as_emitted() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let first = record_events(&mut token);
+        let second = record_events(&mut token);
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.transfer(&alice, &bob, 5000).unwrap_err();
+
+        let expected = vec![TokenEvent::Transfer {
+            from: alice.clone(),
+            to: bob.clone(),
+            amount: 100,
+        }];
+        assert_eq!(*first.borrow(), expected);
+        assert_eq!(*second.borrow(), expected);
+        assert_eq!(token.events().len(), 1);
+    }
+
+    #[test]
+    fn test_cleared_sinks_stop_receiving() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let seen = record_events(&mut token);
+
+        token.clear_event_sinks();
+        token.transfer(&alice, &bob, 100).unwrap();
+
+        assert!(seen.borrow().is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+//! Ethereum-typed entry points (`evm-types` feature).
+//!
+//! Code built on ethers-rs holds addresses as `H160` and amounts as `U256`;
+//! this feature pulls in ethers-core and converts its types directly. An
+//! [`H160`] becomes an [`Address`] with `From` and comes back with
+//! `TryFrom<&Address>`. A [`Balance`] widens to a [`U256`] with ethers' own
+//! `From`, and [`balance_from_u256`] narrows back, failing with
+//! [`EvmError::AmountOverflow`] rather than truncating.
+//!
+//! Addresses are compared in lowercase: an `H160` converts to a lowercase
+//! `0x` address, and [`Address::new`] lowercases anything shaped like one,
+//! so a checksummed address in a config or call names the same account.
+//!
+//! [`TokenState::execute_calldata`] runs ABI-encoded ERC-20 calls
+//! (`transfer`, `approve` and `transferFrom`) with the given sender as
+//! `msg.sender`, and [`encode_call`] produces the same calldata from an
+//! [`Operation`], so a client can hand over exactly what it would send to a
+//! contract.
+
+use std::fmt;
+
+pub use ethers_core::types::{H160, U256};
+
+use crate::operation::{Operation, Receipt};
+use crate::{
+    Address, AddressError, AddressValidator, Balance, EvmValidator, TokenError, TokenState,
+};
+
+// keccak256 서명의 앞 4바이트
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+/// Why an Ethereum-typed value or call could not be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvmError {
+    /// The address is not `0x` and 40 hex digits
+    InvalidAddress(AddressError),
+    /// The amount does not fit in a [`Balance`]
+    AmountOverflow(U256),
+    /// The calldata is not a well-formed call this ledger understands
+    InvalidCalldata(&'static str),
+    /// The operation has no ERC-20 call to encode it as
+    Unsupported,
+    /// The ledger rejected the decoded call
+    Token(TokenError),
+}
+
+impl fmt::Display for EvmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddress(e) => write!(f, "not an EVM address: {e}"),
+            Self::AmountOverflow(_) => f.write_str("amount does not fit in a Balance"),
+            Self::InvalidCalldata(why) => write!(f, "invalid calldata: {why}"),
+            Self::Unsupported => f.write_str("operation has no ERC-20 call"),
+            Self::Token(e) => write!(f, "{e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for EvmError {}
+
+impl From<H160> for Address {
+    fn from(address: H160) -> Self {
+        let hex: String = address.0.iter().map(|b| format!("{b:02x}")).collect();
+        Address::trusted(format!("0x{hex}"))
+    }
+}
+
+impl TryFrom<&Address> for H160 {
+    type Error = EvmError;
+
+    fn try_from(address: &Address) -> Result<Self, EvmError> {
+        let raw = address.as_str();
+        EvmValidator
+            .validate(raw)
+            .map_err(EvmError::InvalidAddress)?;
+        let mut bytes = [0; 20];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            // 검증을 통과했으므로 모두 16진수 두 자리
+            *byte = u8::from_str_radix(&raw[2 + 2 * i..4 + 2 * i], 16).unwrap();
+        }
+        Ok(H160(bytes))
+    }
+}
+
+/// Narrows an ethers amount to a [`Balance`].
+///
+/// A free function rather than `TryFrom`, which the orphan rule forbids
+/// between two foreign types.
+pub fn balance_from_u256(amount: U256) -> Result<Balance, EvmError> {
+    Balance::try_from(amount).map_err(|_| EvmError::AmountOverflow(amount))
+}
+
+/// Decodes ERC-20 calldata sent by `sender` into the matching operation.
+pub fn decode_call(sender: &Address, calldata: &[u8]) -> Result<Operation, EvmError> {
+    let Some((selector, args)) = calldata.split_first_chunk::<4>() else {
+        return Err(EvmError::InvalidCalldata("shorter than a selector"));
+    };
+    let arity = match *selector {
+        TRANSFER | APPROVE => 2,
+        TRANSFER_FROM => 3,
+        _ => return Err(EvmError::InvalidCalldata("unknown selector")),
+    };
+    if args.len() != 32 * arity {
+        return Err(EvmError::InvalidCalldata("wrong argument length"));
+    }
+    let word = |i: usize| -> [u8; 32] { args[32 * i..32 * (i + 1)].try_into().unwrap() };
+
+    Ok(match *selector {
+        TRANSFER => Operation::Transfer {
+            from: sender.clone(),
+            to: address_word(word(0))?,
+            amount: balance_from_u256(U256::from_big_endian(&word(1)))?,
+        },
+        APPROVE => Operation::Approve {
+            owner: sender.clone(),
+            spender: address_word(word(0))?,
+            amount: balance_from_u256(U256::from_big_endian(&word(1)))?,
+        },
+        _ => Operation::TransferFrom {
+            spender: sender.clone(),
+            from: address_word(word(0))?,
+            to: address_word(word(1))?,
+            amount: balance_from_u256(U256::from_big_endian(&word(2)))?,
+        },
+    })
+}
+
+/// Encodes `op` as the ERC-20 calldata its sender would submit.
+///
+/// Only transfers, approvals and delegated transfers have one; every
+/// address in `op` must be an EVM address.
+pub fn encode_call(op: &Operation) -> Result<Vec<u8>, EvmError> {
+    let (selector, addresses, amount) = match op {
+        Operation::Transfer { to, amount, .. } => (TRANSFER, vec![to], amount),
+        Operation::Approve {
+            spender, amount, ..
+        } => (APPROVE, vec![spender], amount),
+        Operation::TransferFrom {
+            from, to, amount, ..
+        } => (TRANSFER_FROM, vec![from, to], amount),
+        _ => return Err(EvmError::Unsupported),
+    };
+    let mut calldata = selector.to_vec();
+    for address in addresses {
+        calldata.extend([0; 12]);
+        calldata.extend(H160::try_from(address)?.0);
+    }
+    let mut word = [0; 32];
+    U256::from(*amount).to_big_endian(&mut word);
+    calldata.extend(word);
+    Ok(calldata)
+}
+
+// 주소 word는 앞 12바이트가 0이어야 함
+fn address_word(word: [u8; 32]) -> Result<Address, EvmError> {
+    let (padding, address) = word.split_at(12);
+    if padding.iter().any(|b| *b != 0) {
+        return Err(EvmError::InvalidCalldata("address word is not zero-padded"));
+    }
+    Ok(H160(address.try_into().unwrap()).into())
+}
+
+impl TokenState {
+    /// Decodes ERC-20 calldata from `sender` and executes it.
+    pub fn execute_calldata(
+        &mut self,
+        sender: &Address,
+        calldata: &[u8],
+    ) -> Result<Receipt, EvmError> {
+        let op = decode_call(sender, calldata)?;
+        self.execute(op).map_err(EvmError::Token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenConfig;
+
+    fn h160(last: u8) -> H160 {
+        let mut bytes = [0; 20];
+        bytes[19] = last;
+        H160(bytes)
+    }
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_address_conversions() {
+        let address = Address::from(H160([0xab; 20]));
+
+        assert_eq!(address.as_str(), format!("0x{}", "ab".repeat(20)));
+        assert_eq!(H160::try_from(&address), Ok(H160([0xab; 20])));
+        // 대소문자가 섞인 체크섬 주소도 같은 계정
+        let checksummed = Address::new(format!("0x{}", "AB".repeat(20))).unwrap();
+        assert_eq!(H160::try_from(&checksummed), Ok(H160([0xab; 20])));
+        assert!(matches!(
+            H160::try_from(&Address::new("alice").unwrap()),
+            Err(EvmError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_amount_conversions() {
+        let mut word = [0; 32];
+        U256::from(1_000 as Balance).to_big_endian(&mut word);
+
+        assert_eq!(word[30..], [0x03, 0xe8]);
+        assert_eq!(balance_from_u256(U256::from_big_endian(&word)), Ok(1_000));
+        assert_eq!(
+            balance_from_u256(U256::from(Balance::MAX)),
+            Ok(Balance::MAX)
+        );
+        assert_eq!(
+            balance_from_u256(U256::MAX),
+            Err(EvmError::AmountOverflow(U256::MAX))
+        );
+    }
+
+    #[test]
+    fn test_executes_erc20_calldata() {
+        let alice = Address::from(h160(0xa1));
+        let bob = Address::from(h160(0xb0));
+        let mut token = TokenState::new(alice.clone(), 1000);
+        // transfer(0x…b0, 250), as a wallet would encode it
+        let calldata = hex(&format!(
+            "a9059cbb{:0>64}{:0>64}",
+            "b0",
+            format!("{:x}", 250)
+        ));
+
+        token.execute_calldata(&alice, &calldata).unwrap();
+        let approve = Operation::Approve {
+            owner: bob.clone(),
+            spender: alice.clone(),
+            amount: 100,
+        };
+        token
+            .execute_calldata(&bob, &encode_call(&approve).unwrap())
+            .unwrap();
+
+        assert_eq!(token.balance_of(&bob), 250);
+        assert_eq!(token.allowance(&bob, &alice), 100);
+        assert_eq!(
+            decode_call(&alice, &calldata),
+            Ok(Operation::Transfer {
+                from: alice,
+                to: bob,
+                amount: 250,
+            })
+        );
+    }
+
+    #[test]
+    fn test_checksummed_addresses_share_balances_with_h160() {
+        let alice = Address::from(h160(0xa1));
+        let checksummed = Address::new(format!("0x{}", "AB".repeat(20))).unwrap();
+        let config = TokenConfig {
+            burn_addresses: [Address::new(crate::BURN_ADDRESS).unwrap()].into(),
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        let mut dead = [0; 20];
+        dead[18..].copy_from_slice(&[0xde, 0xad]);
+        let send = |to: H160, amount: Balance| {
+            encode_call(&Operation::Transfer {
+                from: alice.clone(),
+                to: Address::from(to),
+                amount,
+            })
+            .unwrap()
+        };
+
+        token
+            .execute_calldata(&alice, &send(H160([0xab; 20]), 100))
+            .unwrap();
+        token
+            .execute_calldata(&alice, &send(H160(dead), 300))
+            .unwrap();
+
+        // 체크섬 표기로 조회해도 H160으로 받은 잔액이 보임
+        assert_eq!(token.balance_of(&checksummed), 100);
+        // 설정의 0x…dEaD와 H160의 0x…dead가 같은 소각 주소
+        assert_eq!(token.balance_of(&Address::from(H160(dead))), 0);
+        assert_eq!(token.total_supply(), 700);
+    }
+
+    #[test]
+    fn test_rejects_malformed_calldata() {
+        let alice = Address::from(h160(0xa1));
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let transfer_from = Operation::TransferFrom {
+            spender: alice.clone(),
+            from: alice.clone(),
+            to: Address::from(h160(0xb0)),
+            amount: 1,
+        };
+        let good = encode_call(&transfer_from).unwrap();
+        let mut dirty = good.clone();
+        dirty[4] = 1;
+
+        assert_eq!(decode_call(&alice, &good), Ok(transfer_from));
+        assert_eq!(
+            token.execute_calldata(&alice, &good[..40]),
+            Err(EvmError::InvalidCalldata("wrong argument length"))
+        );
+        assert_eq!(
+            token.execute_calldata(&alice, &dirty),
+            Err(EvmError::InvalidCalldata("address word is not zero-padded"))
+        );
+        assert_eq!(
+            token.execute_calldata(&alice, &[0xde, 0xad, 0xbe, 0xef]),
+            Err(EvmError::InvalidCalldata("unknown selector"))
+        );
+        assert_eq!(
+            token.execute_calldata(&alice, &good),
+            Err(EvmError::Token(TokenError::InsufficientAllowance {
+                available: 0,
+                required: 1
+            }))
+        );
+        assert_eq!(
+            encode_call(&Operation::Burn {
+                from: alice,
+                amount: 1
+            }),
+            Err(EvmError::Unsupported)
+        );
+    }
+}
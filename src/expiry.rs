@@ -0,0 +1,138 @@
+//! Allowances that lapse.
+//!
+//! [`TokenState::approve_until`] sets an allowance that stops working once
+//! the token's [`Clock`](crate::clock::Clock) passes `expires_at`, in
+//! whatever unit the clock counts (seconds or block heights). From then on
+//! [`TokenState::allowance`] reads 0 and `transfer_from`, `burn_from` and
+//! batches fail with [`TokenError::AllowanceExpired`].
+//!
+//! An expiry belongs to the approval that set it. `approve`, `approve_if`
+//! and `permit` grant a fresh allowance with no expiry; increasing or
+//! decreasing an allowance keeps its expiry, and fails once it has lapsed.
+//! Revoking deletes the expiry along with the allowance.
+
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError, TokenState};
+
+impl TokenState {
+    /// Lets `spender` move up to `amount` of `owner`'s tokens until
+    /// `expires_at`, inclusive.
+    pub fn approve_until(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+        expires_at: u64,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::ApproveUntil {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount,
+            expires_at,
+        })
+        .map(|_| ())
+    }
+
+    /// When `owner`'s allowance to `spender` lapses; `None` if it doesn't.
+    pub fn allowance_expiry(&self, owner: &Address, spender: &Address) -> Option<u64> {
+        self.allowance_usage
+            .get(&(owner.clone(), spender.clone()))
+            .and_then(|usage| usage.expires_at)
+    }
+
+    pub(crate) fn apply_approve_until(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+        expires_at: u64,
+    ) -> Result<(), TokenError> {
+        self.set_allowance(owner, spender, amount, Some(expires_at))
+    }
+
+    // 만료됐으면 (만료 시점, 현재 시각)
+    pub(crate) fn allowance_expired(
+        &self,
+        owner: &Address,
+        spender: &Address,
+    ) -> Option<(u64, u64)> {
+        let expires_at = self.allowance_expiry(owner, spender)?;
+        let now = self.now();
+        (now > expires_at).then_some((expires_at, now))
+    }
+
+    pub(crate) fn check_allowance_expiry(
+        &self,
+        owner: &Address,
+        spender: &Address,
+    ) -> Result<(), TokenError> {
+        match self.allowance_expired(owner, spender) {
+            Some((expires_at, now)) => Err(TokenError::AllowanceExpired { expires_at, now }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[test]
+    fn test_expired_allowance_reads_zero_and_rejects() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_clock(Box::new(FixedClock(100)));
+        token.approve_until(&alice, &bob, 50, 200).unwrap();
+        token.transfer_from(&bob, &alice, &bob, 20).unwrap();
+
+        token.set_clock(Box::new(FixedClock(201)));
+        let late = token.transfer_from(&bob, &alice, &bob, 10);
+        let raised = token.increase_allowance(&alice, &bob, 10);
+
+        assert_eq!(token.allowance(&alice, &bob), 0);
+        assert_eq!(
+            late,
+            Err(TokenError::AllowanceExpired {
+                expires_at: 200,
+                now: 201
+            })
+        );
+        assert_eq!(raised, late);
+        assert_eq!(token.balance_of(&bob), 20);
+    }
+
+    #[test]
+    fn test_plain_approval_clears_expiry() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_clock(Box::new(FixedClock(100)));
+        token.approve_until(&alice, &bob, 50, 200).unwrap();
+
+        token.increase_allowance(&alice, &bob, 10).unwrap();
+        let kept = token.allowance_expiry(&alice, &bob);
+        token.approve(&alice, &bob, 5).unwrap();
+
+        assert_eq!(kept, Some(200));
+        assert_eq!(token.allowance_expiry(&alice, &bob), None);
+        token.set_clock(Box::new(FixedClock(1000)));
+        assert_eq!(token.allowance(&alice, &bob), 5);
+    }
+
+    #[test]
+    fn test_expiry_survives_snapshot() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve_until(&alice, &bob, 50, 200).unwrap();
+
+        let mut restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+        restored.set_clock(Box::new(FixedClock(300)));
+
+        assert_eq!(restored.allowance_expiry(&alice, &bob), Some(200));
+        assert_eq!(restored.allowance(&alice, &bob), 0);
+        assert_eq!(restored.to_bytes(), token.to_bytes());
+    }
+}
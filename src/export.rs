@@ -0,0 +1,400 @@
+//! Full-state export that doesn't hold up writes.
+//!
+//! [`TokenState::export`] copies the balance and allowance tables in one
+//! pass and hands back a [`StateExport`] that owns the copy. The ledger is
+//! borrowed only for that copy, so it can keep executing operations while
+//! the export is streamed out at whatever pace the consumer (an analytics
+//! job, a replica being bootstrapped) can manage. Every record reflects the
+//! state as of [`StateExport::seq`]; writes made after the call don't show
+//! up.
+//!
+//! Records come out balances first, then allowances, each in address order,
+//! and [`StateExport::progress`] reports how far along the stream is.
+//!
+//! [`TokenState::export_scrubbed`] produces the same stream for sharing
+//! outside the operator, followed by every transfer still in the event log,
+//! with each address replaced by a keyed pseudonym from a [`Scrubber`]. The
+//! pseudonym is an HMAC-SHA256 of the address under the caller's key, so the
+//! same account maps to the same pseudonym everywhere it appears (as holder,
+//! owner, spender, sender or recipient, and across exports made with the
+//! same key) and the transaction graph survives intact, while nobody without
+//! the key can link a pseudonym back to its account.
+//!
+//! Amounts are exact unless the scrubber rounds them down into buckets.
+//! Records whose amount rounds to zero are left out, the same as zero
+//! balances and allowances, so a bucket width also hides the smallest
+//! holdings and payments. The total supply is always exact.
+
+use crate::events::TokenEvent;
+use crate::sha256::hmac;
+use crate::{Address, Balance, TokenState};
+
+/// One row of an export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportRecord {
+    Balance {
+        address: Address,
+        balance: Balance,
+    },
+    Allowance {
+        owner: Address,
+        spender: Address,
+        amount: Balance,
+    },
+    /// A transfer from the event log, in the order it happened; only
+    /// scrubbed exports carry these
+    Transfer {
+        from: Address,
+        to: Address,
+        amount: Balance,
+    },
+}
+
+impl ExportRecord {
+    fn amount(&self) -> Balance {
+        match self {
+            ExportRecord::Balance { balance, .. } => *balance,
+            ExportRecord::Allowance { amount, .. } | ExportRecord::Transfer { amount, .. } => {
+                *amount
+            }
+        }
+    }
+}
+
+/// Records streamed so far out of the total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportProgress {
+    pub exported: usize,
+    pub total: usize,
+}
+
+impl ExportProgress {
+    /// Fraction complete, from 0.0 to 1.0.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.exported as f64 / self.total as f64
+        }
+    }
+}
+
+/// A point-in-time copy of a ledger, consumed as a stream of records.
+pub struct StateExport {
+    seq: u64,
+    total_supply: Balance,
+    total: usize,
+    records: std::vec::IntoIter<ExportRecord>,
+}
+
+impl StateExport {
+    /// Sequence number of the next operation at the time of the export.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn total_supply(&self) -> Balance {
+        self.total_supply
+    }
+
+    pub fn progress(&self) -> ExportProgress {
+        ExportProgress {
+            exported: self.total - self.records.len(),
+            total: self.total,
+        }
+    }
+}
+
+impl Iterator for StateExport {
+    type Item = ExportRecord;
+
+    fn next(&mut self) -> Option<ExportRecord> {
+        self.records.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
+
+impl ExactSizeIterator for StateExport {}
+
+/// Pseudonymizes addresses, and optionally coarsens amounts, for
+/// [`TokenState::export_scrubbed`].
+pub struct Scrubber {
+    key: Vec<u8>,
+    bucket: Balance,
+}
+
+impl Scrubber {
+    /// Pseudonyms keyed by `key`; keep the key secret, or anyone can test
+    /// guessed addresses against the pseudonyms.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: key.into(),
+            bucket: 1,
+        }
+    }
+
+    /// Rounds every amount down to a multiple of `width`; a width of 0 or 1
+    /// keeps amounts exact.
+    pub fn with_amount_buckets(mut self, width: Balance) -> Self {
+        self.bucket = width.max(1);
+        self
+    }
+
+    /// The pseudonym for `address`: `0x` and the first 20 bytes of its
+    /// HMAC, so it is also a valid EVM-style address.
+    pub fn pseudonym(&self, address: &Address) -> Address {
+        let digest = hmac(&self.key, address.as_str().as_bytes());
+        let hex: String = digest[..20].iter().map(|b| format!("{b:02x}")).collect();
+        Address::trusted(format!("0x{hex}"))
+    }
+
+    pub fn amount(&self, amount: Balance) -> Balance {
+        amount - amount % self.bucket
+    }
+
+    fn record(&self, record: ExportRecord) -> ExportRecord {
+        match record {
+            ExportRecord::Balance { address, balance } => ExportRecord::Balance {
+                address: self.pseudonym(&address),
+                balance: self.amount(balance),
+            },
+            ExportRecord::Allowance {
+                owner,
+                spender,
+                amount,
+            } => ExportRecord::Allowance {
+                owner: self.pseudonym(&owner),
+                spender: self.pseudonym(&spender),
+                amount: self.amount(amount),
+            },
+            ExportRecord::Transfer { from, to, amount } => ExportRecord::Transfer {
+                from: self.pseudonym(&from),
+                to: self.pseudonym(&to),
+                amount: self.amount(amount),
+            },
+        }
+    }
+}
+
+impl TokenState {
+    /// Captures every non-zero balance and allowance for streaming export.
+    ///
+    /// O(n log n) in accounts plus allowances; the returned export does not
+    /// borrow the ledger.
+    pub fn export(&self) -> StateExport {
+        let mut balances: Vec<_> = self
+            .balances
+            .iter()
+            .filter(|(_, b)| **b > 0)
+            .map(|(a, b)| (a.clone(), *b))
+            .collect();
+        balances.sort_unstable();
+        let mut allowances: Vec<_> = self
+            .allowances
+            .iter()
+            .filter(|(_, a)| **a > 0)
+            .map(|((owner, spender), a)| (owner.clone(), spender.clone(), *a))
+            .collect();
+        allowances.sort_unstable();
+
+        let mut records = Vec::with_capacity(balances.len() + allowances.len());
+        records.extend(
+            balances
+                .into_iter()
+                .map(|(address, balance)| ExportRecord::Balance { address, balance }),
+        );
+        records.extend(allowances.into_iter().map(|(owner, spender, amount)| {
+            ExportRecord::Allowance {
+                owner,
+                spender,
+                amount,
+            }
+        }));
+        StateExport {
+            seq: self.next_seq(),
+            total_supply: self.total_supply,
+            total: records.len(),
+            records: records.into_iter(),
+        }
+    }
+
+    /// Like [`TokenState::export`], with addresses and amounts passed
+    /// through `scrubber` and the logged transfers appended; records that
+    /// scrub to a zero amount are dropped.
+    ///
+    /// Balances and allowances are re-sorted by pseudonym, so their order
+    /// says nothing about the original addresses; transfers follow in log
+    /// order.
+    pub fn export_scrubbed(&self, scrubber: &Scrubber) -> StateExport {
+        let export = self.export();
+        let (seq, total_supply) = (export.seq, export.total_supply);
+        let mut records: Vec<_> = export
+            .map(|r| scrubber.record(r))
+            .filter(|r| r.amount() > 0)
+            .collect();
+        // Balance 레코드가 Allowance보다 먼저 오도록 variant 순서로 정렬
+        records.sort_unstable_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+        let transfers = self.events.iter().filter_map(|(_, event)| match event {
+            TokenEvent::Transfer { from, to, amount } => Some(ExportRecord::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                amount: *amount,
+            }),
+            _ => None,
+        });
+        records.extend(
+            transfers
+                .map(|r| scrubber.record(r))
+                .filter(|r| r.amount() > 0),
+        );
+        StateExport {
+            seq,
+            total_supply,
+            total: records.len(),
+            records: records.into_iter(),
+        }
+    }
+}
+
+fn sort_key(record: &ExportRecord) -> (u8, &Address, Option<&Address>) {
+    match record {
+        ExportRecord::Balance { address, .. } => (0, address, None),
+        ExportRecord::Allowance { owner, spender, .. } => (1, owner, Some(spender)),
+        ExportRecord::Transfer { from, to, .. } => (2, from, Some(to)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_is_isolated_from_later_writes() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 50).unwrap();
+
+        let mut export = token.export();
+        let first = export.next();
+        // export 도중에도 쓰기는 계속됨
+        token.transfer(&alice, &bob, 100).unwrap();
+        let rest: Vec<_> = export.by_ref().collect();
+
+        assert_eq!(
+            first,
+            Some(ExportRecord::Balance {
+                address: alice.clone(),
+                balance: 1000
+            })
+        );
+        assert_eq!(
+            rest,
+            [ExportRecord::Allowance {
+                owner: alice.clone(),
+                spender: bob.clone(),
+                amount: 50
+            }]
+        );
+        assert_eq!(export.seq(), 1);
+        assert_eq!(token.balance_of(&bob), 100);
+    }
+
+    #[test]
+    fn test_scrubbed_export_keeps_graph_and_hides_addresses() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 256).unwrap();
+        token.approve(&bob, &alice, 175).unwrap();
+        let scrubber = Scrubber::new(b"research-2026".to_vec()).with_amount_buckets(100);
+        let (p_alice, p_bob) = (scrubber.pseudonym(&alice), scrubber.pseudonym(&bob));
+
+        let records: Vec<_> = token.export_scrubbed(&scrubber).collect();
+
+        assert!(records.contains(&ExportRecord::Balance {
+            address: p_alice.clone(),
+            balance: 700
+        }));
+        assert!(records.contains(&ExportRecord::Balance {
+            address: p_bob.clone(),
+            balance: 200
+        }));
+        assert_eq!(
+            records[2..],
+            [
+                ExportRecord::Allowance {
+                    owner: p_bob.clone(),
+                    spender: p_alice.clone(),
+                    amount: 100
+                },
+                ExportRecord::Transfer {
+                    from: p_alice,
+                    to: p_bob,
+                    amount: 200
+                },
+            ]
+        );
+        assert_eq!(
+            Address::with_validator(scrubber.pseudonym(&alice).as_str(), &crate::EvmValidator)
+                .map(|_| ()),
+            Ok(())
+        );
+        assert_ne!(
+            Scrubber::new(b"other-key".to_vec()).pseudonym(&alice),
+            scrubber.pseudonym(&alice)
+        );
+    }
+
+    #[test]
+    fn test_amounts_that_bucket_to_zero_are_dropped() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 40).unwrap();
+        token.approve(&alice, &bob, 99).unwrap();
+        let scrubber = Scrubber::new(b"k".to_vec()).with_amount_buckets(100);
+
+        let export = token.export_scrubbed(&scrubber);
+
+        assert_eq!(export.total_supply(), 1000);
+        assert_eq!(
+            export.collect::<Vec<_>>(),
+            [ExportRecord::Balance {
+                address: scrubber.pseudonym(&alice),
+                balance: 900
+            }]
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 1).unwrap();
+        let mut export = token.export();
+
+        export.next();
+
+        assert_eq!(
+            export.progress(),
+            ExportProgress {
+                exported: 1,
+                total: 2
+            }
+        );
+        assert_eq!(export.progress().ratio(), 0.5);
+        assert_eq!(
+            TokenState::from_parts(Default::default(), 0, Default::default())
+                .export()
+                .progress()
+                .ratio(),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Transfer fees.
+//!
+//! A [`FeePolicy`] in [`TokenConfig`](crate::TokenConfig) takes a cut of
+//! every `transfer`, `transfer_from` and capability redemption: the sender
+//! is debited the full amount, the recipient is credited the amount less
+//! the fee, and the fee goes wherever [`FeeDestination`] says — to a
+//! treasury account, or out of supply altogether. The policy is ordinary
+//! configuration, so it can be changed on a live token with
+//! `update_config` and is saved in snapshots.
+//!
+//! Transfers to a burn address are not charged: the whole amount leaves
+//! supply anyway. A flat fee that exceeds the amount it is charged on
+//! fails the transfer with [`TokenError::FeeExceedsAmount`](crate::TokenError::FeeExceedsAmount);
+//! one that equals it leaves the recipient nothing and the transfer logs
+//! only the fee. While a fee is configured the treasury counts as a
+//! recipient of every charged operation, so freezing it suspends them
+//! rather than letting a frozen account collect fees.
+//!
+//! Rates above 10 000 basis points and tier schedules out of order are
+//! rejected by [`FeePolicy::validate`], which `update_config` and the
+//! config parser run.
+
+use crate::{Address, Balance};
+
+/// One step of a [`FeePolicy::Tiered`] schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeTier {
+    /// Smallest transfer amount the tier applies to
+    pub min_amount: Balance,
+    /// Fee in hundredths of a percent
+    pub basis_points: u16,
+}
+
+/// How much a transfer is charged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeePolicy {
+    /// No fee
+    #[default]
+    None,
+    /// The same fee on every transfer
+    Flat(Balance),
+    /// A share of the amount, in hundredths of a percent, rounded down
+    BasisPoints(u16),
+    /// The rate of the highest tier whose `min_amount` the amount reaches;
+    /// no fee below every tier. Tiers are listed by strictly ascending
+    /// `min_amount`.
+    Tiered(Vec<FeeTier>),
+}
+
+/// Where collected fees go.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeeDestination {
+    /// Destroyed, reducing total supply
+    #[default]
+    Burn,
+    /// Credited to this account
+    Treasury(Address),
+}
+
+impl FeePolicy {
+    /// Checks that every rate is at most 10 000 basis points and that
+    /// tiers are in strictly ascending order of `min_amount`.
+    pub fn validate(&self) -> Result<(), String> {
+        let rates: Vec<u16> = match self {
+            FeePolicy::None | FeePolicy::Flat(_) => Vec::new(),
+            FeePolicy::BasisPoints(bps) => vec![*bps],
+            FeePolicy::Tiered(tiers) => {
+                if let Some(pair) = tiers
+                    .windows(2)
+                    .find(|w| w[0].min_amount >= w[1].min_amount)
+                {
+                    return Err(format!(
+                        "fee tier at {} does not follow tier at {}",
+                        pair[1].min_amount, pair[0].min_amount
+                    ));
+                }
+                tiers.iter().map(|tier| tier.basis_points).collect()
+            }
+        };
+        match rates.into_iter().find(|bps| *bps > 10_000) {
+            Some(bps) => Err(format!("fee rate {bps} exceeds 10000 basis points")),
+            None => Ok(()),
+        }
+    }
+
+    /// Fee charged on a transfer of `amount`.
+    ///
+    /// May exceed `amount` for a flat fee; such a transfer is refused.
+    pub fn fee_for(&self, amount: Balance) -> Balance {
+        match self {
+            FeePolicy::None => 0,
+            FeePolicy::Flat(fee) => *fee,
+            FeePolicy::BasisPoints(bps) => basis_points(amount, *bps),
+            FeePolicy::Tiered(tiers) => tiers
+                .iter()
+                .filter(|tier| tier.min_amount <= amount)
+                .max_by_key(|tier| tier.min_amount)
+                .map_or(0, |tier| basis_points(amount, tier.basis_points)),
+        }
+    }
+}
+
+// amount * bps / 10_000 을 곱셈 overflow 없이 계산
+fn basis_points(amount: Balance, bps: u16) -> Balance {
+    let bps = Balance::from(bps);
+    (amount / 10_000)
+        .saturating_mul(bps)
+        .saturating_add(amount % 10_000 * bps / 10_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::TokenEvent;
+    use crate::test_util::{addresses, record_events};
+    use crate::{TokenConfig, TokenError, TokenState};
+
+    fn with_fee(owner: &Address, fee: FeePolicy, fee_destination: FeeDestination) -> TokenState {
+        let config = TokenConfig {
+            fee,
+            fee_destination,
+            ..TokenConfig::default()
+        };
+        TokenState::with_config(owner.clone(), 10_000, config)
+    }
+
+    #[test]
+    fn test_policies_compute_fee() {
+        let tiered = FeePolicy::Tiered(vec![
+            FeeTier {
+                min_amount: 100,
+                basis_points: 100,
+            },
+            FeeTier {
+                min_amount: 1_000,
+                basis_points: 50,
+            },
+        ]);
+
+        assert_eq!(FeePolicy::None.fee_for(500), 0);
+        assert_eq!(FeePolicy::Flat(3).fee_for(500), 3);
+        assert_eq!(FeePolicy::BasisPoints(30).fee_for(12_345), 37);
+        assert_eq!(
+            FeePolicy::BasisPoints(10_000).fee_for(Balance::MAX),
+            Balance::MAX
+        );
+        assert_eq!(tiered.fee_for(99), 0);
+        assert_eq!(tiered.fee_for(500), 5);
+        assert_eq!(tiered.fee_for(2_000), 10);
+        assert_eq!(tiered.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_policies_rejected() {
+        let [alice] = addresses(["alice"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let tier = |min_amount, basis_points| FeeTier {
+            min_amount,
+            basis_points,
+        };
+        let unsorted = FeePolicy::Tiered(vec![tier(1_000, 50), tier(100, 100)]);
+        let duplicate = FeePolicy::Tiered(vec![tier(100, 50), tier(100, 100)]);
+        let steep = FeePolicy::Tiered(vec![tier(0, 10_001)]);
+
+        let update = token.update_config(
+            &alice,
+            TokenConfig {
+                fee: FeePolicy::BasisPoints(10_001),
+                ..TokenConfig::default()
+            },
+        );
+
+        assert!(unsorted.validate().is_err());
+        assert!(duplicate.validate().is_err());
+        assert!(steep.validate().is_err());
+        assert_eq!(FeePolicy::BasisPoints(10_000).validate(), Ok(()));
+        assert!(matches!(update, Err(TokenError::InvalidFeePolicy(_))));
+        assert_eq!(token.config().fee, FeePolicy::None);
+    }
+
+    #[test]
+    fn test_fee_to_frozen_treasury_rejected() {
+        let [alice, bob, treasury] = addresses(["alice", "bob", "treasury"]);
+        let mut token = with_fee(
+            &alice,
+            FeePolicy::Flat(5),
+            FeeDestination::Treasury(treasury.clone()),
+        );
+        token.freeze_account(&alice, &treasury).unwrap();
+
+        let result = token.transfer(&alice, &bob, 100);
+
+        assert_eq!(result, Err(TokenError::AccountFrozen { address: treasury }));
+        assert_eq!(token.balance_of(&bob), 0);
+    }
+
+    #[test]
+    fn test_fee_routed_to_treasury_or_burned() {
+        let [alice, bob, treasury] = addresses(["alice", "bob", "treasury"]);
+        let mut paid = with_fee(
+            &alice,
+            FeePolicy::BasisPoints(100),
+            FeeDestination::Treasury(treasury.clone()),
+        );
+        let mut burned = with_fee(&alice, FeePolicy::Flat(7), FeeDestination::Burn);
+        let events = record_events(&mut burned);
+
+        paid.transfer(&alice, &bob, 1_000).unwrap();
+        burned.transfer(&alice, &bob, 1_000).unwrap();
+
+        assert_eq!(paid.balance_of(&alice), 9_000);
+        assert_eq!(paid.balance_of(&bob), 990);
+        assert_eq!(paid.balance_of(&treasury), 10);
+        assert_eq!(paid.total_supply(), 10_000);
+        assert_eq!(burned.balance_of(&bob), 993);
+        assert_eq!(burned.total_supply(), 9_993);
+        assert_eq!(
+            events.borrow().as_slice(),
+            [
+                TokenEvent::Transfer {
+                    from: alice.clone(),
+                    to: bob,
+                    amount: 993
+                },
+                TokenEvent::Burn {
+                    from: alice,
+                    amount: 7
+                },
+            ]
+        );
+        assert_eq!(paid.check_invariants(), Ok(()));
+        assert_eq!(burned.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_fee_above_amount_rejected() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = with_fee(&alice, FeePolicy::Flat(10), FeeDestination::Burn);
+
+        let events = record_events(&mut token);
+
+        let small = token.transfer(&alice, &bob, 5);
+        token.transfer(&alice, &bob, 10).unwrap();
+
+        assert_eq!(
+            small,
+            Err(TokenError::FeeExceedsAmount { fee: 10, amount: 5 })
+        );
+        assert_eq!(token.balance_of(&alice), 9_990);
+        assert_eq!(token.balance_of(&bob), 0);
+        assert!(!token.balances.contains_key(&bob));
+        assert_eq!(
+            events.borrow().as_slice(),
+            [TokenEvent::Burn {
+                from: alice,
+                amount: 10
+            }]
+        );
+    }
+}
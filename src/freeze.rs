@@ -0,0 +1,145 @@
+//! Per-account freezes for sanctioned addresses.
+//!
+//! A [`Role::Freezer`] can freeze an address, after which any operation
+//! that would debit or credit it fails with [`TokenError::AccountFrozen`]:
+//! transfers in either direction, `transfer_from` as the source or
+//! destination, mints, burns, shielding and unshielding. Allowances the
+//! account granted or holds are left untouched, so unfreezing restores it
+//! exactly as it was.
+
+use std::collections::HashSet;
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, Role, TokenError, TokenState};
+
+impl TokenState {
+    pub fn freeze_account(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::FreezeAccount {
+            caller: caller.clone(),
+            address: address.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub fn unfreeze_account(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::UnfreezeAccount {
+            caller: caller.clone(),
+            address: address.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub fn is_frozen(&self, address: &Address) -> bool {
+        self.frozen.contains(address)
+    }
+
+    /// Every frozen address, unordered.
+    pub fn frozen_accounts(&self) -> &HashSet<Address> {
+        &self.frozen
+    }
+
+    pub(crate) fn apply_set_frozen(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+        frozen: bool,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Freezer)?;
+        let changed = if frozen {
+            self.frozen.insert(address.clone())
+        } else {
+            self.frozen.remove(address)
+        };
+        if changed {
+            let address = address.clone();
+            self.emit(if frozen {
+                TokenEvent::AccountFrozen { address }
+            } else {
+                TokenEvent::AccountUnfrozen { address }
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_not_frozen(&self, op: &Operation) -> Result<(), TokenError> {
+        if self.frozen.is_empty() {
+            return Ok(());
+        }
+        let mut parties = op.senders().into_iter().chain(op.recipients(self));
+        match parties.find(|a| self.frozen.contains(*a)) {
+            Some(address) => Err(TokenError::AccountFrozen {
+                address: address.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_account_cannot_send_or_receive() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 100).unwrap();
+
+        token.freeze_account(&alice, &bob).unwrap();
+
+        let frozen = Err(TokenError::AccountFrozen {
+            address: bob.clone(),
+        });
+        assert_eq!(token.transfer(&bob, &alice, 1), frozen);
+        assert_eq!(token.transfer(&alice, &bob, 1), frozen);
+        assert_eq!(token.mint(&alice, &bob, 1), frozen);
+        assert_eq!(token.balance_of(&bob), 100);
+        assert!(token.is_frozen(&bob));
+    }
+
+    #[test]
+    fn test_frozen_source_blocks_transfer_from() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&bob, &carol, 50).unwrap();
+        token.freeze_account(&alice, &bob).unwrap();
+
+        let result = token.transfer_from(&carol, &bob, &carol, 10);
+
+        assert_eq!(
+            result,
+            Err(TokenError::AccountFrozen {
+                address: bob.clone()
+            })
+        );
+        assert_eq!(token.allowance(&bob, &carol), 50);
+    }
+
+    #[test]
+    fn test_unfreeze_and_authorization() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        assert!(token.freeze_account(&bob, &alice).is_err());
+        token.freeze_account(&alice, &bob).unwrap();
+        token.unfreeze_account(&alice, &bob).unwrap();
+        token.transfer(&alice, &bob, 5).unwrap();
+
+        assert!(!token.is_frozen(&bob));
+        assert_eq!(token.balance_of(&bob), 5);
+    }
+}
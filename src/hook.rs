@@ -0,0 +1,170 @@
+//! Callbacks around every movement of tokens between two accounts.
+//!
+//! Where an [`Interceptor`](crate::Interceptor) sees whole operations, a
+//! [`TransferHook`] sees the transfers inside them: the one in `transfer`,
+//! `transfer_from` and capability redemption, including transfers into a
+//! burn address. That makes it the place for ERC-777-style compliance
+//! checks and accounting that should not care which method moved the
+//! tokens. Mints, burns, shielding and escrow do not pass through hooks.
+//!
+//! Hooks run in registration order. `before_transfer` is called before the
+//! transfer is checked against dust, fee and overflow rules, and the first
+//! hook to return an error vetoes it: the error fails the operation and
+//! later hooks are skipped. `after_transfer` is called once the balances
+//! are written, with the amount actually debited, which is larger than the
+//! one requested when a dust sweep takes the whole balance. Rolling back
+//! does not recall it: an operation that fails after its transfer, or a
+//! batch or transaction that rolls back, has already reported the
+//! transfer to `after_transfer`.
+//!
+//! Like interceptors, hooks get no handle to the [`TokenState`] and are
+//! detached while they run, so they cannot re-enter the token.
+
+use crate::{Address, Balance, TokenError, TokenState};
+
+pub trait TransferHook {
+    /// Called before `amount` moves from `from` to `to`; an error vetoes it.
+    fn before_transfer(
+        &mut self,
+        _from: &Address,
+        _to: &Address,
+        _amount: Balance,
+    ) -> Result<(), TokenError> {
+        Ok(())
+    }
+
+    /// Called after `amount` was debited from `from` for `to`.
+    fn after_transfer(&mut self, _from: &Address, _to: &Address, _amount: Balance) {}
+}
+
+impl TokenState {
+    pub fn add_transfer_hook(&mut self, hook: Box<dyn TransferHook>) {
+        self.transfer_hooks.push(hook);
+    }
+
+    pub fn clear_transfer_hooks(&mut self) {
+        self.transfer_hooks.clear();
+    }
+
+    pub(crate) fn run_before_transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        let mut hooks = std::mem::take(&mut self.transfer_hooks);
+        let verdict = hooks
+            .iter_mut()
+            .try_for_each(|hook| hook.before_transfer(from, to, amount));
+        self.transfer_hooks = hooks;
+        verdict
+    }
+
+    pub(crate) fn run_after_transfer(&mut self, from: &Address, to: &Address, amount: Balance) {
+        let mut hooks = std::mem::take(&mut self.transfer_hooks);
+        for hook in hooks.iter_mut() {
+            hook.after_transfer(from, to, amount);
+        }
+        self.transfer_hooks = hooks;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::addresses;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Logs both callbacks as `"<name> before|after <from> <to> <amount>"`.
+    struct Logger(&'static str, Rc<RefCell<Vec<String>>>);
+
+    impl TransferHook for Logger {
+        fn before_transfer(
+            &mut self,
+            from: &Address,
+            to: &Address,
+            amount: Balance,
+        ) -> Result<(), TokenError> {
+            let line = format!("{} before {from} {to} {amount}", self.0);
+            self.1.borrow_mut().push(line);
+            Ok(())
+        }
+
+        fn after_transfer(&mut self, from: &Address, to: &Address, amount: Balance) {
+            let line = format!("{} after {from} {to} {amount}", self.0);
+            self.1.borrow_mut().push(line);
+        }
+    }
+
+    /// Vetoes transfers to one address.
+    struct Blocklist(Address);
+
+    impl TransferHook for Blocklist {
+        fn before_transfer(
+            &mut self,
+            _from: &Address,
+            to: &Address,
+            _amount: Balance,
+        ) -> Result<(), TokenError> {
+            if to == &self.0 {
+                return Err(TokenError::RecipientNotPermitted { to: to.clone() });
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hooks_run_in_order_around_each_transfer() {
+        let [alice, bob, carol] = addresses(["alice", "bob", "carol"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        token.add_transfer_hook(Box::new(Logger("first", log.clone())));
+        token.add_transfer_hook(Box::new(Logger("second", log.clone())));
+        token.approve(&alice, &bob, 50).unwrap();
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.transfer_from(&bob, &alice, &carol, 50).unwrap();
+
+        assert_eq!(
+            *log.borrow(),
+            [
+                "first before alice bob 100",
+                "second before alice bob 100",
+                "first after alice bob 100",
+                "second after alice bob 100",
+                "first before alice carol 50",
+                "second before alice carol 50",
+                "first after alice carol 50",
+                "second after alice carol 50",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_veto_fails_the_transfer_and_skips_later_hooks() {
+        let [alice, bob, mallory] = addresses(["alice", "bob", "mallory"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        token.add_transfer_hook(Box::new(Blocklist(mallory.clone())));
+        token.add_transfer_hook(Box::new(Logger("logger", log.clone())));
+
+        let blocked = token.transfer(&alice, &mallory, 100);
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.clear_transfer_hooks();
+        token.transfer(&alice, &mallory, 100).unwrap();
+
+        assert_eq!(
+            blocked,
+            Err(TokenError::RecipientNotPermitted {
+                to: mallory.clone()
+            })
+        );
+        assert_eq!(
+            *log.borrow(),
+            ["logger before alice bob 100", "logger after alice bob 100"]
+        );
+        assert_eq!(token.balance_of(&mallory), 100);
+        assert_eq!(token.balance_of(&alice), 800);
+    }
+}
@@ -0,0 +1,160 @@
+//! Middleware wrapped around every state-changing operation.
+//!
+//! Interceptors registered on a [`TokenState`] see each [`Operation`] before
+//! it runs (and may veto it) and after it completes, whatever the method:
+//! transfers, approvals, shielding, bulk credits, and admin tagging alike.
+//! This is the place for cross-cutting logging, metering, auth, and policy.
+//!
+//! Interceptors run in registration order. If one rejects an operation,
+//! later interceptors' `before` hooks are skipped, the operation does not
+//! run, and no `after` hooks are called.
+//!
+//! Hooks cannot re-enter the token: they receive the operation by reference
+//! but no handle to the [`TokenState`], and the interceptor list is detached
+//! while an operation runs. Any future hook that is handed the state must
+//! revisit this and either reject nested calls or define their semantics.
+
+use crate::operation::Operation;
+use crate::{TokenError, TokenState};
+
+pub trait Interceptor {
+    /// Called before the operation; returning an error aborts it.
+    fn before(&mut self, _op: &Operation) -> Result<(), TokenError> {
+        Ok(())
+    }
+
+    /// Called after the operation ran, with its outcome.
+    fn after(&mut self, _op: &Operation, _outcome: Result<(), &TokenError>) {}
+}
+
+impl TokenState {
+    pub fn add_interceptor(&mut self, interceptor: Box<dyn Interceptor>) {
+        self.interceptors.push(interceptor);
+    }
+
+    pub fn clear_interceptors(&mut self) {
+        self.interceptors.clear();
+    }
+
+    pub(crate) fn intercept<T>(
+        &mut self,
+        op: &Operation,
+        apply: impl FnOnce(&mut Self) -> Result<T, TokenError>,
+    ) -> Result<T, TokenError> {
+        if self.interceptors.is_empty() {
+            return apply(self);
+        }
+
+        let mut interceptors = std::mem::take(&mut self.interceptors);
+        let verdict = interceptors.iter_mut().try_for_each(|i| i.before(op));
+        let result = verdict.and_then(|_| {
+            let result = apply(self);
+            let outcome = result.as_ref().map(|_| ());
+            for i in interceptors.iter_mut() {
+                i.after(op, outcome);
+            }
+            result
+        });
+        self.interceptors = interceptors;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+    use crate::test_util::{CallRecorder, Calls};
+
+    /// Blocks approvals, lets everything else through.
+    struct NoApprovals;
+
+    impl Interceptor for NoApprovals {
+        fn before(&mut self, op: &Operation) -> Result<(), TokenError> {
+            match op {
+                Operation::Approve { .. } => Err(TokenError::InjectedFault),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_interceptor_sees_before_and_after() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let log = Calls::default();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(CallRecorder("i", log.clone())));
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.transfer(&alice, &bob, 5000).unwrap_err();
+
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                format!(
+                    "i before {:?}",
+                    Operation::Transfer {
+                        from: alice.clone(),
+                        to: bob.clone(),
+                        amount: 100
+                    }
+                ),
+                "i after true".to_string(),
+                format!(
+                    "i before {:?}",
+                    Operation::Transfer {
+                        from: alice.clone(),
+                        to: bob.clone(),
+                        amount: 5000
+                    }
+                ),
+                "i after false".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interceptor_can_short_circuit() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let log = Calls::default();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(NoApprovals));
+        token.add_interceptor(Box::new(CallRecorder("i", log.clone())));
+
+        let result = token.approve(&alice, &bob, 100);
+
+        assert_eq!(result.unwrap_err(), TokenError::InjectedFault);
+        assert_eq!(token.allowance(&alice, &bob), 0);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_interceptor_covers_admin_and_shield_ops() {
+        let alice = Address::new("alice").unwrap();
+        let log = Calls::default();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(CallRecorder("i", log.clone())));
+
+        token.mark_non_circulating(&alice, &alice).unwrap();
+        token.shield(&alice, 10).unwrap();
+
+        let log = log.borrow();
+        assert!(log[0].starts_with("i before MarkNonCirculating"));
+        assert!(log[2].starts_with("i before Shield"));
+    }
+
+    #[test]
+    fn test_approve_if_intercepted_once() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let log = Calls::default();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(CallRecorder("i", log.clone())));
+
+        token.approve_if(&alice, &bob, 0, 100).unwrap();
+
+        assert_eq!(log.borrow().len(), 2);
+    }
+}
@@ -0,0 +1,170 @@
+//! Internal consistency checks over the whole ledger.
+//!
+//! [`TokenState::check_invariants`] recomputes every aggregate the state
+//! maintains incrementally and compares it against the stored value. It is
+//! O(accounts + allowances), so long-running drivers call it periodically
+//! rather than after every operation.
+
+use crate::{Balance, TokenState};
+
+impl TokenState {
+    /// Verifies that incrementally maintained aggregates match the data
+    /// they summarize, describing the first mismatch found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let public = self
+            .balances
+            .values()
+            .try_fold(0, |acc: Balance, b| acc.checked_add(*b))
+            .ok_or("sum of balances overflows")?;
+        let escrowed = self
+            .escrows
+            .values()
+            .try_fold(0, |acc: Balance, e| acc.checked_add(e.amount))
+            .ok_or("sum of escrows overflows")?;
+        let accounted = public
+            .checked_add(self.shielded_pool)
+            .and_then(|sum| sum.checked_add(escrowed))
+            .ok_or("balances + shielded pool + escrows overflows")?;
+        if accounted != self.total_supply {
+            return Err(format!(
+                "balances {public} + shielded {} + escrowed {escrowed} != total_supply {}",
+                self.shielded_pool, self.total_supply
+            ));
+        }
+
+        if let Some(cap) = self.max_supply.filter(|cap| self.total_supply > *cap) {
+            return Err(format!(
+                "total_supply {} exceeds max_supply {cap}",
+                self.total_supply
+            ));
+        }
+
+        let non_circulating: Balance = self
+            .non_circulating
+            .iter()
+            .map(|address| self.balance_of(address))
+            .sum();
+        if non_circulating != self.non_circulating_balance {
+            return Err(format!(
+                "non-circulating balances {non_circulating} != cached {}",
+                self.non_circulating_balance
+            ));
+        }
+
+        let minimum = self.config.existential_deposit;
+        if minimum > 0
+            && let Some((address, balance)) = self.balances.iter().find(|(_, b)| **b < minimum)
+        {
+            return Err(format!(
+                "{address} holds {balance}, below existential deposit {minimum}"
+            ));
+        }
+
+        for ((owner, spender), usage) in &self.allowance_usage {
+            if usage.spent > usage.granted {
+                return Err(format!(
+                    "{owner}->{spender} spent {} of only {} granted",
+                    usage.spent, usage.granted
+                ));
+            }
+        }
+
+        for owner in self.locks.keys() {
+            let (locked, balance) = (self.locked_balance(owner), self.balance_of(owner));
+            if locked > balance {
+                return Err(format!("{owner} has {locked} locked but holds {balance}"));
+            }
+        }
+
+        if let Some(id) = self.escrows.keys().find(|id| id.0 >= self.next_escrow_id) {
+            return Err(format!("{id} was never issued"));
+        }
+
+        if let Some(id) = self.spent_notes.iter().find(|id| **id >= self.next_note_id) {
+            return Err(format!("spent note {id} was never issued"));
+        }
+        for id in self.open_notes.keys() {
+            if *id >= self.next_note_id {
+                return Err(format!("open note {id} was never issued"));
+            }
+            if self.spent_notes.contains(id) {
+                return Err(format!("open note {id} is already spent"));
+            }
+        }
+        let recorded = self
+            .open_notes
+            .values()
+            .try_fold(0, |acc: Balance, amount| acc.checked_add(*amount))
+            .ok_or("sum of open notes overflows")?;
+        if recorded != self.shielded_pool {
+            return Err(format!(
+                "open notes {recorded} do not add up to shielded pool {}",
+                self.shielded_pool
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AcceptAllVerifier, Address, TokenConfig};
+
+    #[test]
+    fn test_busy_state_is_consistent() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 5,
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+        token.mark_non_circulating(&alice, &bob).unwrap();
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&bob, &alice, 50).unwrap();
+        token.burn_from(&alice, &bob, 20).unwrap();
+        let note = token.shield(&alice, 300).unwrap();
+        token.unshield(&bob, &note, b"proof").unwrap();
+        token.mint(&alice, &alice, 7).unwrap();
+
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_detects_supply_mismatch() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.total_supply += 1;
+
+        let error = token.check_invariants().unwrap_err();
+        assert!(error.contains("total_supply 1001"), "{error}");
+    }
+
+    #[test]
+    fn test_detects_stale_non_circulating_cache() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.mark_non_circulating(&alice, &alice).unwrap();
+
+        token.non_circulating_balance = 0;
+
+        assert!(token.check_invariants().is_err());
+    }
+
+    #[test]
+    fn test_detects_lost_open_note() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let note = token.shield(&alice, 300).unwrap();
+
+        token.open_notes.remove(&note.id);
+
+        let error = token.check_invariants().unwrap_err();
+        assert!(error.contains("shielded pool 300"), "{error}");
+    }
+}
@@ -0,0 +1,399 @@
+//! Cheap branching for simulations.
+//!
+//! [`TokenState::checkpoint`] marks the current state and starts a journal:
+//! from then on every balance, allowance, nonce, capability, lock, escrow
+//! and note write records the value it replaced. [`TokenState::revert_to`] replays the journal
+//! backwards to the mark, so exploring a branch costs memory proportional
+//! to what the branch changed rather than a clone of every table. Supply,
+//! sequence numbers, configuration with its queued updates and admin state
+//! (owner, pause flag, roles, freezes) are copied at the mark; they are
+//! small.
+//!
+//! Checkpoints nest. Reverting to one discards it and every checkpoint
+//! taken after it; [`TokenState::release_checkpoint`] keeps the state but
+//! drops the mark, and journaling stops once no checkpoint is open.
+//!
+//! Batches and transactions roll back through the same journal, each
+//! under a checkpoint of its own (the `undo` module).
+//!
+//! Reverting drops every logged event with a sequence number from the
+//! mark on, including those emitted after the log was drained, so
+//! reverted sequence numbers never appear twice in the log. Event sinks
+//! and callers that drained the log have already seen the reverted events
+//! and are not told otherwise. Interceptors
+//! and sinks are runtime attachments and are left as they are.
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::escrow::{Escrow, EscrowId};
+use crate::ocap::{CapabilityGrant, CapabilityId};
+use crate::{
+    Address, AllowanceUsage, Balance, ConfigUpdateId, Lock, QueuedConfig, Role, TokenConfig,
+    TokenError, TokenState,
+};
+
+/// Handle to a mark taken by [`TokenState::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CheckpointId {
+    depth: usize,
+    serial: u64,
+}
+
+// 되돌릴 때 덮어쓸 이전 값
+enum Undo {
+    Balance(Address, Option<Balance>),
+    Allowance((Address, Address), Option<Balance>, Option<AllowanceUsage>),
+    // 노트 id → (열린 노트 금액, 사용 여부)
+    Note(u64, Option<Balance>, bool),
+    Nonce(Address, Option<u64>),
+    Capability(CapabilityId, Option<CapabilityGrant>),
+    Lock(Address, Option<Vec<Lock>>),
+    Escrow(EscrowId, Option<Escrow>),
+    #[cfg(feature = "account-stats")]
+    Stats(Address, Option<(u64, crate::stats::AccountStats)>),
+}
+
+// 체크포인트 시점에 통째로 복사해 두는 작은 상태
+struct Mark {
+    serial: u64,
+    undo_len: usize,
+    total_supply: Balance,
+    non_circulating_balance: Balance,
+    shielded_pool: Balance,
+    next_note_id: u64,
+    next_capability_id: u64,
+    next_escrow_id: u64,
+    next_seq: u64,
+    config: TokenConfig,
+    non_circulating: HashSet<Address>,
+    owner: Option<Address>,
+    pending_owner: Option<Address>,
+    paused: bool,
+    frozen: HashSet<Address>,
+    roles: HashSet<(Address, Role)>,
+    config_updates: BTreeMap<ConfigUpdateId, QueuedConfig>,
+    next_config_update_id: u64,
+    #[cfg(feature = "account-stats")]
+    stats_epoch: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Journal {
+    undo: Vec<Undo>,
+    marks: Vec<Mark>,
+    next_serial: u64,
+}
+
+impl TokenState {
+    /// Marks the current state so it can be restored with
+    /// [`TokenState::revert_to`].
+    pub fn checkpoint(&mut self) -> CheckpointId {
+        let serial = self.journal.next_serial;
+        self.journal.next_serial += 1;
+        let id = CheckpointId {
+            depth: self.journal.marks.len(),
+            serial,
+        };
+        let mark = Mark {
+            serial,
+            undo_len: self.journal.undo.len(),
+            total_supply: self.total_supply,
+            non_circulating_balance: self.non_circulating_balance,
+            shielded_pool: self.shielded_pool,
+            next_note_id: self.next_note_id,
+            next_capability_id: self.next_capability_id,
+            next_escrow_id: self.next_escrow_id,
+            next_seq: self.next_seq,
+            config: self.config.clone(),
+            non_circulating: self.non_circulating.clone(),
+            owner: self.owner.clone(),
+            pending_owner: self.pending_owner.clone(),
+            paused: self.paused,
+            frozen: self.frozen.clone(),
+            roles: self.roles.clone(),
+            config_updates: self.config_updates.clone(),
+            next_config_update_id: self.next_config_update_id,
+            #[cfg(feature = "account-stats")]
+            stats_epoch: self.stats_epoch(),
+        };
+        self.journal.marks.push(mark);
+        id
+    }
+
+    /// Restores the state as of `id`, discarding it and every later
+    /// checkpoint.
+    ///
+    /// Fails with [`TokenError::UnknownCheckpoint`] if `id` was already
+    /// reverted or released.
+    pub fn revert_to(&mut self, id: CheckpointId) -> Result<(), TokenError> {
+        self.check_checkpoint(id)?;
+        let mark = self.journal.marks.drain(id.depth..).next().unwrap();
+        let undo: Vec<_> = self.journal.undo.drain(mark.undo_len..).collect();
+        for entry in undo.into_iter().rev() {
+            match entry {
+                Undo::Balance(address, balance) => {
+                    match balance {
+                        Some(balance) => self.balances.insert(address, balance),
+                        None => self.balances.remove(&address),
+                    };
+                }
+                Undo::Allowance(key, amount, usage) => {
+                    match usage {
+                        Some(usage) => self.allowance_usage.insert(key.clone(), usage),
+                        None => self.allowance_usage.remove(&key),
+                    };
+                    match amount {
+                        Some(amount) => self.allowances.insert(key, amount),
+                        None => self.allowances.remove(&key),
+                    };
+                }
+                Undo::Note(id, open, spent) => {
+                    match open {
+                        Some(amount) => self.open_notes.insert(id, amount),
+                        None => self.open_notes.remove(&id),
+                    };
+                    if spent {
+                        self.spent_notes.insert(id);
+                    } else {
+                        self.spent_notes.remove(&id);
+                    }
+                }
+                Undo::Nonce(address, nonce) => {
+                    match nonce {
+                        Some(nonce) => self.nonces.insert(address, nonce),
+                        None => self.nonces.remove(&address),
+                    };
+                }
+                Undo::Capability(id, grant) => {
+                    match grant {
+                        Some(grant) => self.capabilities.insert(id, grant),
+                        None => self.capabilities.remove(&id),
+                    };
+                }
+                Undo::Lock(address, locks) => {
+                    match locks {
+                        Some(locks) => self.locks.insert(address, locks),
+                        None => self.locks.remove(&address),
+                    };
+                }
+                Undo::Escrow(id, escrow) => {
+                    match escrow {
+                        Some(escrow) => self.escrows.insert(id, escrow),
+                        None => self.escrows.remove(&id),
+                    };
+                }
+                #[cfg(feature = "account-stats")]
+                Undo::Stats(address, stats) => self.stats.put_back(&address, stats),
+            }
+        }
+
+        self.total_supply = mark.total_supply;
+        self.non_circulating_balance = mark.non_circulating_balance;
+        self.shielded_pool = mark.shielded_pool;
+        self.next_note_id = mark.next_note_id;
+        self.next_capability_id = mark.next_capability_id;
+        self.next_escrow_id = mark.next_escrow_id;
+        self.next_seq = mark.next_seq;
+        // drain_events가 로그를 비웠을 수 있으므로 길이가 아니라 seq로 자름
+        self.events.retain(|(seq, _)| *seq < mark.next_seq);
+        self.config = mark.config;
+        self.non_circulating = mark.non_circulating;
+        self.owner = mark.owner;
+        self.pending_owner = mark.pending_owner;
+        self.paused = mark.paused;
+        self.frozen = mark.frozen;
+        self.roles = mark.roles;
+        self.config_updates = mark.config_updates;
+        self.next_config_update_id = mark.next_config_update_id;
+        #[cfg(feature = "account-stats")]
+        self.stats.set_epoch(mark.stats_epoch);
+        self.trim_journal();
+        Ok(())
+    }
+
+    /// Keeps the current state and drops `id` and every later checkpoint.
+    pub fn release_checkpoint(&mut self, id: CheckpointId) -> Result<(), TokenError> {
+        self.check_checkpoint(id)?;
+        self.journal.marks.truncate(id.depth);
+        self.trim_journal();
+        Ok(())
+    }
+
+    fn check_checkpoint(&self, id: CheckpointId) -> Result<(), TokenError> {
+        match self.journal.marks.get(id.depth) {
+            Some(mark) if mark.serial == id.serial => Ok(()),
+            _ => Err(TokenError::UnknownCheckpoint),
+        }
+    }
+
+    /// Writes recorded for the open checkpoints; zero when none is open.
+    pub fn journal_len(&self) -> usize {
+        self.journal.undo.len()
+    }
+
+    // 열린 체크포인트가 없으면 기록할 필요가 없음
+    fn trim_journal(&mut self) {
+        if self.journal.marks.is_empty() {
+            self.journal.undo.clear();
+        }
+    }
+
+    pub(crate) fn journal_balance(&mut self, address: &Address) {
+        if !self.journal.marks.is_empty() {
+            let balance = self.balances.get(address).copied();
+            self.journal
+                .undo
+                .push(Undo::Balance(address.clone(), balance));
+        }
+    }
+
+    pub(crate) fn journal_allowance(&mut self, owner: &Address, spender: &Address) {
+        if !self.journal.marks.is_empty() {
+            let key = (owner.clone(), spender.clone());
+            let amount = self.allowances.get(&key).copied();
+            let usage = self.allowance_usage.get(&key).copied();
+            self.journal.undo.push(Undo::Allowance(key, amount, usage));
+        }
+    }
+
+    pub(crate) fn journal_note(&mut self, id: u64) {
+        if !self.journal.marks.is_empty() {
+            let open = self.open_notes.get(&id).copied();
+            let spent = self.spent_notes.contains(&id);
+            self.journal.undo.push(Undo::Note(id, open, spent));
+        }
+    }
+
+    pub(crate) fn journal_nonce(&mut self, address: &Address) {
+        if !self.journal.marks.is_empty() {
+            let nonce = self.nonces.get(address).copied();
+            self.journal.undo.push(Undo::Nonce(address.clone(), nonce));
+        }
+    }
+
+    pub(crate) fn journal_capability(&mut self, id: CapabilityId) {
+        if !self.journal.marks.is_empty() {
+            let grant = self.capabilities.get(&id).cloned();
+            self.journal.undo.push(Undo::Capability(id, grant));
+        }
+    }
+
+    pub(crate) fn journal_lock(&mut self, address: &Address) {
+        if !self.journal.marks.is_empty() {
+            let locks = self.locks.get(address).cloned();
+            self.journal.undo.push(Undo::Lock(address.clone(), locks));
+        }
+    }
+
+    pub(crate) fn journal_escrow(&mut self, id: EscrowId) {
+        if !self.journal.marks.is_empty() {
+            let escrow = self.escrows.get(&id).cloned();
+            self.journal.undo.push(Undo::Escrow(id, escrow));
+        }
+    }
+
+    #[cfg(feature = "account-stats")]
+    pub(crate) fn journal_stats(&mut self, address: &Address) {
+        if !self.journal.marks.is_empty() {
+            let stats = self.stats.saved(address);
+            self.journal.undo.push(Undo::Stats(address.clone(), stats));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revert_restores_branch_point() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 50).unwrap();
+        let hash_before = token.state_hash();
+        let seq_before = token.next_seq();
+
+        let id = token.checkpoint();
+        token.transfer(&alice, &bob, 300).unwrap();
+        token.transfer_from(&bob, &alice, &bob, 50).unwrap();
+        token.burn(&bob, 100).unwrap();
+        token.revoke_all(&alice).unwrap();
+        token.pause(&alice).unwrap();
+        token.revert_to(id).unwrap();
+
+        assert_eq!(token.state_hash(), hash_before);
+        assert_eq!(token.total_supply(), 1000);
+        assert_eq!(token.allowance_usage(&alice, &bob), (50, 0, 50));
+        assert_eq!(token.next_seq(), seq_before);
+        assert!(!token.is_paused());
+        assert_eq!(token.revert_to(id), Err(TokenError::UnknownCheckpoint));
+    }
+
+    #[test]
+    fn test_revert_after_drain_drops_later_events() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let id = token.checkpoint();
+        token.transfer(&alice, &bob, 100).unwrap();
+        let drained = token.drain_events();
+        token.transfer(&alice, &bob, 200).unwrap();
+        token.revert_to(id).unwrap();
+        token.transfer(&alice, &bob, 300).unwrap();
+
+        assert_eq!(drained.len(), 1);
+        assert_eq!(token.events().len(), 1);
+        assert_eq!(token.events()[0].0, drained[0].0);
+        assert_eq!(token.balance_of(&bob), 300);
+    }
+
+    #[test]
+    fn test_revert_restores_notes() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(crate::AcceptAllVerifier));
+        let kept = token.shield(&alice, 100).unwrap();
+
+        let id = token.checkpoint();
+        token.unshield(&bob, &kept, &[]).unwrap();
+        let dropped = token.shield(&alice, 200).unwrap();
+        token.revert_to(id).unwrap();
+
+        assert_eq!(token.shielded_supply(), 100);
+        assert_eq!(
+            token.unshield(&bob, &dropped, &[]),
+            Err(TokenError::UnknownNote)
+        );
+        assert_eq!(token.unshield(&bob, &kept, &[]), Ok(()));
+        assert_eq!(token.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let outer = token.checkpoint();
+        token.transfer(&alice, &bob, 100).unwrap();
+        let inner = token.checkpoint();
+        token.transfer(&alice, &bob, 200).unwrap();
+        token.revert_to(inner).unwrap();
+        let after_inner = token.balance_of(&bob);
+        let sibling = token.checkpoint();
+        token.transfer(&alice, &bob, 1).unwrap();
+        token.release_checkpoint(sibling).unwrap();
+        token.revert_to(outer).unwrap();
+
+        assert_eq!(after_inner, 100);
+        assert_eq!(token.balance_of(&bob), 0);
+        assert_eq!(token.balance_of(&alice), 1000);
+        assert_eq!(
+            token.release_checkpoint(inner),
+            Err(TokenError::UnknownCheckpoint)
+        );
+    }
+}
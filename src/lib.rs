@@ -8,7 +8,8 @@
 //! - **Core transfers**: Direct token transfers with overflow protection
 //! - **Allowance pattern**: Delegated transfers for DeFi integration
 //! - **Comprehensive error handling**: Detailed error types for debugging
-//! - **Production-ready**: 16 tests covering all edge cases
+//! - **Tested**: unit tests beside the code in each module, golden snapshot
+//!   images and randomized soak runs
 //!
 //! ## Quick Start
 //!
@@ -16,17 +17,15 @@
 //! use token_standard::*;
 //!
 //! // Create a new token
-//! let mut token = TokenState::new("alice".to_string(), 1000);
+//! let alice: Address = "alice".parse().unwrap();
+//! let bob: Address = "bob".parse().unwrap();
+//! let mut token = TokenState::new(alice.clone(), 1000);
 //!
 //! // Transfer tokens
-//! token.transfer(
-//!     &"alice".to_string(),
-//!     &"bob".to_string(),
-//!     100
-//! ).unwrap();
+//! token.transfer(&alice, &bob, 100).unwrap();
 //!
 //! // Check balance
-//! assert_eq!(token.balance_of(&"bob".to_string()), 100);
+//! assert_eq!(token.balance_of(&bob), 100);
 //! ```
 //!
 //! ## Architecture
@@ -35,12 +34,108 @@
 //! - `balances: HashMap<Address, Balance>` - Account balances
 //! - `allowances: HashMap<(Address, Address), Balance>` - Approved spending limits
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub mod address;
+pub mod addrgen;
+pub mod admin;
+pub mod backup;
+pub mod batch;
+pub mod capabilities;
+pub mod chaos;
+pub mod clock;
+pub mod codec;
+pub mod confidential;
+pub mod config;
+pub mod conformance;
+pub mod escrow;
+pub mod events;
+#[cfg(feature = "evm-types")]
+pub mod evm;
+pub mod expiry;
+pub mod export;
+pub mod fee;
+pub mod freeze;
+pub mod hook;
+pub mod interceptor;
+pub mod invariants;
+pub mod journal;
+pub mod lifecycle;
+pub mod lock;
+pub mod merkle;
+pub mod metadata;
+pub mod mirror;
+pub mod multi_token;
+pub mod netting;
+pub mod nft;
+pub mod ocap;
+pub mod operation;
+pub mod permit;
+pub mod receiver;
+pub mod revoke;
+pub mod rng;
+pub mod roles;
+#[cfg(feature = "scripting")]
+pub mod script;
+mod sha256;
+pub mod shielded;
+pub mod shrink;
+pub mod sim;
+mod snapshot;
+pub mod soak;
+mod soulbound;
+pub mod spec;
+pub mod state_hash;
+#[cfg(feature = "account-stats")]
+pub mod stats;
+pub mod stealth;
+pub mod supply_cap;
+pub mod synth;
+pub mod tenancy;
+#[cfg(test)]
+mod test_util;
+pub mod transaction;
+pub mod tx;
+mod undo;
+pub mod workload;
+
+pub use address::{Address, AddressError, AddressValidator, DefaultValidator, EvmValidator};
+pub use capabilities::Capabilities;
+pub use codec::SnapshotError;
+pub use confidential::{
+    Commitment, CommitmentScheme, ConfidentialLedger, ConfidentialTransfer, SimulatedPedersen,
+};
+pub use config::{ConfigError, ConfigUpdateId, QueuedConfig};
+pub use escrow::{Escrow, EscrowId};
+pub use events::{EventSink, TokenEvent};
+pub use fee::{FeeDestination, FeePolicy, FeeTier};
+pub use hook::TransferHook;
+pub use interceptor::Interceptor;
+pub use journal::CheckpointId;
+pub use lifecycle::LifecycleError;
+pub use lock::Lock;
+pub use merkle::{BalanceProof, verify_proof};
+pub use metadata::TokenMetadata;
+pub use multi_token::{MultiTokenState, TokenId};
+pub use nft::{NftId, NftState};
+pub use ocap::{Capability, CapabilityGrant, CapabilityId};
+pub use operation::{Operation, Receipt};
+pub use receiver::{ReceiverRegistry, TokenReceiver};
+pub use revoke::Spender;
+pub use roles::Role;
+pub use shielded::{AcceptAllVerifier, Note, ProofVerifier};
+pub use stealth::{
+    Announcement, KeyDerivation, PublicKey, SecretKey, SimulatedKeys, StealthKeys,
+    StealthMetaAddress, StealthRegistry,
+};
+pub use transaction::Transaction;
+pub use tx::{SignatureVerifier, SignedTransfer};
 
 /// Errors that can occur during token operations.
 ///
 /// All errors include contextual information to aid debugging.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenError {
     /// Attempted transfer with insufficient balance.
     ///
@@ -83,10 +178,350 @@ pub enum TokenError {
         /// Amount of tokens approved for spending
         available: Balance,
     },
+
+    /// A range proof, or a shielded note's proof, was rejected.
+    InvalidProof,
+
+    /// A shielded note was unshielded more than once.
+    ///
+    /// Each note can be released exactly once; replays are rejected.
+    NoteAlreadySpent,
+
+    /// A shielded note names an id the pool never issued.
+    UnknownNote,
+
+    /// A shielded note's amount differs from what was shielded under its id.
+    NoteMismatch {
+        /// Amount shielded when the note was issued
+        issued: Balance,
+        /// Amount the presented note claims
+        presented: Balance,
+    },
+
+    /// A failure injected by [`chaos::Chaos`] rather than the ledger itself.
+    InjectedFault,
+
+    /// Compare-and-set approval found a different allowance than expected.
+    ///
+    /// Another writer changed the allowance first; re-read and retry.
+    AllowanceMismatch {
+        /// Allowance the caller expected to replace
+        expected: Balance,
+        /// Allowance actually stored
+        actual: Balance,
+    },
+
+    /// An account would be left holding less than the existential deposit.
+    ///
+    /// Applies to a sender's remainder and to a recipient's new balance.
+    BelowExistentialDeposit {
+        /// Balance the account would end up with
+        balance: Balance,
+        /// Configured existential deposit
+        minimum: Balance,
+    },
+
+    /// Raising an allowance would overflow `Balance`.
+    AllowanceOverflow,
+
+    /// Attempted to lower an allowance by more than it holds.
+    ///
+    /// Includes the requested decrease and the current allowance.
+    AllowanceUnderflow {
+        /// Amount the caller asked to subtract
+        required: Balance,
+        /// Allowance actually stored
+        available: Balance,
+    },
+
+    /// Minting would push `total_supply` past `Balance::MAX`.
+    ///
+    /// Checked before any balance changes, so a failed mint has no effect.
+    SupplyOverflow,
+
+    /// Minting would push `total_supply` past the token's `max_supply`.
+    SupplyCapExceeded {
+        /// Configured maximum supply
+        cap: Balance,
+        /// Total supply the operation would have produced
+        attempted: Balance,
+    },
+
+    /// A runtime-loaded policy script refused the operation.
+    ///
+    /// Carries the script's reason, or the script error if it failed.
+    PolicyRejected(String),
+
+    /// The token is paused; holder operations are suspended.
+    Paused,
+
+    /// The caller lacks the authority the operation requires.
+    Unauthorized {
+        /// Address that attempted the operation
+        caller: Address,
+    },
+
+    /// The operation would debit or credit a frozen account.
+    AccountFrozen {
+        /// The frozen party
+        address: Address,
+    },
+
+    /// One leg of a batch failed, so none of the batch was applied.
+    BatchLegFailed {
+        /// Position of the failing leg in the batch
+        index: usize,
+        /// Why that leg failed
+        error: Box<TokenError>,
+    },
+
+    /// The checkpoint was already reverted or released.
+    UnknownCheckpoint,
+
+    /// A signed transfer's signature was rejected by the verifier.
+    InvalidSignature,
+
+    /// A signed transfer's nonce was not the sender's next one.
+    ///
+    /// A lower nonce is a replay; a higher one arrived out of order.
+    InvalidNonce {
+        /// Nonce the sender's next transfer must carry
+        expected: u64,
+        /// Nonce the transfer carried
+        got: u64,
+    },
+
+    /// A signed operation was presented to a ledger built without a
+    /// domain separator, so there is nothing to bind its signature to.
+    NoDomainSeparator,
+
+    /// A permit was presented after its deadline.
+    PermitExpired {
+        /// Last instant the permit was valid
+        deadline: u64,
+        /// Current time by the token's clock
+        now: u64,
+    },
+
+    /// A delegated transfer used an allowance past its expiry.
+    AllowanceExpired {
+        /// Last instant the allowance was valid
+        expires_at: u64,
+        /// Current time by the token's clock
+        now: u64,
+    },
+
+    /// A capability handle is unknown, revoked, or carries the wrong secret.
+    InvalidCapability,
+
+    /// A capability was redeemed after its expiry.
+    CapabilityExpired {
+        /// Last instant the capability was valid
+        expires_at: u64,
+        /// Current time by the token's clock
+        now: u64,
+    },
+
+    /// A safe transfer's recipient refused the tokens.
+    ReceiverRejected {
+        /// The recipient whose receiver refused
+        to: Address,
+    },
+
+    /// The recipient may not receive these tokens: it is outside a
+    /// capability's scope, or a burn address named as an escrow payee.
+    RecipientNotPermitted {
+        /// The recipient the bearer asked for
+        to: Address,
+    },
+
+    /// A capability was redeemed for more than it has left.
+    CapabilityExhausted {
+        /// Amount the bearer asked to move
+        required: Balance,
+        /// Amount the capability still allows
+        available: Balance,
+    },
+
+    /// `unlock` found no lock past its deadline.
+    NothingToUnlock,
+
+    /// The escrow id was never issued or is already settled.
+    UnknownEscrow,
+
+    /// The token is soulbound: it can be minted and burned but not moved.
+    NonTransferable,
+
+    /// The NFT id was never minted or is burned.
+    UnknownToken,
+
+    /// An NFT with this id already exists.
+    TokenAlreadyMinted,
+
+    /// The configured transfer fee is larger than the amount sent.
+    FeeExceedsAmount {
+        /// Fee the policy charges
+        fee: Balance,
+        /// Amount the transfer would move
+        amount: Balance,
+    },
+
+    /// A configuration's fee policy is malformed.
+    ///
+    /// Carries the reason from [`FeePolicy::validate`].
+    InvalidFeePolicy(String),
+
+    /// The configuration has an update delay, so changes must be queued.
+    ConfigTimelocked {
+        /// Seconds a queued update waits
+        delay: u64,
+    },
+
+    /// The configuration update id was never queued, or was already
+    /// executed or cancelled.
+    UnknownConfigUpdate,
+
+    /// A queued configuration update was executed before its delay passed.
+    ConfigUpdateNotReady {
+        /// First instant the update can be executed
+        eta: u64,
+        /// Current time by the token's clock
+        now: u64,
+    },
 }
 
-pub type Address = String; // 일단 간단하게
+/// Token amounts: `u64`, or `u128` with the `u128-balance` feature.
+///
+/// The two widths are mutually exclusive. Enabling `u128-balance` anywhere
+/// in a build changes `Balance` for every crate that uses this one, so
+/// code that assumes either width should write `Balance`, not `u64`.
+/// Other integer types are not supported.
+#[cfg(not(feature = "u128-balance"))]
 pub type Balance = u64;
+/// Token amounts: `u128` with the `u128-balance` feature, for chains whose
+/// supplies don't fit in `u64`.
+///
+/// The two widths are mutually exclusive. Enabling `u128-balance` anywhere
+/// in a build changes `Balance` for every crate that uses this one, so
+/// code that assumes either width should write `Balance`, not `u64`.
+/// Other integer types are not supported.
+#[cfg(feature = "u128-balance")]
+pub type Balance = u128;
+
+/// Conventional dead address; add it to [`TokenConfig::burn_addresses`] to
+/// make sends to it reduce `total_supply`.
+pub const BURN_ADDRESS: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// The ERC-20 style operations every token backend provides.
+///
+/// [`TokenState`] is the reference implementation. Wrappers such as
+/// [`chaos::Chaos`] implement it too, so code written against the trait
+/// can run on either.
+pub trait FungibleToken {
+    fn total_supply(&self) -> Balance;
+    fn balance_of(&self, address: &Address) -> Balance;
+    fn transfer(&mut self, from: &Address, to: &Address, amount: Balance)
+    -> Result<(), TokenError>;
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError>;
+    fn allowance(&self, owner: &Address, spender: &Address) -> Balance;
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError>;
+}
+
+/// How an edge-case request that ERC-20 tolerates should be handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeCaseMode {
+    /// Reject with a dedicated error (this crate's historical behavior)
+    #[default]
+    Reject,
+    /// Succeed without changing balances, as the ERC-20 standard permits
+    NoOp,
+}
+
+/// Behavioral switches fixed at construction time.
+///
+/// The default is strict: self-transfers and zero-amount transfers are
+/// rejected. [`TokenConfig::erc20_compatible`] accepts both as no-ops so that
+/// replayed mainnet traces don't fail spuriously.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenConfig {
+    /// Handling of `from == to` in `transfer` / `transfer_from`
+    pub self_transfer: EdgeCaseMode,
+    /// Handling of `amount == 0` in `transfer` / `transfer_from`
+    pub zero_amount: EdgeCaseMode,
+    /// Minimum balance an account may hold; 0 disables dust rules
+    pub existential_deposit: Balance,
+    /// What to do when a transfer would leave the sender below the minimum
+    pub dust_policy: DustPolicy,
+    /// Recipients whose incoming transfers are destroyed, reducing supply
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::snapshot::sorted_set")
+    )]
+    pub burn_addresses: HashSet<Address>,
+    /// Fee charged on each transfer
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee: FeePolicy,
+    /// Where collected fees go
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee_destination: FeeDestination,
+    /// Tokens can be minted and burned but never change hands
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub soulbound: bool,
+    /// Seconds, by the token's clock, between queueing a configuration
+    /// update and executing it; 0 lets `update_config` apply changes at once
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub update_delay: u64,
+}
+
+/// Handling of a sender left with a non-zero balance below the
+/// existential deposit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DustPolicy {
+    /// Fail with [`TokenError::BelowExistentialDeposit`]
+    #[default]
+    Reject,
+    /// Send the whole balance and reap the sender.
+    ///
+    /// Only `transfer` sweeps; `transfer_from` always rejects so a spender
+    /// never moves more than it asked for.
+    Sweep,
+}
+
+impl TokenConfig {
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    pub fn erc20_compatible() -> Self {
+        Self {
+            self_transfer: EdgeCaseMode::NoOp,
+            zero_amount: EdgeCaseMode::NoOp,
+            ..Self::default()
+        }
+    }
+
+    /// Strict rules with non-transferable tokens.
+    pub fn soulbound() -> Self {
+        Self {
+            soulbound: true,
+            ..Self::default()
+        }
+    }
+}
 
 /// The main token state container.
 ///
@@ -96,21 +531,74 @@ pub type Balance = u64;
 ///
 /// # Design Decisions
 ///
-/// - **Address type**: Currently `String` for simplicity. Production use
-///   should consider `[u8; 32]` for memory efficiency.
-/// - **Balance type**: `u64` provides sufficient range while maintaining
-///   performance. Overflow protection via `checked_add`.
+/// - **Address type**: A validated [`Address`] newtype over `String`, so
+///   encodings of any chain fit. Production use should consider `[u8; 32]`
+///   for memory efficiency.
+/// - **Balance type**: [`Balance`] is `u64`, or `u128` with the
+///   `u128-balance` feature for larger supplies. Overflow protection via
+///   `checked_add`.
 /// - **Allowance storage**: Tuple keys `(owner, spender)` enable O(1) lookups.
 pub struct TokenState {
     balances: HashMap<Address, Balance>,
     allowances: HashMap<(Address, Address), Balance>,
     total_supply: Balance,
+    shielded_pool: Balance,
+    spent_notes: HashSet<u64>,
+    next_note_id: u64,
+    // 아직 쓰이지 않은 노트의 발행 금액
+    open_notes: HashMap<u64, Balance>,
+    config: TokenConfig,
+    allowance_usage: HashMap<(Address, Address), AllowanceUsage>,
+    non_circulating: HashSet<Address>,
+    non_circulating_balance: Balance,
+    #[cfg(feature = "account-stats")]
+    stats: stats::StatsLedger,
+    interceptors: Vec<Box<dyn Interceptor>>,
+    transfer_hooks: Vec<Box<dyn TransferHook>>,
+    proof_verifier: Option<Box<dyn ProofVerifier>>,
+    next_seq: u64,
+    metadata: Option<TokenMetadata>,
+    events: Vec<(u64, TokenEvent)>,
+    event_sinks: Vec<Box<dyn EventSink>>,
+    owner: Option<Address>,
+    pending_owner: Option<Address>,
+    max_supply: Option<Balance>,
+    paused: bool,
+    frozen: HashSet<Address>,
+    roles: HashSet<(Address, Role)>,
+    nonces: HashMap<Address, u64>,
+    clock: Box<dyn clock::Clock>,
+    domain_separator: Option<[u8; 32]>,
+    capabilities: HashMap<ocap::CapabilityId, ocap::CapabilityGrant>,
+    next_capability_id: u64,
+    journal: journal::Journal,
+    locks: HashMap<Address, Vec<Lock>>,
+    escrows: HashMap<EscrowId, Escrow>,
+    next_escrow_id: u64,
+    config_updates: BTreeMap<ConfigUpdateId, QueuedConfig>,
+    next_config_update_id: u64,
+}
+
+// (owner, spender)별 누적 승인량 / 사용량, 그리고 allowance 만료 시점
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct AllowanceUsage {
+    granted: Balance,
+    spent: Balance,
+    expires_at: Option<u64>,
+}
+
+// 전송 한 건의 결과: 송신자에게서 빠진 양과 수신자에게 (수수료 제외) 입금된 양
+#[derive(Debug, Default, Clone, Copy)]
+struct Settlement {
+    moved: Balance,
+    credited: Balance,
 }
 
 #[cfg(test)]
 impl TokenState {
     pub fn mint_for_test(&mut self, address: Address, amount: Balance) {
-        self.balances.insert(address, amount);
+        self.write_balance(&address, amount);
     }
 }
 
@@ -120,16 +608,93 @@ impl TokenState {
     }
 
     pub fn new(creator: Address, initial_supply: Balance) -> Self {
+        Self::with_config(creator, initial_supply, TokenConfig::default())
+    }
+
+    pub fn with_config(creator: Address, initial_supply: Balance, config: TokenConfig) -> Self {
         let mut balances = HashMap::new();
-        balances.insert(creator, initial_supply);
+        balances.insert(creator.clone(), initial_supply);
+
+        Self::from_parts(balances, initial_supply, config).with_owner(creator)
+    }
+
+    /// Builds a state directly from `(address, balance)` pairs.
+    ///
+    /// The map is pre-sized and the supply sum is validated once, which is
+    /// far faster than replaying transfers when restoring or setting up large
+    /// states. Duplicate addresses are summed.
+    pub fn from_balances(
+        entries: impl IntoIterator<Item = (Address, Balance)>,
+    ) -> Result<Self, TokenError> {
+        let entries = entries.into_iter();
+        let mut balances = HashMap::with_capacity(entries.size_hint().0);
+        let mut total_supply: Balance = 0;
+
+        for (address, amount) in entries {
+            total_supply = total_supply
+                .checked_add(amount)
+                .ok_or(TokenError::BalanceOverFlow)?;
+            // 개별 잔액 <= total_supply 이므로 overflow 불가능
+            *balances.entry(address).or_insert(0) += amount;
+        }
+
+        Ok(Self::from_parts(
+            balances,
+            total_supply,
+            TokenConfig::default(),
+        ))
+    }
 
+    fn from_parts(
+        balances: HashMap<Address, Balance>,
+        total_supply: Balance,
+        config: TokenConfig,
+    ) -> Self {
         Self {
             balances,
             allowances: HashMap::new(),
-            total_supply: initial_supply,
+            total_supply,
+            shielded_pool: 0,
+            spent_notes: HashSet::new(),
+            next_note_id: 0,
+            open_notes: HashMap::new(),
+            config,
+            allowance_usage: HashMap::new(),
+            non_circulating: HashSet::new(),
+            non_circulating_balance: 0,
+            #[cfg(feature = "account-stats")]
+            stats: stats::StatsLedger::default(),
+            interceptors: Vec::new(),
+            transfer_hooks: Vec::new(),
+            proof_verifier: None,
+            next_seq: 0,
+            metadata: None,
+            events: Vec::new(),
+            event_sinks: Vec::new(),
+            owner: None,
+            pending_owner: None,
+            max_supply: None,
+            paused: false,
+            frozen: HashSet::new(),
+            roles: HashSet::new(),
+            nonces: HashMap::new(),
+            clock: Box::new(clock::SystemClock),
+            domain_separator: None,
+            capabilities: HashMap::new(),
+            next_capability_id: 0,
+            journal: journal::Journal::default(),
+            locks: HashMap::new(),
+            escrows: HashMap::new(),
+            config_updates: BTreeMap::new(),
+            next_config_update_id: 0,
+            next_escrow_id: 0,
         }
     }
 
+    pub fn config(&self) -> &TokenConfig {
+        &self.config
+    }
+
     pub fn balance_of(&self, address: &Address) -> Balance {
         self.balances.get(address).copied().unwrap_or(0)
     }
@@ -140,50 +705,195 @@ impl TokenState {
         to: &Address,
         amount: Balance,
     ) -> Result<(), TokenError> {
-        if from == to {
-            return Err(TokenError::SelfTransfer);
-        }
-        if amount == 0 {
-            return Err(TokenError::ZeroAmount);
+        self.execute(Operation::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<Settlement, TokenError> {
+        if self.check_edge_cases(from, to, amount)? {
+            return Ok(Settlement::default());
         }
 
-        let from_bal = self.balance_of(from);
-        if from_bal < amount {
-            return Err(TokenError::InsufficientBalance {
-                required: amount,
-                available: from_bal,
-            });
+        let from_bal = self.check_spendable(from, amount)?;
+        if from == to {
+            return Ok(Settlement::default());
         }
 
-        let to_bal = self
-            .balance_of(to)
-            .checked_add(amount)
-            .ok_or(TokenError::BalanceOverFlow)?;
+        self.settle(from, to, from_bal, amount, true)
+    }
 
-        self.balances.insert(from.clone(), from_bal - amount);
-        self.balances.insert(to.clone(), to_bal);
+    pub fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::Approve {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
 
-        Ok(())
+    fn apply_approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.set_allowance(owner, spender, amount, None)
     }
 
-    pub fn approve(
+    // 새 승인은 만료 시점도 새로 정함; 만료된 allowance는 0에서 시작
+    fn set_allowance(
         &mut self,
         owner: &Address,
         spender: &Address,
         amount: Balance,
+        expires_at: Option<u64>,
     ) -> Result<(), TokenError> {
         // 1. owner == spender check
         if owner == spender {
             return Err(TokenError::SelfApproval);
         }
         // 2. Save in allowances
-        self.allowances
-            .insert((owner.clone(), spender.clone()), amount);
+        let previous = self.allowance(owner, spender);
+        self.journal_allowance(owner, spender);
+        let key = (owner.clone(), spender.clone());
+        self.allowances.insert(key.clone(), amount);
+        let usage = self.allowance_usage.entry(key).or_default();
+        if amount > previous {
+            usage.granted = usage.granted.saturating_add(amount - previous);
+        }
+        usage.expires_at = expires_at;
+        self.emit(TokenEvent::Approval {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            previous,
+            amount,
+        });
         // 3. return Ok(())
         Ok(())
     }
 
+    /// Sets the allowance only if it currently equals `expected_current`.
+    ///
+    /// A race-safe alternative to [`TokenState::approve`]'s blind overwrite.
+    pub fn approve_if(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        expected_current: Balance,
+        new_amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::ApproveIf {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            expected_current,
+            new_amount,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_approve_if(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        expected_current: Balance,
+        new_amount: Balance,
+    ) -> Result<(), TokenError> {
+        let actual = self.allowance(owner, spender);
+        if actual != expected_current {
+            return Err(TokenError::AllowanceMismatch {
+                expected: expected_current,
+                actual,
+            });
+        }
+        self.apply_approve(owner, spender, new_amount)
+    }
+
+    /// Raises the allowance by `added` relative to its current value.
+    ///
+    /// Unlike [`TokenState::approve`], this cannot silently discard a
+    /// concurrent change made by a spender or another approval.
+    pub fn increase_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        added: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::IncreaseAllowance {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            added,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_increase_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        added: Balance,
+    ) -> Result<(), TokenError> {
+        self.check_allowance_expiry(owner, spender)?;
+        let amount = self
+            .allowance(owner, spender)
+            .checked_add(added)
+            .ok_or(TokenError::AllowanceOverflow)?;
+        let expires_at = self.allowance_expiry(owner, spender);
+        self.set_allowance(owner, spender, amount, expires_at)
+    }
+
+    /// Lowers the allowance by `subtracted`, failing if it holds less.
+    pub fn decrease_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        subtracted: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::DecreaseAllowance {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            subtracted,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_decrease_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        subtracted: Balance,
+    ) -> Result<(), TokenError> {
+        self.check_allowance_expiry(owner, spender)?;
+        let current = self.allowance(owner, spender);
+        let amount = current
+            .checked_sub(subtracted)
+            .ok_or(TokenError::AllowanceUnderflow {
+                required: subtracted,
+                available: current,
+            })?;
+        let expires_at = self.allowance_expiry(owner, spender);
+        self.set_allowance(owner, spender, amount, expires_at)
+    }
+
+    /// Amount `spender` may still move from `owner`; 0 once the allowance
+    /// has expired.
     pub fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        if self.allowance_expired(owner, spender).is_some() {
+            return 0;
+        }
         // Retrieve from allowances using the (owner, spender)key
         // if not found, return 0
         self.allowances
@@ -192,6 +902,25 @@ impl TokenState {
             .unwrap_or(0)
     }
 
+    /// Lifetime usage of the `(owner, spender)` pair as
+    /// `(granted_total, spent_total, remaining)`.
+    ///
+    /// `granted_total` grows only when an approval raises the allowance above
+    /// what was left, so `granted_total - spent_total - remaining` is the
+    /// amount the owner has revoked or lowered over time.
+    pub fn allowance_usage(
+        &self,
+        owner: &Address,
+        spender: &Address,
+    ) -> (Balance, Balance, Balance) {
+        let usage = self
+            .allowance_usage
+            .get(&(owner.clone(), spender.clone()))
+            .copied()
+            .unwrap_or_default();
+        (usage.granted, usage.spent, self.allowance(owner, spender))
+    }
+
     pub fn transfer_from(
         &mut self,
         spender: &Address,
@@ -199,13 +928,27 @@ impl TokenState {
         to: &Address,
         amount: Balance,
     ) -> Result<(), TokenError> {
-        if from == to {
-            return Err(TokenError::SelfTransfer);
-        }
-        if amount == 0 {
-            return Err(TokenError::ZeroAmount);
+        self.execute(Operation::TransferFrom {
+            spender: spender.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<Settlement, TokenError> {
+        if self.check_edge_cases(from, to, amount)? {
+            return Ok(Settlement::default());
         }
 
+        self.check_allowance_expiry(from, spender)?;
         let current_allowance = self.allowance(from, spender);
         if current_allowance < amount {
             return Err(TokenError::InsufficientAllowance {
@@ -214,53 +957,566 @@ impl TokenState {
             });
         }
 
-        let from_bal = self.balance_of(from);
-        if from_bal < amount {
-            return Err(TokenError::InsufficientBalance {
-                required: amount,
-                available: from_bal,
-            });
-        }
-
-        let to_bal = self
-            .balance_of(to)
-            .checked_add(amount)
-            .ok_or(TokenError::BalanceOverFlow)?;
+        let from_bal = self.check_spendable(from, amount)?;
 
-        self.balances.insert(from.clone(), from_bal - amount);
-        self.balances.insert(to.clone(), to_bal);
+        // ERC-20 호환 모드의 자기 전송: 잔액 변화 없이 allowance만 소모
+        let settled = if from != to {
+            // spender가 요청한 양보다 더 빼갈 수 없으므로 sweep 불가
+            self.settle(from, to, from_bal, amount, false)?
+        } else {
+            Settlement::default()
+        };
 
-        self.allowances
-            .insert((from.clone(), spender.clone()), current_allowance - amount);
+        self.spend_allowance(from, spender, current_allowance - amount, amount);
 
-        Ok(())
+        Ok(settled)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Destroys `amount` of `from`'s tokens on its behalf, consuming
+    /// `spender`'s allowance as [`TokenState::transfer_from`] does.
+    pub fn burn_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::BurnFrom {
+            spender: spender.clone(),
+            from: from.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
 
-    #[test]
-    fn test_new_token() {
-        // AAA Pattern(Arrange-Act-Assert)
-        //   Arrange: 테스트 데이터 준비
-        //   Act: 테스트할 함수 실행
-        //   Assert: 결과 검증
+    fn apply_burn_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        amount: Balance,
+    ) -> Result<Balance, TokenError> {
+        if self.check_zero_amount(amount)? {
+            return Ok(0);
+        }
 
-        let creator = "alice".to_string();
-        let initial_supply = 1000;
+        self.check_allowance_expiry(from, spender)?;
+        let current_allowance = self.allowance(from, spender);
+        if current_allowance < amount {
+            return Err(TokenError::InsufficientAllowance {
+                required: amount,
+                available: current_allowance,
+            });
+        }
 
-        let token = TokenState::new(creator, initial_supply);
+        let burned = self.apply_burn(from, amount)?;
+        self.spend_allowance(from, spender, current_allowance - amount, amount);
 
-        assert_eq!(token.total_supply(), initial_supply);
+        Ok(burned)
     }
 
-    #[test]
-    fn test_balance_of_existing_address() {
-        let creator = String::from("alice");
-        let initial_supply = 1000;
-        let token = TokenState::new(creator.clone(), initial_supply);
+    /// Mints into many accounts at once, emitting one `Mint` per credit.
+    ///
+    /// Meant for populating large benchmark or test states. `caller` must
+    /// hold [`Role::Minter`], as for [`TokenState::mint`]. Credits add to
+    /// existing balances and grow `total_supply`; the supply sum and each
+    /// credited account's existential deposit are checked up front, so on
+    /// failure nothing is applied.
+    pub fn bulk_credit(
+        &mut self,
+        caller: &Address,
+        entries: impl IntoIterator<Item = (Address, Balance)>,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::BulkCredit {
+            caller: caller.clone(),
+            entries: entries.into_iter().collect(),
+        })
+        .map(|_| ())
+    }
+
+    fn apply_bulk_credit(
+        &mut self,
+        caller: &Address,
+        entries: &[(Address, Balance)],
+    ) -> Result<Balance, TokenError> {
+        self.check_role(caller, Role::Minter)?;
+        let added = entries
+            .iter()
+            .try_fold(0, |acc: Balance, (_, amount)| acc.checked_add(*amount))
+            .ok_or(TokenError::BalanceOverFlow)?;
+        let total_supply = self
+            .total_supply
+            .checked_add(added)
+            .ok_or(TokenError::BalanceOverFlow)?;
+        self.check_supply_cap(total_supply)?;
+
+        // 같은 주소가 여러 번 나올 수 있으므로 최종 잔액으로 검사
+        let minimum = self.config.existential_deposit;
+        if minimum > 0 {
+            let mut credited: HashMap<&Address, Balance> = HashMap::new();
+            for (address, amount) in entries.iter().filter(|(_, amount)| *amount > 0) {
+                *credited.entry(address).or_default() += amount;
+            }
+            for (address, amount) in credited {
+                let balance = self.balance_of(address) + amount;
+                if balance < minimum {
+                    return Err(TokenError::BelowExistentialDeposit { balance, minimum });
+                }
+            }
+        }
+
+        self.balances.reserve(entries.len());
+        for (address, amount) in entries {
+            // 개별 잔액 <= total_supply 이므로 overflow 불가능
+            let balance = self.balance_of(address) + amount;
+            self.write_balance(address, balance);
+            if *amount > 0 {
+                self.emit(TokenEvent::Mint {
+                    to: address.clone(),
+                    amount: *amount,
+                });
+            }
+        }
+        self.total_supply = total_supply;
+
+        Ok(added)
+    }
+
+    /// Creates `amount` new tokens in `to`'s account.
+    ///
+    /// `caller` must hold [`Role::Minter`].
+    pub fn mint(
+        &mut self,
+        caller: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::Mint {
+            caller: caller.clone(),
+            to: to.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_mint(
+        &mut self,
+        caller: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<Balance, TokenError> {
+        self.check_role(caller, Role::Minter)?;
+        if self.check_zero_amount(amount)? {
+            return Ok(0);
+        }
+
+        let total_supply = self
+            .total_supply
+            .checked_add(amount)
+            .ok_or(TokenError::SupplyOverflow)?;
+        self.check_supply_cap(total_supply)?;
+        // 개별 잔액 <= total_supply 이므로 overflow 불가능
+        let to_bal = self.balance_of(to) + amount;
+        let minimum = self.config.existential_deposit;
+        if to_bal < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: to_bal,
+                minimum,
+            });
+        }
+
+        self.write_balance(to, to_bal);
+        self.total_supply = total_supply;
+        self.emit(TokenEvent::Mint {
+            to: to.clone(),
+            amount,
+        });
+        Ok(amount)
+    }
+
+    /// Destroys `amount` tokens from `from`'s account.
+    ///
+    /// Dust rules apply as for a transfer, except that burns never sweep.
+    pub fn burn(&mut self, from: &Address, amount: Balance) -> Result<(), TokenError> {
+        self.execute(Operation::Burn {
+            from: from.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
+
+    fn apply_burn(&mut self, from: &Address, amount: Balance) -> Result<Balance, TokenError> {
+        if self.check_zero_amount(amount)? {
+            return Ok(0);
+        }
+
+        let from_bal = self.check_spendable(from, amount)?;
+        let remaining = from_bal - amount;
+        let minimum = self.config.existential_deposit;
+        if remaining > 0 && remaining < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: remaining,
+                minimum,
+            });
+        }
+
+        self.write_balance(from, remaining);
+        self.total_supply -= amount;
+        self.emit(TokenEvent::Burn {
+            from: from.clone(),
+            amount,
+        });
+        Ok(amount)
+    }
+
+    /// Reserves room for at least `additional` more accounts.
+    pub fn reserve_accounts(&mut self, additional: usize) {
+        self.balances.reserve(additional);
+    }
+
+    /// Reserves room for at least `additional` more `(owner, spender)` pairs.
+    pub fn reserve_allowances(&mut self, additional: usize) {
+        self.allowances.reserve(additional);
+        self.allowance_usage.reserve(additional);
+    }
+
+    /// Releases excess capacity held by the internal maps.
+    pub fn shrink_to_fit(&mut self) {
+        self.balances.shrink_to_fit();
+        self.allowances.shrink_to_fit();
+        self.allowance_usage.shrink_to_fit();
+        self.non_circulating.shrink_to_fit();
+        self.spent_notes.shrink_to_fit();
+        self.locks.shrink_to_fit();
+        self.escrows.shrink_to_fit();
+    }
+
+    /// Excludes `address` (treasury, vesting escrow, bridge custody, ...)
+    /// from [`TokenState::circulating_supply`]; `caller` must hold
+    /// [`Role::Admin`].
+    pub fn mark_non_circulating(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::MarkNonCirculating {
+            caller: caller.clone(),
+            address: address.clone(),
+        })
+        .map(|_| ())
+    }
+
+    fn apply_mark_non_circulating(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        if self.non_circulating.insert(address.clone()) {
+            self.non_circulating_balance += self.balance_of(address);
+        }
+        Ok(())
+    }
+
+    /// Counts `address` toward the circulating supply again; `caller` must
+    /// hold [`Role::Admin`].
+    pub fn unmark_non_circulating(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::UnmarkNonCirculating {
+            caller: caller.clone(),
+            address: address.clone(),
+        })
+        .map(|_| ())
+    }
+
+    fn apply_unmark_non_circulating(
+        &mut self,
+        caller: &Address,
+        address: &Address,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        if self.non_circulating.remove(address) {
+            self.non_circulating_balance -= self.balance_of(address);
+        }
+        Ok(())
+    }
+
+    pub fn is_non_circulating(&self, address: &Address) -> bool {
+        self.non_circulating.contains(address)
+    }
+
+    /// `total_supply` minus balances held by non-circulating addresses.
+    ///
+    /// Maintained incrementally on every balance change, so this is O(1).
+    pub fn circulating_supply(&self) -> Balance {
+        self.total_supply - self.non_circulating_balance
+    }
+
+    /// Number of accounts present in the balance map.
+    ///
+    /// With an existential deposit configured, emptied accounts are reaped
+    /// and no longer counted.
+    pub fn account_count(&self) -> usize {
+        self.balances.len()
+    }
+
+    // 검증이 끝난 이동을 transfer hook으로 감싸서 적용
+    fn settle(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        from_bal: Balance,
+        amount: Balance,
+        sweep_allowed: bool,
+    ) -> Result<Settlement, TokenError> {
+        if self.transfer_hooks.is_empty() {
+            return self.move_balance(from, to, from_bal, amount, sweep_allowed);
+        }
+        self.run_before_transfer(from, to, amount)?;
+        let settled = self.move_balance(from, to, from_bal, amount, sweep_allowed)?;
+        self.run_after_transfer(from, to, settled.moved);
+        Ok(settled)
+    }
+
+    // dust 규칙, 수수료, overflow 검사 후 잔액 기록
+    fn move_balance(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        from_bal: Balance,
+        amount: Balance,
+        sweep_allowed: bool,
+    ) -> Result<Settlement, TokenError> {
+        let minimum = self.config.existential_deposit;
+        let mut amount = amount;
+        let remaining = from_bal - amount;
+        if remaining > 0 && remaining < minimum {
+            // 잠긴 토큰은 sweep으로도 옮기지 않음
+            if sweep_allowed
+                && self.config.dust_policy == DustPolicy::Sweep
+                && self.locked_balance(from) == 0
+            {
+                amount = from_bal;
+            } else {
+                return Err(TokenError::BelowExistentialDeposit {
+                    balance: remaining,
+                    minimum,
+                });
+            }
+        }
+
+        if self.config.burn_addresses.contains(to) {
+            self.write_balance(from, from_bal - amount);
+            self.total_supply -= amount;
+            #[cfg(feature = "account-stats")]
+            self.record_stats(from, to, amount);
+            self.emit(TokenEvent::Burn {
+                from: from.clone(),
+                amount,
+            });
+            return Ok(Settlement {
+                moved: amount,
+                credited: 0,
+            });
+        }
+
+        let fee = self.config.fee.fee_for(amount);
+        if fee > amount {
+            return Err(TokenError::FeeExceedsAmount { fee, amount });
+        }
+        let received = amount - fee;
+        let to_bal = self
+            .balance_of(to)
+            .checked_add(received)
+            .ok_or(TokenError::BalanceOverFlow)?;
+        let treasury = match &self.config.fee_destination {
+            FeeDestination::Treasury(treasury) if fee > 0 => Some(treasury.clone()),
+            _ => None,
+        };
+        // treasury가 송신자나 수신자일 수 있으므로 이번 전송 반영 후 잔액에 더함
+        let treasury_bal = match &treasury {
+            Some(treasury) => {
+                let base = if treasury == from {
+                    from_bal - amount
+                } else if treasury == to {
+                    to_bal
+                } else {
+                    self.balance_of(treasury)
+                };
+                let bal = base.checked_add(fee).ok_or(TokenError::BalanceOverFlow)?;
+                Some(bal)
+            }
+            None => None,
+        };
+        // 수수료가 전액이면 수신자 잔액은 그대로: 0 잔액 기록과 0 Transfer 이벤트 생략
+        let to_final = (received > 0 && treasury.as_ref() != Some(to)).then_some(to_bal);
+        for balance in [to_final, treasury_bal].into_iter().flatten() {
+            if balance < minimum {
+                return Err(TokenError::BelowExistentialDeposit { balance, minimum });
+            }
+        }
+
+        self.write_balance(from, from_bal - amount);
+        if received > 0 {
+            self.write_balance(to, to_bal);
+            #[cfg(feature = "account-stats")]
+            self.record_stats(from, to, received);
+            self.emit(TokenEvent::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                amount: received,
+            });
+        }
+        if let (Some(treasury), Some(treasury_bal)) = (treasury, treasury_bal) {
+            self.write_balance(&treasury, treasury_bal);
+            #[cfg(feature = "account-stats")]
+            self.record_stats(from, &treasury, fee);
+            self.emit(TokenEvent::Transfer {
+                from: from.clone(),
+                to: treasury,
+                amount: fee,
+            });
+        } else if fee > 0 {
+            self.total_supply -= fee;
+            self.emit(TokenEvent::Burn {
+                from: from.clone(),
+                amount: fee,
+            });
+        }
+
+        Ok(Settlement {
+            moved: amount,
+            credited: received,
+        })
+    }
+
+    // 검증된 위임 사용을 반영: 남은 allowance 기록과 누적 사용량 갱신
+    fn spend_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        remaining: Balance,
+        spent: Balance,
+    ) {
+        self.journal_allowance(owner, spender);
+        let key = (owner.clone(), spender.clone());
+        self.allowances.insert(key.clone(), remaining);
+        let usage = self.allowance_usage.entry(key).or_default();
+        usage.spent = usage.spent.saturating_add(spent);
+        self.emit(TokenEvent::AllowanceConsumed {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount: spent,
+            remaining,
+        });
+    }
+
+    // 모든 잔액 기록은 여기를 거침: 계정 정리(reap)와 비유통 합계를 함께 유지
+    fn write_balance(&mut self, address: &Address, balance: Balance) {
+        self.journal_balance(address);
+        let previous = if balance == 0 && self.config.existential_deposit > 0 {
+            self.balances.remove(address)
+        } else {
+            self.balances.insert(address.clone(), balance)
+        }
+        .unwrap_or(0);
+
+        if self.non_circulating.contains(address) {
+            self.non_circulating_balance = self.non_circulating_balance - previous + balance;
+        }
+    }
+
+    // self-transfer / zero-amount 규칙 적용. Ok(true)면 no-op으로 즉시 성공
+    fn check_edge_cases(
+        &self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<bool, TokenError> {
+        if from == to && self.config.self_transfer == EdgeCaseMode::Reject {
+            return Err(TokenError::SelfTransfer);
+        }
+        self.check_zero_amount(amount)
+    }
+
+    fn check_zero_amount(&self, amount: Balance) -> Result<bool, TokenError> {
+        if amount == 0 {
+            return match self.config.zero_amount {
+                EdgeCaseMode::Reject => Err(TokenError::ZeroAmount),
+                EdgeCaseMode::NoOp => Ok(true),
+            };
+        }
+        Ok(false)
+    }
+}
+
+impl FungibleToken for TokenState {
+    fn total_supply(&self) -> Balance {
+        TokenState::total_supply(self)
+    }
+
+    fn balance_of(&self, address: &Address) -> Balance {
+        TokenState::balance_of(self, address)
+    }
+
+    fn transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        TokenState::transfer(self, from, to, amount)
+    }
+
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        TokenState::approve(self, owner, spender, amount)
+    }
+
+    fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        TokenState::allowance(self, owner, spender)
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        TokenState::transfer_from(self, spender, from, to, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_token() {
+        // AAA Pattern(Arrange-Act-Assert)
+        //   Arrange: 테스트 데이터 준비
+        //   Act: 테스트할 함수 실행
+        //   Assert: 결과 검증
+
+        let creator = Address::new("alice").unwrap();
+        let initial_supply = 1000;
+
+        let token = TokenState::new(creator, initial_supply);
+
+        assert_eq!(token.total_supply(), initial_supply);
+    }
+
+    #[test]
+    fn test_balance_of_existing_address() {
+        let creator = Address::new("alice").unwrap();
+        let initial_supply = 1000;
+        let token = TokenState::new(creator.clone(), initial_supply);
 
         let balance = token.balance_of(&creator);
         assert_eq!(balance, 1000);
@@ -268,19 +1524,19 @@ mod tests {
 
     #[test]
     fn test_balance_of_non_existing_address() {
-        let creator = String::from("alice");
+        let creator = Address::new("alice").unwrap();
         let initial_supply = 1000;
         let token = TokenState::new(creator.clone(), initial_supply);
 
-        let bob = "bob".to_string();
+        let bob = Address::new("bob").unwrap();
         let balance = token.balance_of(&bob);
         assert_eq!(balance, 0);
     }
 
     #[test]
     fn test_transfer_success() {
-        let creator = "alice".to_string();
-        let recipient = String::from("bob");
+        let creator = Address::new("alice").unwrap();
+        let recipient = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(creator.clone(), initial_supply);
 
@@ -294,8 +1550,8 @@ mod tests {
 
     #[test]
     fn test_transfer_insufficient_balance() {
-        let creator = "alice".to_string();
-        let recipient = String::from("bob");
+        let creator = Address::new("alice").unwrap();
+        let recipient = Address::new("bob").unwrap();
         let initial_supply = 100;
         let mut token = TokenState::new(creator.clone(), initial_supply);
 
@@ -312,7 +1568,7 @@ mod tests {
 
     #[test]
     fn test_transfer_self() {
-        let creator = "alice".to_string();
+        let creator = Address::new("alice").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(creator.clone(), initial_supply);
 
@@ -322,8 +1578,8 @@ mod tests {
 
     #[test]
     fn test_transfer_zero_amount() {
-        let creator = "alice".to_string();
-        let reciptient = "bob".to_string();
+        let creator = Address::new("alice").unwrap();
+        let reciptient = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(creator.clone(), initial_supply);
 
@@ -333,13 +1589,13 @@ mod tests {
 
     #[test]
     fn test_transfer_overflow() {
-        let creator = "alice".to_string();
-        let reciptient = "bob".to_string();
+        let creator = Address::new("alice").unwrap();
+        let reciptient = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(creator.clone(), initial_supply);
 
-        // bob에게 일단 u64::MAX - 100을 줌
-        token.mint_for_test(reciptient.clone(), u64::MAX - 100);
+        // bob에게 일단 Balance::MAX - 100을 줌
+        token.mint_for_test(reciptient.clone(), Balance::MAX - 100);
 
         let result = token.transfer(&creator, &reciptient, 200);
         assert_eq!(result.unwrap_err(), TokenError::BalanceOverFlow);
@@ -347,8 +1603,8 @@ mod tests {
 
     #[test]
     fn test_approve_success() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -360,7 +1616,7 @@ mod tests {
 
     #[test]
     fn test_approve_self() {
-        let alice = "alice".to_string();
+        let alice = Address::new("alice").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -371,8 +1627,8 @@ mod tests {
 
     #[test]
     fn test_approve_zero() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -384,8 +1640,8 @@ mod tests {
 
     #[test]
     fn test_approve_overwrite() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -399,9 +1655,9 @@ mod tests {
 
     #[test]
     fn test_transfer_from_success() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
-        let charlie = "charlie".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -417,9 +1673,9 @@ mod tests {
 
     #[test]
     fn test_transfer_from_insufficient_allowance() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
-        let charlie = "charlie".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -437,9 +1693,9 @@ mod tests {
 
     #[test]
     fn test_transfer_from_insufficient_balance() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
-        let charlie = "charlie".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
         let initial_supply = 100;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -457,10 +1713,10 @@ mod tests {
 
     #[test]
     fn test_transfer_from_updates_allowance() {
-        let alice = "alice".to_string();
-        let bob = "bob".to_string();
-        let david = "david".to_string();
-        let charlie = "charlie".to_string();
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let david = Address::new("david").unwrap();
+        let charlie = Address::new("charlie").unwrap();
         let initial_supply = 1000;
         let mut token = TokenState::new(alice.clone(), initial_supply);
 
@@ -470,4 +1726,627 @@ mod tests {
 
         assert_eq!(token.allowance(&alice, &bob), 50);
     }
+
+    #[test]
+    fn test_erc20_compatible_self_transfer_is_noop() {
+        let alice = Address::new("alice").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 1000, TokenConfig::erc20_compatible());
+
+        let result = token.transfer(&alice, &alice, 100);
+
+        assert!(result.is_ok());
+        assert_eq!(token.balance_of(&alice), 1000);
+    }
+
+    #[test]
+    fn test_erc20_compatible_self_transfer_checks_balance() {
+        let alice = Address::new("alice").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 100, TokenConfig::erc20_compatible());
+
+        let result = token.transfer(&alice, &alice, 200);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::InsufficientBalance {
+                required: 200,
+                available: 100
+            }
+        );
+    }
+
+    #[test]
+    fn test_erc20_compatible_zero_amount_is_noop() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 1000, TokenConfig::erc20_compatible());
+
+        assert!(token.transfer(&bob, &alice, 0).is_ok());
+        assert!(token.transfer_from(&bob, &alice, &bob, 0).is_ok());
+        assert_eq!(token.balance_of(&alice), 1000);
+    }
+
+    #[test]
+    fn test_erc20_compatible_self_transfer_from_consumes_allowance() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 1000, TokenConfig::erc20_compatible());
+        token.approve(&alice, &bob, 100).unwrap();
+
+        let result = token.transfer_from(&bob, &alice, &alice, 60);
+
+        assert!(result.is_ok());
+        assert_eq!(token.balance_of(&alice), 1000);
+        assert_eq!(token.allowance(&alice, &bob), 40);
+    }
+
+    #[test]
+    fn test_approve_if_matching() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 100).unwrap();
+
+        let result = token.approve_if(&alice, &bob, 100, 50);
+
+        assert!(result.is_ok());
+        assert_eq!(token.allowance(&alice, &bob), 50);
+    }
+
+    #[test]
+    fn test_approve_if_mismatch() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 100).unwrap();
+
+        let result = token.approve_if(&alice, &bob, 0, 50);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::AllowanceMismatch {
+                expected: 0,
+                actual: 100
+            }
+        );
+        assert_eq!(token.allowance(&alice, &bob), 100);
+    }
+
+    #[test]
+    fn test_allowance_usage_tracks_spending() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.approve(&alice, &bob, 100).unwrap();
+        token.transfer_from(&bob, &alice, &charlie, 30).unwrap();
+        token.transfer_from(&bob, &alice, &charlie, 20).unwrap();
+
+        assert_eq!(token.allowance_usage(&alice, &bob), (100, 50, 50));
+    }
+
+    #[test]
+    fn test_allowance_usage_counts_only_increases() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.approve(&alice, &bob, 100).unwrap();
+        token.transfer_from(&bob, &alice, &charlie, 80).unwrap();
+        token.approve(&alice, &bob, 100).unwrap();
+        token.approve(&alice, &bob, 10).unwrap();
+
+        assert_eq!(token.allowance_usage(&alice, &bob), (180, 80, 10));
+    }
+
+    #[test]
+    fn test_allowance_usage_unknown_pair() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let token = TokenState::new(alice.clone(), 1000);
+
+        assert_eq!(token.allowance_usage(&alice, &bob), (0, 0, 0));
+    }
+
+    fn dust_config(policy: DustPolicy) -> TokenConfig {
+        TokenConfig {
+            existential_deposit: 10,
+            dust_policy: policy,
+            ..TokenConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_dust_remainder_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token =
+            TokenState::with_config(alice.clone(), 100, dust_config(DustPolicy::Reject));
+
+        let result = token.transfer(&alice, &bob, 95);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+        assert_eq!(token.balance_of(&alice), 100);
+    }
+
+    #[test]
+    fn test_dust_remainder_swept_and_reaped() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::with_config(alice.clone(), 100, dust_config(DustPolicy::Sweep));
+
+        token.transfer(&alice, &bob, 95).unwrap();
+
+        assert_eq!(token.balance_of(&alice), 0);
+        assert_eq!(token.balance_of(&bob), 100);
+        assert_eq!(token.account_count(), 1);
+    }
+
+    #[test]
+    fn test_dust_recipient_below_minimum_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::with_config(alice.clone(), 100, dust_config(DustPolicy::Sweep));
+
+        let result = token.transfer(&alice, &bob, 5);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_dust_transfer_from_never_sweeps() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
+        let mut token = TokenState::with_config(alice.clone(), 100, dust_config(DustPolicy::Sweep));
+        token.approve(&alice, &bob, 100).unwrap();
+
+        let result = token.transfer_from(&bob, &alice, &charlie, 95);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+        assert_eq!(token.allowance(&alice, &bob), 100);
+    }
+
+    fn burn_config() -> TokenConfig {
+        TokenConfig {
+            burn_addresses: HashSet::from([Address::new(BURN_ADDRESS).unwrap()]),
+            ..TokenConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_transfer_to_burn_address_reduces_supply() {
+        let alice = Address::new("alice").unwrap();
+        let dead = Address::new(BURN_ADDRESS).unwrap();
+        let mut token = TokenState::with_config(alice.clone(), 1000, burn_config());
+
+        token.transfer(&alice, &dead, 300).unwrap();
+
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.balance_of(&dead), 0);
+        assert_eq!(token.total_supply(), 700);
+    }
+
+    #[test]
+    fn test_transfer_from_to_burn_address_reduces_supply() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let dead = Address::new(BURN_ADDRESS).unwrap();
+        let mut token = TokenState::with_config(alice.clone(), 1000, burn_config());
+        token.approve(&alice, &bob, 100).unwrap();
+
+        token.transfer_from(&bob, &alice, &dead, 100).unwrap();
+
+        assert_eq!(token.total_supply(), 900);
+        assert_eq!(token.allowance(&alice, &bob), 0);
+    }
+
+    #[test]
+    fn test_burn_address_not_configured_by_default() {
+        let alice = Address::new("alice").unwrap();
+        let dead = Address::new(BURN_ADDRESS).unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.transfer(&alice, &dead, 300).unwrap();
+
+        assert_eq!(token.balance_of(&dead), 300);
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_circulating_supply_excludes_tagged_addresses() {
+        let alice = Address::new("alice").unwrap();
+        let treasury = Address::new("treasury").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &treasury, 400).unwrap();
+
+        let unauthorized = token.mark_non_circulating(&treasury, &treasury);
+        token.mark_non_circulating(&alice, &treasury).unwrap();
+
+        assert_eq!(
+            unauthorized,
+            Err(TokenError::Unauthorized {
+                caller: treasury.clone()
+            })
+        );
+        assert!(token.is_non_circulating(&treasury));
+        assert_eq!(token.circulating_supply(), 600);
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_circulating_supply_follows_transfers() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let treasury = Address::new("treasury").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.mark_non_circulating(&alice, &treasury).unwrap();
+
+        token.transfer(&alice, &treasury, 500).unwrap();
+        token.transfer(&treasury, &bob, 200).unwrap();
+
+        assert_eq!(token.circulating_supply(), 700);
+    }
+
+    #[test]
+    fn test_unmark_non_circulating_restores_supply() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.mark_non_circulating(&alice, &alice).unwrap();
+        token.mark_non_circulating(&alice, &alice).unwrap();
+        assert_eq!(token.circulating_supply(), 0);
+
+        token.unmark_non_circulating(&alice, &alice).unwrap();
+
+        assert_eq!(token.circulating_supply(), 1000);
+    }
+
+    #[test]
+    fn test_bulk_credit_populates_accounts() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let addresses = addrgen::generate(1, 1000);
+
+        token
+            .bulk_credit(&alice, addresses.iter().map(|a| (a.clone(), 5)))
+            .unwrap();
+
+        assert_eq!(token.total_supply(), 6000);
+        assert_eq!(token.account_count(), 1001);
+        assert_eq!(token.balance_of(&addresses[999]), 5);
+    }
+
+    #[test]
+    fn test_bulk_credit_overflow_applies_nothing() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let result = token.bulk_credit(&alice, [(bob.clone(), 10), (alice.clone(), Balance::MAX)]);
+
+        assert_eq!(result.unwrap_err(), TokenError::BalanceOverFlow);
+        assert_eq!(token.balance_of(&bob), 0);
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_bulk_credit_is_a_checked_mint() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 10,
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+
+        let stranger = token.bulk_credit(&bob, [(bob.clone(), 100)]);
+        let dust = token.bulk_credit(&alice, [(bob.clone(), 100), (carol.clone(), 5)]);
+        // 같은 주소에 나눠 넣어도 합계가 최소 잔액 이상이면 허용
+        token
+            .bulk_credit(&alice, [(carol.clone(), 5), (carol.clone(), 5)])
+            .unwrap();
+
+        assert_eq!(
+            stranger.unwrap_err(),
+            TokenError::Unauthorized {
+                caller: bob.clone()
+            }
+        );
+        assert_eq!(
+            dust.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+        assert_eq!(token.balance_of(&bob), 0);
+        assert_eq!(token.balance_of(&carol), 10);
+        assert_eq!(token.total_supply(), 1010);
+    }
+
+    #[test]
+    fn test_from_balances() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+
+        let token =
+            TokenState::from_balances([(alice.clone(), 700), (bob.clone(), 300), (bob.clone(), 5)])
+                .unwrap();
+
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.balance_of(&bob), 305);
+        assert_eq!(token.total_supply(), 1005);
+        assert_eq!(token.account_count(), 2);
+    }
+
+    #[test]
+    fn test_from_balances_supply_overflow() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+
+        let result = TokenState::from_balances([(alice, Balance::MAX), (bob, 1)]);
+
+        assert_eq!(result.err(), Some(TokenError::BalanceOverFlow));
+    }
+
+    #[test]
+    fn test_reserve_and_shrink_keep_state() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.reserve_accounts(10_000);
+        token.reserve_allowances(1_000);
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&alice, &bob, 50).unwrap();
+        token.shrink_to_fit();
+
+        assert_eq!(token.balance_of(&bob), 100);
+        assert_eq!(token.allowance(&alice, &bob), 50);
+        assert_eq!(token.account_count(), 2);
+    }
+
+    #[test]
+    fn test_mint_and_burn_track_supply() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.mint(&alice, &bob, 500).unwrap();
+        token.burn(&alice, 300).unwrap();
+
+        assert_eq!(token.balance_of(&bob), 500);
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.total_supply(), 1200);
+    }
+
+    #[test]
+    fn test_mint_supply_overflow() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), Balance::MAX - 10);
+
+        let result = token.mint(&alice, &bob, 11);
+
+        assert_eq!(result.unwrap_err(), TokenError::SupplyOverflow);
+        assert_eq!(token.balance_of(&bob), 0);
+        assert_eq!(token.total_supply(), Balance::MAX - 10);
+    }
+
+    #[test]
+    fn test_burn_insufficient_balance() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 100);
+
+        let result = token.burn(&alice, 101);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::InsufficientBalance {
+                required: 101,
+                available: 100
+            }
+        );
+        assert_eq!(token.total_supply(), 100);
+    }
+
+    #[test]
+    fn test_mint_burn_zero_amount_follow_config() {
+        let alice = Address::new("alice").unwrap();
+        let mut strict = TokenState::new(alice.clone(), 100);
+        let mut lenient =
+            TokenState::with_config(alice.clone(), 100, TokenConfig::erc20_compatible());
+
+        assert_eq!(
+            strict.mint(&alice, &alice, 0).unwrap_err(),
+            TokenError::ZeroAmount
+        );
+        assert_eq!(strict.burn(&alice, 0).unwrap_err(), TokenError::ZeroAmount);
+        lenient.mint(&alice, &alice, 0).unwrap();
+        lenient.burn(&alice, 0).unwrap();
+        assert_eq!(lenient.total_supply(), 100);
+    }
+
+    #[test]
+    fn test_burn_respects_existential_deposit() {
+        let alice = Address::new("alice").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 10,
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 100, config);
+
+        let dust = token.burn(&alice, 95);
+        token.burn(&alice, 100).unwrap();
+
+        assert_eq!(
+            dust.unwrap_err(),
+            TokenError::BelowExistentialDeposit {
+                balance: 5,
+                minimum: 10
+            }
+        );
+        assert_eq!(token.account_count(), 0);
+        assert_eq!(token.total_supply(), 0);
+    }
+
+    #[test]
+    fn test_mint_updates_circulating_supply() {
+        let alice = Address::new("alice").unwrap();
+        let treasury = Address::new("treasury").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.mark_non_circulating(&alice, &treasury).unwrap();
+
+        token.mint(&alice, &treasury, 400).unwrap();
+
+        assert_eq!(token.total_supply(), 1400);
+        assert_eq!(token.circulating_supply(), 1000);
+    }
+
+    #[test]
+    fn test_increase_and_decrease_allowance() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 100).unwrap();
+
+        token.increase_allowance(&alice, &bob, 50).unwrap();
+        token.decrease_allowance(&alice, &bob, 120).unwrap();
+
+        assert_eq!(token.allowance(&alice, &bob), 30);
+        assert_eq!(token.allowance_usage(&alice, &bob), (150, 0, 30));
+    }
+
+    #[test]
+    fn test_increase_allowance_overflow() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, Balance::MAX).unwrap();
+
+        let result = token.increase_allowance(&alice, &bob, 1);
+
+        assert_eq!(result.unwrap_err(), TokenError::AllowanceOverflow);
+        assert_eq!(token.allowance(&alice, &bob), Balance::MAX);
+    }
+
+    #[test]
+    fn test_decrease_allowance_underflow() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 10).unwrap();
+
+        let result = token.decrease_allowance(&alice, &bob, 11);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::AllowanceUnderflow {
+                required: 11,
+                available: 10
+            }
+        );
+        assert_eq!(token.allowance(&alice, &bob), 10);
+    }
+
+    #[test]
+    fn test_adjust_allowance_rejects_self() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let result = token.increase_allowance(&alice, &alice, 1);
+
+        assert_eq!(result.unwrap_err(), TokenError::SelfApproval);
+    }
+
+    #[test]
+    fn test_burn_from_consumes_allowance() {
+        let alice = Address::new("alice").unwrap();
+        let bridge = Address::new("bridge").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bridge, 300).unwrap();
+
+        token.burn_from(&bridge, &alice, 200).unwrap();
+
+        assert_eq!(token.balance_of(&alice), 800);
+        assert_eq!(token.total_supply(), 800);
+        assert_eq!(token.allowance(&alice, &bridge), 100);
+        assert_eq!(token.allowance_usage(&alice, &bridge), (300, 200, 100));
+    }
+
+    #[test]
+    fn test_burn_from_insufficient_allowance() {
+        let alice = Address::new("alice").unwrap();
+        let bridge = Address::new("bridge").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bridge, 50).unwrap();
+
+        let result = token.burn_from(&bridge, &alice, 51);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::InsufficientAllowance {
+                required: 51,
+                available: 50
+            }
+        );
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_burn_from_insufficient_balance_keeps_allowance() {
+        let alice = Address::new("alice").unwrap();
+        let bridge = Address::new("bridge").unwrap();
+        let mut token = TokenState::new(alice.clone(), 100);
+        token.approve(&alice, &bridge, 500).unwrap();
+
+        let result = token.burn_from(&bridge, &alice, 200);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::InsufficientBalance {
+                required: 200,
+                available: 100
+            }
+        );
+        assert_eq!(token.allowance(&alice, &bridge), 500);
+    }
+
+    #[cfg(feature = "u128-balance")]
+    #[test]
+    fn test_supply_beyond_u64() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let supply = Balance::from(u64::MAX) * 4;
+        let mut token = TokenState::new(alice.clone(), supply);
+
+        token.transfer(&alice, &bob, supply / 2).unwrap();
+
+        assert_eq!(token.balance_of(&bob), Balance::from(u64::MAX) * 2);
+        assert_eq!(token.total_supply(), supply);
+    }
 }
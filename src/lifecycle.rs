@@ -0,0 +1,164 @@
+//! Snapshot on shutdown and recovery on startup.
+//!
+//! [`TokenState::shutdown`] writes a [`TokenState::to_bytes`] image next to
+//! the target path, syncs it, and renames it into place, so the file at the
+//! target path is always either the previous snapshot or the new one, never
+//! a half-written mix. [`TokenState::recover`] loads that file on startup,
+//! discarding any temporary file a crash left behind, and reports the
+//! sequence number the ledger resumes from.
+//!
+//! The event log travels inside the snapshot; events delivered to sinks
+//! after the last snapshot are not replayed.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::TokenState;
+use crate::codec::SnapshotError;
+
+/// Why a snapshot could not be written or recovered.
+#[derive(Debug)]
+pub enum LifecycleError {
+    Io(io::Error),
+    /// The snapshot file exists but does not decode
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "snapshot i/o: {e}"),
+            Self::Snapshot(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LifecycleError {}
+
+impl From<io::Error> for LifecycleError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A ledger restored by [`TokenState::recover`].
+pub struct Recovery {
+    pub state: TokenState,
+    /// Sequence number the next operation will get
+    pub seq: u64,
+    /// A write was interrupted and its partial file was discarded
+    pub discarded_partial: bool,
+}
+
+impl TokenState {
+    /// Atomically replaces the snapshot at `path` with the current state.
+    ///
+    /// Returns the sequence number recorded in the snapshot.
+    pub fn shutdown(&self, path: impl AsRef<Path>) -> Result<u64, LifecycleError> {
+        let path = path.as_ref();
+        let partial = partial_path(path);
+        let mut file = File::create(&partial)?;
+        file.write_all(&self.to_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&partial, path)?;
+        // rename 자체를 디스크에 반영 (디렉터리 fsync, 지원 안 되는 플랫폼은 무시)
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let _ = File::open(dir).and_then(|d| d.sync_all());
+        }
+        Ok(self.next_seq())
+    }
+
+    /// Loads the snapshot at `path`, or `None` if none was ever completed.
+    pub fn recover(path: impl AsRef<Path>) -> Result<Option<Recovery>, LifecycleError> {
+        let path = path.as_ref();
+        let partial = partial_path(path);
+        let discarded_partial = partial.exists();
+        if discarded_partial {
+            fs::remove_file(&partial)?;
+        }
+
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let state = TokenState::from_bytes(&bytes).map_err(LifecycleError::Snapshot)?;
+        Ok(Some(Recovery {
+            seq: state.next_seq(),
+            state,
+            discarded_partial,
+        }))
+    }
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+
+    fn scratch(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("token-standard-{}-{name}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(partial_path(&path));
+        path
+    }
+
+    #[test]
+    fn test_shutdown_then_recover() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 10).unwrap();
+        let path = scratch("round-trip");
+
+        let seq = token.shutdown(&path).unwrap();
+        let recovery = TokenState::recover(&path).unwrap().unwrap();
+
+        assert_eq!(seq, 1);
+        assert_eq!(recovery.seq, 1);
+        assert!(!recovery.discarded_partial);
+        assert_eq!(recovery.state.balance_of(&bob), 10);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_write_keeps_previous_snapshot() {
+        let alice = Address::new("alice").unwrap();
+        let token = TokenState::new(alice.clone(), 1000);
+        let path = scratch("interrupted");
+        token.shutdown(&path).unwrap();
+        // 쓰기 도중 크래시: rename 전의 잘린 임시 파일만 남음
+        fs::write(partial_path(&path), &token.to_bytes()[..7]).unwrap();
+
+        let recovery = TokenState::recover(&path).unwrap().unwrap();
+
+        assert!(recovery.discarded_partial);
+        assert_eq!(recovery.state.balance_of(&alice), 1000);
+        assert!(!partial_path(&path).exists());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_fresh_start_and_corrupt_snapshot() {
+        let path = scratch("missing");
+
+        assert!(TokenState::recover(&path).unwrap().is_none());
+        fs::write(&path, b"garbage").unwrap();
+        assert!(matches!(
+            TokenState::recover(&path),
+            Err(LifecycleError::Snapshot(SnapshotError::BadMagic))
+        ));
+        fs::remove_file(path).unwrap();
+    }
+}
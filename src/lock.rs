@@ -0,0 +1,221 @@
+//! Timelocked balances.
+//!
+//! [`TokenState::lock`] sets part of a holder's balance aside until
+//! `unlock_at` by the token's [`Clock`](crate::clock::Clock), for staking
+//! and governance lockups. Locked tokens stay in the holder's balance and
+//! count toward supply, but every debit — `transfer`, `transfer_from`,
+//! `burn`, `shield`, capability redemption — is checked against
+//! [`TokenState::available_balance`] instead, and a dust sweep never
+//! takes them.
+//!
+//! A holder may have several locks with different deadlines. A lock stops
+//! restricting the balance as soon as the clock reaches `unlock_at`;
+//! [`TokenState::unlock`] then removes the matured locks from the record.
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError, TokenState};
+
+/// `amount` of a holder's balance, held until `unlock_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lock {
+    pub amount: Balance,
+    /// First instant at which the tokens may move again
+    pub unlock_at: u64,
+}
+
+impl TokenState {
+    /// Holds `amount` of `owner`'s available balance until `unlock_at`.
+    pub fn lock(
+        &mut self,
+        owner: &Address,
+        amount: Balance,
+        unlock_at: u64,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::Lock {
+            owner: owner.clone(),
+            amount,
+            unlock_at,
+        })
+        .map(|_| ())
+    }
+
+    /// Drops `owner`'s matured locks.
+    ///
+    /// Fails with [`TokenError::NothingToUnlock`] if none has matured.
+    pub fn unlock(&mut self, owner: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::Unlock {
+            owner: owner.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// `owner`'s locks, matured or not, in the order they were made.
+    pub fn locks(&self, owner: &Address) -> &[Lock] {
+        self.locks.get(owner).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Part of `owner`'s balance still held by a lock.
+    pub fn locked_balance(&self, owner: &Address) -> Balance {
+        let now = self.now();
+        self.locks(owner)
+            .iter()
+            .filter(|lock| now < lock.unlock_at)
+            .fold(0, |acc: Balance, lock| acc.saturating_add(lock.amount))
+    }
+
+    /// Part of `owner`'s balance that may be moved or burned now.
+    pub fn available_balance(&self, owner: &Address) -> Balance {
+        self.balance_of(owner)
+            .saturating_sub(self.locked_balance(owner))
+    }
+
+    pub(crate) fn apply_lock(
+        &mut self,
+        owner: &Address,
+        amount: Balance,
+        unlock_at: u64,
+    ) -> Result<(), TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        self.check_spendable(owner, amount)?;
+
+        self.journal_lock(owner);
+        self.locks
+            .entry(owner.clone())
+            .or_default()
+            .push(Lock { amount, unlock_at });
+        self.emit(TokenEvent::Locked {
+            owner: owner.clone(),
+            amount,
+            unlock_at,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn apply_unlock(&mut self, owner: &Address) -> Result<(), TokenError> {
+        let now = self.now();
+        let (matured, held): (Vec<Lock>, Vec<Lock>) = self
+            .locks(owner)
+            .iter()
+            .partition(|lock| now >= lock.unlock_at);
+        if matured.is_empty() {
+            return Err(TokenError::NothingToUnlock);
+        }
+
+        self.journal_lock(owner);
+        if held.is_empty() {
+            self.locks.remove(owner);
+        } else {
+            self.locks.insert(owner.clone(), held);
+        }
+        let amount = matured
+            .iter()
+            .fold(0, |acc: Balance, lock| acc.saturating_add(lock.amount));
+        self.emit(TokenEvent::Unlocked {
+            owner: owner.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // 잠기지 않은 잔액으로 출금 가능 여부 검사, 통과하면 전체 잔액 반환
+    pub(crate) fn check_spendable(
+        &self,
+        from: &Address,
+        amount: Balance,
+    ) -> Result<Balance, TokenError> {
+        let available = self.available_balance(from);
+        if available < amount {
+            return Err(TokenError::InsufficientBalance {
+                required: amount,
+                available,
+            });
+        }
+        Ok(self.balance_of(from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::test_util::addresses;
+
+    fn setup() -> (TokenState, ManualClock, [Address; 2]) {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let clock = ManualClock::new(100);
+        let token = TokenState::new(alice.clone(), 1000).with_clock(Box::new(clock.clone()));
+        (token, clock, [alice, bob])
+    }
+
+    #[test]
+    fn test_locked_tokens_cannot_move_until_deadline() {
+        let (mut token, clock, [alice, bob]) = setup();
+        token.lock(&alice, 600, 200).unwrap();
+
+        let early = token.transfer(&alice, &bob, 500);
+        token.transfer(&alice, &bob, 400).unwrap();
+        let burn = token.burn(&alice, 1);
+        clock.advance(100);
+        token.transfer(&alice, &bob, 600).unwrap();
+
+        let locked = Err(TokenError::InsufficientBalance {
+            required: 500,
+            available: 400,
+        });
+        assert_eq!(early, locked);
+        assert_eq!(
+            burn,
+            Err(TokenError::InsufficientBalance {
+                required: 1,
+                available: 0
+            })
+        );
+        assert_eq!(token.balance_of(&bob), 1000);
+    }
+
+    #[test]
+    fn test_unlock_drops_only_matured_locks() {
+        let (mut token, clock, [alice, _]) = setup();
+        token.lock(&alice, 100, 150).unwrap();
+        token.lock(&alice, 200, 300).unwrap();
+
+        let too_soon = token.unlock(&alice);
+        clock.advance(50);
+        token.unlock(&alice).unwrap();
+
+        assert_eq!(too_soon, Err(TokenError::NothingToUnlock));
+        assert_eq!(
+            token.locks(&alice),
+            [Lock {
+                amount: 200,
+                unlock_at: 300
+            }]
+        );
+        assert_eq!(token.available_balance(&alice), 800);
+        assert_eq!(
+            token.lock(&alice, 801, 400),
+            Err(TokenError::InsufficientBalance {
+                required: 801,
+                available: 800
+            })
+        );
+    }
+
+    #[test]
+    fn test_locks_survive_snapshot_and_revert() {
+        let (mut token, _, [alice, _]) = setup();
+        token.lock(&alice, 100, 150).unwrap();
+        let id = token.checkpoint();
+        token.lock(&alice, 200, 300).unwrap();
+        token.revert_to(id).unwrap();
+
+        let restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+
+        assert_eq!(restored.locks(&alice), token.locks(&alice));
+        assert_eq!(token.locks(&alice).len(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+//! Merkle commitments over balances for light clients.
+//!
+//! A [`BalanceTree`] hashes every non-zero balance, in address order, into a
+//! binary SHA-256 tree. A light client that trusts only the root can check
+//! a [`BalanceProof`] with [`verify_proof`] and learn one account's balance
+//! without downloading the state.
+//!
+//! Leaves are `H(0x00 || len || address || balance)` and inner nodes
+//! `H(0x01 || left || right)`, the prefixes keeping a leaf from ever being
+//! passed off as an inner node. When a level has an odd number of nodes the
+//! last one is carried up unchanged rather than paired with itself, which
+//! rules out the duplicated-last-leaf collisions of Bitcoin-style trees.
+//! Balances are widened to 128
+//! bits, making roots identical across `u64` and `u128-balance` builds.
+//! The root of an empty tree is all zeroes.
+
+use crate::sha256::Sha256;
+use crate::{Address, Balance, TokenState};
+
+/// 32-byte SHA-256 digest.
+pub type Hash = [u8; 32];
+
+/// Evidence that `address` held `balance` under some root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BalanceProof {
+    pub address: Address,
+    pub balance: Balance,
+    /// Position of the leaf among all leaves
+    pub index: u64,
+    /// Number of leaves in the tree
+    pub leaf_count: u64,
+    /// Sibling hashes from the leaf level upward; levels where the node was
+    /// carried up have no entry
+    pub siblings: Vec<Hash>,
+}
+
+/// All levels of the balance tree, leaves first.
+pub struct BalanceTree {
+    addresses: Vec<(Address, Balance)>,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl BalanceTree {
+    pub fn root(&self) -> Hash {
+        self.levels
+            .last()
+            .and_then(|top| top.first())
+            .copied()
+            .unwrap_or([0; 32])
+    }
+
+    /// Inclusion proof for `address`, or `None` if it holds no balance.
+    pub fn prove(&self, address: &Address) -> Option<BalanceProof> {
+        let index = self
+            .addresses
+            .binary_search_by(|(a, _)| a.cmp(address))
+            .ok()?;
+
+        let mut siblings = Vec::new();
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = i ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            i /= 2;
+        }
+
+        Some(BalanceProof {
+            address: address.clone(),
+            balance: self.addresses[index].1,
+            index: index as u64,
+            leaf_count: self.addresses.len() as u64,
+            siblings,
+        })
+    }
+}
+
+impl TokenState {
+    /// Builds the Merkle tree over current balances. O(n log n).
+    pub fn balance_tree(&self) -> BalanceTree {
+        let mut addresses: Vec<_> = self
+            .balances
+            .iter()
+            .filter(|(_, b)| **b > 0)
+            .map(|(a, b)| (a.clone(), *b))
+            .collect();
+        addresses.sort_unstable();
+
+        let mut levels = vec![
+            addresses
+                .iter()
+                .map(|(a, b)| leaf(a, *b))
+                .collect::<Vec<_>>(),
+        ];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let below = levels.last().unwrap();
+            let above = below
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(above);
+        }
+        BalanceTree { addresses, levels }
+    }
+
+    /// Root of [`TokenState::balance_tree`].
+    pub fn balance_root(&self) -> Hash {
+        self.balance_tree().root()
+    }
+
+    /// Inclusion proof for one account against [`TokenState::balance_root`].
+    ///
+    /// Builds the whole tree; use [`TokenState::balance_tree`] to prove
+    /// many accounts at once.
+    pub fn prove_balance(&self, address: &Address) -> Option<BalanceProof> {
+        self.balance_tree().prove(address)
+    }
+}
+
+/// Checks that `proof` commits to its balance under `root`.
+pub fn verify_proof(root: &Hash, proof: &BalanceProof) -> bool {
+    if proof.index >= proof.leaf_count {
+        return false;
+    }
+
+    let mut hash = leaf(&proof.address, proof.balance);
+    let mut siblings = proof.siblings.iter();
+    let (mut i, mut width) = (proof.index, proof.leaf_count);
+    while width > 1 {
+        // 홀수 레벨의 마지막 노드는 형제 없이 그대로 올라감
+        if i ^ 1 < width {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = if i.is_multiple_of(2) {
+                node(&hash, sibling)
+            } else {
+                node(sibling, &hash)
+            };
+        }
+        i /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && hash == *root
+}
+
+#[allow(clippy::unnecessary_cast)]
+fn leaf(address: &Address, balance: Balance) -> Hash {
+    let raw = address.as_str().as_bytes();
+    let mut hasher = Sha256::new();
+    hasher.update(&[0x00]);
+    hasher.update(&(raw.len() as u64).to_le_bytes());
+    hasher.update(raw);
+    hasher.update(&(balance as u128).to_le_bytes());
+    hasher.finish()
+}
+
+fn node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(&[0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenConfig;
+
+    fn token_with(n: usize) -> TokenState {
+        let balances = (0..n)
+            .map(|i| (Address::new(format!("acct{i}")).unwrap(), i as Balance + 1))
+            .collect();
+        let supply = (1..=n as Balance).sum();
+        TokenState::from_parts(balances, supply, TokenConfig::default())
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        for n in 1..=9 {
+            let token = token_with(n);
+            let tree = token.balance_tree();
+            let root = tree.root();
+
+            for i in 0..n {
+                let address = Address::new(format!("acct{i}")).unwrap();
+                let proof = tree.prove(&address).unwrap();
+
+                assert_eq!(proof.balance, i as Balance + 1);
+                assert!(verify_proof(&root, &proof), "n={n} i={i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_fail() {
+        let token = token_with(7);
+        let root = token.balance_root();
+        let proof = token
+            .prove_balance(&Address::new("acct3").unwrap())
+            .unwrap();
+
+        let mut inflated = proof.clone();
+        inflated.balance += 1;
+        let mut moved = proof.clone();
+        moved.index = 2;
+        let mut short = proof.clone();
+        short.siblings.pop();
+
+        assert!(verify_proof(&root, &proof));
+        assert!(!verify_proof(&root, &inflated));
+        assert!(!verify_proof(&root, &moved));
+        assert!(!verify_proof(&root, &short));
+        assert!(!verify_proof(&[0; 32], &proof));
+    }
+
+    #[test]
+    fn test_root_tracks_balances() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let before = token.balance_root();
+
+        token.transfer(&alice, &bob, 10).unwrap();
+
+        assert_ne!(token.balance_root(), before);
+        assert_eq!(token.prove_balance(&Address::new("carol").unwrap()), None);
+        assert_eq!(
+            TokenState::from_parts(Default::default(), 0, TokenConfig::default()).balance_root(),
+            [0; 32]
+        );
+    }
+}
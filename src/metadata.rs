@@ -0,0 +1,78 @@
+//! ERC-20 style descriptive metadata.
+//!
+//! Wallets and explorers need a token's name and symbol to label it, and its
+//! number of decimals to render raw amounts: with 6 decimals, a balance of
+//! 1_500_000 reads as 1.5. A token built with
+//! [`TokenState::new_with_metadata`] carries a [`TokenMetadata`] for its
+//! lifetime; tokens built otherwise have none. The metadata is purely
+//! descriptive and never affects ledger rules.
+
+use crate::{Address, Balance, TokenState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    /// Places after the decimal point when displaying amounts
+    pub decimals: u8,
+}
+
+impl TokenMetadata {
+    pub fn new(name: impl Into<String>, symbol: impl Into<String>, decimals: u8) -> Self {
+        Self {
+            name: name.into(),
+            symbol: symbol.into(),
+            decimals,
+        }
+    }
+}
+
+impl TokenState {
+    /// Like [`TokenState::new`], with name, symbol and decimals attached.
+    pub fn new_with_metadata(
+        creator: Address,
+        initial_supply: Balance,
+        metadata: TokenMetadata,
+    ) -> Self {
+        let mut token = Self::new(creator, initial_supply);
+        token.metadata = Some(metadata);
+        token
+    }
+
+    pub fn metadata(&self) -> Option<&TokenMetadata> {
+        self.metadata.as_ref()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.metadata.as_ref().map(|m| m.name.as_str())
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        self.metadata.as_ref().map(|m| m.symbol.as_str())
+    }
+
+    pub fn decimals(&self) -> Option<u8> {
+        self.metadata.as_ref().map(|m| m.decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors_report_metadata() {
+        let alice = Address::new("alice").unwrap();
+        let usd = TokenMetadata::new("Test Dollar", "TUSD", 6);
+
+        let token = TokenState::new_with_metadata(alice.clone(), 1_000_000, usd.clone());
+        let plain = TokenState::new(alice, 1000);
+
+        assert_eq!(token.name(), Some("Test Dollar"));
+        assert_eq!(token.symbol(), Some("TUSD"));
+        assert_eq!(token.decimals(), Some(6));
+        assert_eq!(token.metadata(), Some(&usd));
+        assert_eq!(plain.metadata(), None);
+    }
+}
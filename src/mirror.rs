@@ -0,0 +1,287 @@
+//! Dual-write shadow mode for migrating between token backends.
+//!
+//! [`DualWrite`] wraps a primary and a shadow [`FungibleToken`]. Every
+//! mutation goes to the primary first and is then replayed on the shadow;
+//! callers only ever see the primary's result and reads are served by the
+//! primary alone, so a misbehaving shadow can't affect live traffic.
+//!
+//! Divergences are recorded rather than raised: a mutation whose results
+//! differ is logged immediately, and every `compare_every` mutations the
+//! total supply and the balances and allowances touched since the last
+//! comparison are diffed. Once a migration has run clean for long enough
+//! the shadow can be promoted with [`DualWrite::into_parts`].
+
+use std::collections::HashSet;
+
+use crate::{Address, Balance, FungibleToken, TokenError};
+
+/// A disagreement between the primary and the shadow.
+#[derive(Debug, PartialEq)]
+pub enum Divergence {
+    /// The same mutation succeeded on one side and failed (or failed
+    /// differently) on the other
+    Result {
+        op: String,
+        primary: Result<(), TokenError>,
+        shadow: Result<(), TokenError>,
+    },
+    Supply {
+        primary: Balance,
+        shadow: Balance,
+    },
+    Balance {
+        address: Address,
+        primary: Balance,
+        shadow: Balance,
+    },
+    Allowance {
+        owner: Address,
+        spender: Address,
+        primary: Balance,
+        shadow: Balance,
+    },
+}
+
+pub struct DualWrite<P: FungibleToken, S: FungibleToken> {
+    primary: P,
+    shadow: S,
+    compare_every: u64,
+    writes: u64,
+    touched: HashSet<Address>,
+    touched_allowances: HashSet<(Address, Address)>,
+    divergences: Vec<Divergence>,
+}
+
+impl<P: FungibleToken, S: FungibleToken> DualWrite<P, S> {
+    /// Mirrors writes from `primary` to `shadow`, diffing state every
+    /// `compare_every` mutations (0 disables the periodic diff).
+    pub fn new(primary: P, shadow: S, compare_every: u64) -> Self {
+        Self {
+            primary,
+            shadow,
+            compare_every,
+            writes: 0,
+            touched: HashSet::new(),
+            touched_allowances: HashSet::new(),
+            divergences: Vec::new(),
+        }
+    }
+
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    pub fn shadow(&self) -> &S {
+        &self.shadow
+    }
+
+    pub fn into_parts(self) -> (P, S) {
+        (self.primary, self.shadow)
+    }
+
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+
+    pub fn take_divergences(&mut self) -> Vec<Divergence> {
+        std::mem::take(&mut self.divergences)
+    }
+
+    /// Diffs supply and every account and allowance touched since the last
+    /// comparison, recording any mismatch.
+    pub fn compare(&mut self) {
+        let (primary, shadow) = (self.primary.total_supply(), self.shadow.total_supply());
+        if primary != shadow {
+            self.divergences
+                .push(Divergence::Supply { primary, shadow });
+        }
+
+        let mut touched: Vec<_> = self.touched.drain().collect();
+        touched.sort_unstable();
+        for address in touched {
+            let (primary, shadow) = (
+                self.primary.balance_of(&address),
+                self.shadow.balance_of(&address),
+            );
+            if primary != shadow {
+                self.divergences.push(Divergence::Balance {
+                    address,
+                    primary,
+                    shadow,
+                });
+            }
+        }
+
+        let mut pairs: Vec<_> = self.touched_allowances.drain().collect();
+        pairs.sort_unstable();
+        for (owner, spender) in pairs {
+            let (primary, shadow) = (
+                self.primary.allowance(&owner, &spender),
+                self.shadow.allowance(&owner, &spender),
+            );
+            if primary != shadow {
+                self.divergences.push(Divergence::Allowance {
+                    owner,
+                    spender,
+                    primary,
+                    shadow,
+                });
+            }
+        }
+    }
+
+    // 양쪽 결과를 비교해 기록하고, 호출자에게는 primary 결과만 돌려줌
+    fn record(
+        &mut self,
+        op: impl FnOnce() -> String,
+        primary: Result<(), TokenError>,
+        shadow: Result<(), TokenError>,
+    ) -> Result<(), TokenError> {
+        if primary != shadow {
+            self.divergences.push(Divergence::Result {
+                op: op(),
+                primary: primary.clone(),
+                shadow,
+            });
+        }
+        self.writes += 1;
+        if self.compare_every > 0 && self.writes.is_multiple_of(self.compare_every) {
+            self.compare();
+        }
+        primary
+    }
+}
+
+impl<P: FungibleToken, S: FungibleToken> FungibleToken for DualWrite<P, S> {
+    fn total_supply(&self) -> Balance {
+        self.primary.total_supply()
+    }
+
+    fn balance_of(&self, address: &Address) -> Balance {
+        self.primary.balance_of(address)
+    }
+
+    fn transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        let primary = self.primary.transfer(from, to, amount);
+        let shadow = self.shadow.transfer(from, to, amount);
+        self.touched.extend([from.clone(), to.clone()]);
+        self.record(
+            || format!("transfer {from} -> {to} {amount}"),
+            primary,
+            shadow,
+        )
+    }
+
+    fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        let primary = self.primary.approve(owner, spender, amount);
+        let shadow = self.shadow.approve(owner, spender, amount);
+        self.touched_allowances
+            .insert((owner.clone(), spender.clone()));
+        self.record(
+            || format!("approve {owner} -> {spender} {amount}"),
+            primary,
+            shadow,
+        )
+    }
+
+    fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        self.primary.allowance(owner, spender)
+    }
+
+    fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        let primary = self.primary.transfer_from(spender, from, to, amount);
+        let shadow = self.shadow.transfer_from(spender, from, to, amount);
+        self.touched.extend([from.clone(), to.clone()]);
+        self.touched_allowances
+            .insert((from.clone(), spender.clone()));
+        self.record(
+            || format!("transfer_from {spender}: {from} -> {to} {amount}"),
+            primary,
+            shadow,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenState;
+    use crate::chaos::{Chaos, ChaosConfig};
+
+    #[test]
+    fn test_identical_backends_never_diverge() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut mirror = DualWrite::new(
+            TokenState::new(alice.clone(), 1000),
+            TokenState::new(alice.clone(), 1000),
+            1,
+        );
+
+        mirror.transfer(&alice, &bob, 100).unwrap();
+        mirror.approve(&bob, &alice, 50).unwrap();
+        mirror.transfer_from(&alice, &bob, &alice, 20).unwrap();
+        let rejected = mirror.transfer(&bob, &alice, 1_000);
+
+        assert!(rejected.is_err());
+        assert_eq!(mirror.divergences(), []);
+        let (primary, shadow) = mirror.into_parts();
+        assert_eq!(primary.state_hash(), shadow.state_hash());
+    }
+
+    #[test]
+    fn test_faulty_shadow_is_reported_not_surfaced() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let faulty = Chaos::new(
+            TokenState::new(alice.clone(), 1000),
+            ChaosConfig {
+                fail_rate: 1.0,
+                ..ChaosConfig::default()
+            },
+            1,
+        );
+        let mut mirror = DualWrite::new(TokenState::new(alice.clone(), 1000), faulty, 0);
+
+        mirror.transfer(&alice, &bob, 100).unwrap();
+        mirror.compare();
+
+        assert_eq!(mirror.balance_of(&bob), 100);
+        assert_eq!(
+            mirror.take_divergences(),
+            vec![
+                Divergence::Result {
+                    op: "transfer alice -> bob 100".into(),
+                    primary: Ok(()),
+                    shadow: Err(TokenError::InjectedFault),
+                },
+                Divergence::Balance {
+                    address: alice.clone(),
+                    primary: 900,
+                    shadow: 1000,
+                },
+                Divergence::Balance {
+                    address: bob.clone(),
+                    primary: 100,
+                    shadow: 0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Many fungible token classes in one ledger, ERC-1155 style.
+//!
+//! A [`MultiTokenState`] keeps a balance per `(address, token id)` and a
+//! supply per id, so a game can model hundreds of item classes without a
+//! [`TokenState`](crate::TokenState) for each. Ids need no registration:
+//! minting to a fresh id creates the class. Only the owner mints; holders
+//! burn and transfer their own tokens.
+//!
+//! [`MultiTokenState::batch_transfer`] moves several ids between the same
+//! two accounts at once and is all-or-nothing: every leg is checked,
+//! repeated ids counted together, before any balance changes.
+//!
+//! The rules follow the strict defaults of a [`TokenConfig`](crate::TokenConfig):
+//! zero amounts and transfers to oneself are rejected. Operations here are
+//! not [`Operation`](crate::Operation)s and are not logged, journaled or
+//! snapshotted.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::{Address, Balance, TokenError};
+
+/// Identifies a token class in a [`MultiTokenState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenId(pub u64);
+
+impl fmt::Display for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "token #{}", self.0)
+    }
+}
+
+/// Balances and supplies of many token classes.
+pub struct MultiTokenState {
+    owner: Address,
+    balances: HashMap<(Address, TokenId), Balance>,
+    // 공급량이 0이 되면 항목 제거
+    supplies: BTreeMap<TokenId, Balance>,
+}
+
+impl MultiTokenState {
+    /// An empty ledger whose classes `owner` may mint.
+    pub fn new(owner: Address) -> Self {
+        Self {
+            owner,
+            balances: HashMap::new(),
+            supplies: BTreeMap::new(),
+        }
+    }
+
+    pub fn owner(&self) -> &Address {
+        &self.owner
+    }
+
+    pub fn balance_of(&self, address: &Address, id: TokenId) -> Balance {
+        self.balances
+            .get(&(address.clone(), id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// One balance per `(address, id)` query, in order.
+    pub fn balance_of_batch(&self, queries: &[(Address, TokenId)]) -> Vec<Balance> {
+        queries
+            .iter()
+            .map(|(address, id)| self.balance_of(address, *id))
+            .collect()
+    }
+
+    pub fn total_supply(&self, id: TokenId) -> Balance {
+        self.supplies.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Ids with tokens in circulation, in ascending order.
+    pub fn token_ids(&self) -> impl Iterator<Item = TokenId> + '_ {
+        self.supplies.keys().copied()
+    }
+
+    /// Creates `amount` of class `id` for `to`; only the owner may.
+    pub fn mint(
+        &mut self,
+        caller: &Address,
+        to: &Address,
+        id: TokenId,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        if caller != &self.owner {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        let supply = self
+            .total_supply(id)
+            .checked_add(amount)
+            .ok_or(TokenError::SupplyOverflow)?;
+        // 잔액 <= 공급량이므로 공급량 검사를 통과하면 overflow 불가능
+        let to_bal = self.balance_of(to, id) + amount;
+
+        self.supplies.insert(id, supply);
+        self.write_balance(to, id, to_bal);
+        Ok(())
+    }
+
+    /// Destroys `amount` of `from`'s tokens of class `id`.
+    pub fn burn(&mut self, from: &Address, id: TokenId, amount: Balance) -> Result<(), TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+        let from_bal = self.check_balance(from, id, amount)?;
+
+        self.write_balance(from, id, from_bal - amount);
+        let supply = self.total_supply(id) - amount;
+        if supply == 0 {
+            self.supplies.remove(&id);
+        } else {
+            self.supplies.insert(id, supply);
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` of class `id` from `from` to `to`.
+    pub fn transfer(
+        &mut self,
+        id: TokenId,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.batch_transfer(from, to, &[(id, amount)])
+    }
+
+    /// Moves every `(id, amount)` leg from `from` to `to`, or none of them.
+    pub fn batch_transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        legs: &[(TokenId, Balance)],
+    ) -> Result<(), TokenError> {
+        if from == to {
+            return Err(TokenError::SelfTransfer);
+        }
+        // 같은 id가 여러 번 나오면 합산해서 한 번에 검사
+        let mut totals: BTreeMap<TokenId, Balance> = BTreeMap::new();
+        for &(id, amount) in legs {
+            if amount == 0 {
+                return Err(TokenError::ZeroAmount);
+            }
+            let total = totals.entry(id).or_default();
+            *total = total
+                .checked_add(amount)
+                .ok_or(TokenError::BalanceOverFlow)?;
+        }
+        for (&id, &total) in &totals {
+            self.check_balance(from, id, total)?;
+        }
+
+        // 잔액 합 <= 공급량이므로 수신 측 overflow 불가능
+        for (id, total) in totals {
+            let from_bal = self.balance_of(from, id);
+            let to_bal = self.balance_of(to, id);
+            self.write_balance(from, id, from_bal - total);
+            self.write_balance(to, id, to_bal + total);
+        }
+        Ok(())
+    }
+
+    fn check_balance(
+        &self,
+        from: &Address,
+        id: TokenId,
+        amount: Balance,
+    ) -> Result<Balance, TokenError> {
+        let available = self.balance_of(from, id);
+        if available < amount {
+            return Err(TokenError::InsufficientBalance {
+                required: amount,
+                available,
+            });
+        }
+        Ok(available)
+    }
+
+    fn write_balance(&mut self, address: &Address, id: TokenId, balance: Balance) {
+        let key = (address.clone(), id);
+        if balance == 0 {
+            self.balances.remove(&key);
+        } else {
+            self.balances.insert(key, balance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::addresses;
+
+    const SWORD: TokenId = TokenId(1);
+    const POTION: TokenId = TokenId(2);
+
+    fn setup() -> (MultiTokenState, [Address; 2]) {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut items = MultiTokenState::new(alice.clone());
+        items.mint(&alice, &alice, SWORD, 10).unwrap();
+        items.mint(&alice, &alice, POTION, 500).unwrap();
+        (items, [alice, bob])
+    }
+
+    #[test]
+    fn test_classes_are_tracked_separately() {
+        let (mut items, [alice, bob]) = setup();
+
+        items.transfer(SWORD, &alice, &bob, 3).unwrap();
+        items.burn(&alice, POTION, 500).unwrap();
+        let not_owner = items.mint(&bob, &bob, SWORD, 1);
+
+        assert_eq!(
+            items.balance_of_batch(&[
+                (alice.clone(), SWORD),
+                (bob.clone(), SWORD),
+                (alice, POTION)
+            ]),
+            [7, 3, 0]
+        );
+        assert_eq!(items.total_supply(SWORD), 10);
+        assert_eq!(items.token_ids().collect::<Vec<_>>(), [SWORD]);
+        assert_eq!(not_owner, Err(TokenError::Unauthorized { caller: bob }));
+    }
+
+    #[test]
+    fn test_batch_transfer_moves_every_leg() {
+        let (mut items, [alice, bob]) = setup();
+
+        items
+            .batch_transfer(&alice, &bob, &[(SWORD, 2), (POTION, 100), (SWORD, 3)])
+            .unwrap();
+
+        assert_eq!(items.balance_of(&bob, SWORD), 5);
+        assert_eq!(items.balance_of(&bob, POTION), 100);
+        assert_eq!(items.balance_of(&alice, POTION), 400);
+    }
+
+    #[test]
+    fn test_failed_batch_changes_nothing() {
+        let (mut items, [alice, bob]) = setup();
+
+        let result = items.batch_transfer(&alice, &bob, &[(POTION, 100), (SWORD, 6), (SWORD, 5)]);
+
+        assert_eq!(
+            result,
+            Err(TokenError::InsufficientBalance {
+                required: 11,
+                available: 10
+            })
+        );
+        assert_eq!(items.balance_of(&alice, POTION), 500);
+        assert_eq!(items.balance_of(&bob, POTION), 0);
+    }
+}
@@ -0,0 +1,395 @@
+//! Multilateral netting of obligations across ledgers.
+//!
+//! A clearing run collects many gross [`Obligation`]s — `from` owes `to`
+//! some amount on one ledger of a [`TenantRegistry`] — and most of them
+//! cancel out. [`net`] reduces them to each party's net position per
+//! ledger and pairs net payers with net receivers, producing at most one
+//! transfer fewer than the number of parties with a non-zero position.
+//!
+//! [`settle`] then applies those net transfers as one unit: every ledger
+//! involved is opened under an undo log (see the `undo` module), each
+//! transfer runs through [`TenantRegistry::execute`] with the ledger's
+//! quota, interceptors and pause and freeze checks, and if any transfer
+//! fails every ledger is rolled back and no event sink hears anything.
+//! Rate-limit windows are not undone, as with interceptor state in a
+//! rolled-back transaction.
+//!
+//! Netting is deterministic: positions and transfers come out ordered by
+//! ledger id, then address.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::operation::{Operation, Receipt};
+use crate::tenancy::{TenantError, TenantRegistry};
+use crate::undo::UndoMark;
+use crate::{Address, Balance};
+
+/// `from` owes `to` `amount` on `ledger`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Obligation {
+    /// Tenant id of the ledger the obligation is denominated in
+    pub ledger: String,
+    pub from: Address,
+    pub to: Address,
+    pub amount: Balance,
+}
+
+/// One party's gross flows on one ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetPosition {
+    pub ledger: String,
+    pub address: Address,
+    /// Sum of obligations the party owes
+    pub owes: Balance,
+    /// Sum of obligations owed to the party
+    pub owed: Balance,
+}
+
+impl NetPosition {
+    /// Amount the party pays on settlement, 0 for a net receiver.
+    pub fn pays(&self) -> Balance {
+        self.owes.saturating_sub(self.owed)
+    }
+
+    /// Amount the party receives on settlement, 0 for a net payer.
+    pub fn receives(&self) -> Balance {
+        self.owed.saturating_sub(self.owes)
+    }
+}
+
+/// A transfer that settles part of the net positions on one ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetTransfer {
+    pub ledger: String,
+    pub from: Address,
+    pub to: Address,
+    pub amount: Balance,
+}
+
+/// The outcome of netting, and of settling when it came from [`settle`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SettlementReport {
+    /// Number of obligations submitted
+    pub gross_count: usize,
+    /// Sum of submitted amounts, per ledger
+    pub gross_volume: BTreeMap<String, Balance>,
+    /// Every party with an obligation, flat or not
+    pub positions: Vec<NetPosition>,
+    /// The transfers that settle the positions, in execution order
+    pub transfers: Vec<NetTransfer>,
+    /// One receipt per entry of `transfers`; empty until settled
+    pub receipts: Vec<Receipt>,
+}
+
+impl SettlementReport {
+    /// Sum of net transfer amounts on `ledger`.
+    pub fn net_volume(&self, ledger: &str) -> Balance {
+        self.transfers
+            .iter()
+            .filter(|t| t.ledger == ledger)
+            .fold(0, |acc: Balance, t| acc.saturating_add(t.amount))
+    }
+}
+
+/// Why netting or settlement failed.
+#[derive(Debug, PartialEq)]
+pub enum SettlementError {
+    /// An obligation names a ledger the registry does not hold
+    UnknownLedger(String),
+    /// A party's gross flows on `ledger` overflow `Balance`
+    Overflow { ledger: String },
+    /// A net transfer was rejected, so no ledger was changed
+    TransferFailed {
+        transfer: NetTransfer,
+        error: Box<TenantError>,
+    },
+}
+
+impl fmt::Display for SettlementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLedger(id) => write!(f, "unknown ledger {id:?}"),
+            Self::Overflow { ledger } => write!(f, "gross flows overflow on ledger {ledger:?}"),
+            Self::TransferFailed { transfer, error } => write!(
+                f,
+                "net transfer of {} from {} to {} on {:?} failed: {error}",
+                transfer.amount, transfer.from, transfer.to, transfer.ledger
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettlementError {}
+
+/// Computes net positions and the transfers that settle them.
+///
+/// Self-obligations and zero amounts are ignored. Nothing is applied.
+pub fn net(obligations: &[Obligation]) -> Result<SettlementReport, SettlementError> {
+    let overflow = |ledger: &str| SettlementError::Overflow {
+        ledger: ledger.to_string(),
+    };
+    let mut gross_volume: BTreeMap<String, Balance> = BTreeMap::new();
+    // (ledger, address) → (owes, owed)
+    let mut flows: BTreeMap<(&str, &Address), (Balance, Balance)> = BTreeMap::new();
+    for o in obligations {
+        if o.amount == 0 || o.from == o.to {
+            continue;
+        }
+        let volume = gross_volume.entry(o.ledger.clone()).or_default();
+        *volume = volume
+            .checked_add(o.amount)
+            .ok_or_else(|| overflow(&o.ledger))?;
+        let owes = &mut flows.entry((&o.ledger, &o.from)).or_default().0;
+        *owes = owes
+            .checked_add(o.amount)
+            .ok_or_else(|| overflow(&o.ledger))?;
+        let owed = &mut flows.entry((&o.ledger, &o.to)).or_default().1;
+        *owed = owed
+            .checked_add(o.amount)
+            .ok_or_else(|| overflow(&o.ledger))?;
+    }
+
+    let positions: Vec<NetPosition> = flows
+        .into_iter()
+        .map(|((ledger, address), (owes, owed))| NetPosition {
+            ledger: ledger.to_string(),
+            address: address.clone(),
+            owes,
+            owed,
+        })
+        .collect();
+
+    // 원장별로 지급자와 수취자를 주소 순서대로 짝지음
+    let mut transfers = Vec::new();
+    for ledger in gross_volume.keys() {
+        let on_ledger = || positions.iter().filter(move |p| &p.ledger == ledger);
+        let mut payers = on_ledger().filter(|p| p.pays() > 0).map(|p| (p, p.pays()));
+        let mut payees = on_ledger()
+            .filter(|p| p.receives() > 0)
+            .map(|p| (p, p.receives()));
+        let (mut payer, mut payee) = (payers.next(), payees.next());
+        while let (Some((from, left_to_pay)), Some((to, left_to_receive))) =
+            (&mut payer, &mut payee)
+        {
+            let amount = (*left_to_pay).min(*left_to_receive);
+            transfers.push(NetTransfer {
+                ledger: ledger.clone(),
+                from: from.address.clone(),
+                to: to.address.clone(),
+                amount,
+            });
+            *left_to_pay -= amount;
+            *left_to_receive -= amount;
+            if *left_to_pay == 0 {
+                payer = payers.next();
+            }
+            if *left_to_receive == 0 {
+                payee = payees.next();
+            }
+        }
+    }
+
+    Ok(SettlementReport {
+        gross_count: obligations.len(),
+        gross_volume,
+        positions,
+        transfers,
+        receipts: Vec::new(),
+    })
+}
+
+/// Nets `obligations` and applies the net transfers atomically.
+///
+/// On failure every ledger is left as it was.
+pub fn settle(
+    registry: &mut TenantRegistry,
+    obligations: &[Obligation],
+) -> Result<SettlementReport, SettlementError> {
+    let mut report = net(obligations)?;
+    let ledgers: Vec<String> = report.gross_volume.keys().cloned().collect();
+    if let Some(missing) = ledgers.iter().find(|id| registry.get(id).is_none()) {
+        return Err(SettlementError::UnknownLedger(missing.clone()));
+    }
+
+    let mut open = OpenLedgers {
+        registry,
+        marks: Vec::with_capacity(ledgers.len()),
+    };
+    for id in &ledgers {
+        if let Some(ledger) = open.registry.get_mut(id) {
+            open.marks.push((id.clone(), ledger.mark_undo()));
+        }
+    }
+
+    for transfer in &report.transfers {
+        let result = open.registry.execute(
+            &transfer.ledger,
+            Operation::Transfer {
+                from: transfer.from.clone(),
+                to: transfer.to.clone(),
+                amount: transfer.amount,
+            },
+        );
+        match result {
+            Ok(receipt) => report.receipts.push(receipt),
+            Err(error) => {
+                open.close(false);
+                return Err(SettlementError::TransferFailed {
+                    transfer: transfer.clone(),
+                    error: Box::new(error),
+                });
+            }
+        }
+    }
+
+    open.close(true);
+    Ok(report)
+}
+
+// 정산 중인 원장들의 undo 표시: 패닉으로 빠져나가도 drop이 모두 되돌림
+struct OpenLedgers<'a> {
+    registry: &'a mut TenantRegistry,
+    marks: Vec<(String, UndoMark)>,
+}
+
+impl OpenLedgers<'_> {
+    fn close(&mut self, keep: bool) {
+        for (id, mark) in self.marks.drain(..) {
+            let Some(ledger) = self.registry.get_mut(&id) else {
+                continue;
+            };
+            if keep {
+                // 위에서 연 표시이므로 체크포인트는 항상 살아 있음
+                let _ = ledger.commit_undo(mark);
+            } else {
+                ledger.rollback_undo(mark);
+            }
+        }
+    }
+}
+
+impl Drop for OpenLedgers<'_> {
+    fn drop(&mut self) {
+        self.close(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenState;
+    use crate::tenancy::TenantRegistry;
+    use crate::test_util::{addresses, record_events};
+
+    fn owe(ledger: &str, from: &Address, to: &Address, amount: Balance) -> Obligation {
+        Obligation {
+            ledger: ledger.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        }
+    }
+
+    fn registry(holders: &[Address]) -> TenantRegistry {
+        let mut registry = TenantRegistry::default();
+        for id in ["usd", "eur"] {
+            let mut ledger =
+                TokenState::from_balances(holders.iter().map(|a| (a.clone(), 100))).unwrap();
+            ledger.drain_events();
+            registry.create(id, ledger).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn test_cycle_nets_to_single_transfer() {
+        let [a, b, c] = addresses(["a", "b", "c"]);
+        let obligations = [
+            owe("usd", &a, &b, 50),
+            owe("usd", &b, &c, 50),
+            owe("usd", &c, &a, 30),
+            owe("usd", &a, &a, 999),
+        ];
+
+        let report = net(&obligations).unwrap();
+
+        assert_eq!(report.gross_count, 4);
+        assert_eq!(report.gross_volume["usd"], 130);
+        assert_eq!(
+            report.transfers,
+            vec![NetTransfer {
+                ledger: "usd".into(),
+                from: a.clone(),
+                to: c.clone(),
+                amount: 20,
+            }]
+        );
+        assert_eq!(report.net_volume("usd"), 20);
+        assert_eq!(
+            report
+                .positions
+                .iter()
+                .filter(|p| p.pays() == 0 && p.receives() == 0)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_settle_applies_net_positions_per_ledger() {
+        let [a, b, c] = addresses(["a", "b", "c"]);
+        let mut registry = registry(&[a.clone(), b.clone(), c.clone()]);
+        let obligations = [
+            owe("usd", &a, &b, 80),
+            owe("usd", &b, &a, 30),
+            owe("usd", &c, &b, 10),
+            owe("eur", &b, &c, 40),
+            owe("eur", &c, &b, 40),
+        ];
+
+        let report = settle(&mut registry, &obligations).unwrap();
+
+        let usd = registry.get("usd").unwrap();
+        assert_eq!(usd.balance_of(&a), 50);
+        assert_eq!(usd.balance_of(&b), 160);
+        assert_eq!(usd.balance_of(&c), 90);
+        assert_eq!(registry.get("eur").unwrap().balance_of(&b), 100);
+        assert_eq!(report.transfers.len(), 2);
+        assert_eq!(report.receipts.len(), 2);
+        assert_eq!(report.net_volume("eur"), 0);
+    }
+
+    #[test]
+    fn test_failed_transfer_rolls_back_every_ledger() {
+        let [a, b, c] = addresses(["a", "b", "c"]);
+        let mut registry = registry(&[a.clone(), b.clone(), c.clone()]);
+        let heard = record_events(registry.get_mut("eur").unwrap());
+        let obligations = [owe("eur", &a, &b, 60), owe("usd", &c, &a, 500)];
+
+        let result = settle(&mut registry, &obligations);
+
+        assert_eq!(
+            result,
+            Err(SettlementError::TransferFailed {
+                transfer: NetTransfer {
+                    ledger: "usd".into(),
+                    from: c.clone(),
+                    to: a.clone(),
+                    amount: 500,
+                },
+                error: Box::new(TenantError::Token(crate::TokenError::InsufficientBalance {
+                    required: 500,
+                    available: 100
+                })),
+            })
+        );
+        assert_eq!(registry.get("eur").unwrap().balance_of(&b), 100);
+        assert!(registry.get("eur").unwrap().events().is_empty());
+        assert!(heard.borrow().is_empty());
+        assert_eq!(
+            settle(&mut registry, &[owe("yen", &a, &b, 1)]),
+            Err(SettlementError::UnknownLedger("yen".into()))
+        );
+    }
+}
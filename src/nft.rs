@@ -0,0 +1,280 @@
+//! Non-fungible tokens, ERC-721 style.
+//!
+//! An [`NftState`] records one owner per [`NftId`]. Only the ledger's
+//! minter creates tokens and sets their metadata URI. A token moves when
+//! its owner sends it, or when someone the owner authorized does: the
+//! account approved for that one token with [`NftState::approve`], or an
+//! operator approved for all the owner's tokens with
+//! [`NftState::set_approval_for_all`]. Moving or burning a token clears its
+//! single-token approval; operator approvals stay until withdrawn.
+//!
+//! As with fungible tokens, transfers to oneself are rejected. Operations
+//! here are not [`Operation`](crate::Operation)s and are not logged,
+//! journaled or snapshotted.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::{Address, TokenError};
+
+/// Identifies a non-fungible token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NftId(pub u64);
+
+impl fmt::Display for NftId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nft #{}", self.0)
+    }
+}
+
+/// Ownership, approvals and metadata of a set of non-fungible tokens.
+pub struct NftState {
+    minter: Address,
+    owners: BTreeMap<NftId, Address>,
+    // 주소별 보유 개수, 0이 되면 항목 제거
+    balances: HashMap<Address, u64>,
+    approvals: HashMap<NftId, Address>,
+    // (소유자, operator)
+    operators: HashSet<(Address, Address)>,
+    uris: HashMap<NftId, String>,
+}
+
+impl NftState {
+    /// An empty collection that only `minter` may add to.
+    pub fn new(minter: Address) -> Self {
+        Self {
+            minter,
+            owners: BTreeMap::new(),
+            balances: HashMap::new(),
+            approvals: HashMap::new(),
+            operators: HashSet::new(),
+            uris: HashMap::new(),
+        }
+    }
+
+    pub fn minter(&self) -> &Address {
+        &self.minter
+    }
+
+    pub fn owner_of(&self, id: NftId) -> Option<&Address> {
+        self.owners.get(&id)
+    }
+
+    /// Number of tokens `owner` holds.
+    pub fn balance_of(&self, owner: &Address) -> u64 {
+        self.balances.get(owner).copied().unwrap_or(0)
+    }
+
+    /// Number of tokens in existence.
+    pub fn total_supply(&self) -> usize {
+        self.owners.len()
+    }
+
+    /// The account approved to move token `id`, if any.
+    pub fn get_approved(&self, id: NftId) -> Option<&Address> {
+        self.approvals.get(&id)
+    }
+
+    pub fn is_approved_for_all(&self, owner: &Address, operator: &Address) -> bool {
+        self.operators.contains(&(owner.clone(), operator.clone()))
+    }
+
+    pub fn token_uri(&self, id: NftId) -> Option<&str> {
+        self.uris.get(&id).map(String::as_str)
+    }
+
+    /// Creates token `id` for `to`; only the minter may.
+    pub fn mint(&mut self, caller: &Address, to: &Address, id: NftId) -> Result<(), TokenError> {
+        self.check_minter(caller)?;
+        if self.owners.contains_key(&id) {
+            return Err(TokenError::TokenAlreadyMinted);
+        }
+        self.owners.insert(id, to.clone());
+        *self.balances.entry(to.clone()).or_default() += 1;
+        Ok(())
+    }
+
+    /// Destroys token `id`; its owner or an authorized account may.
+    pub fn burn(&mut self, caller: &Address, id: NftId) -> Result<(), TokenError> {
+        let owner = self.check_authorized(caller, id)?;
+        self.owners.remove(&id);
+        self.approvals.remove(&id);
+        self.uris.remove(&id);
+        self.decrement(&owner);
+        Ok(())
+    }
+
+    /// Sends token `id` from its owner to `to`.
+    pub fn transfer(
+        &mut self,
+        caller: &Address,
+        to: &Address,
+        id: NftId,
+    ) -> Result<(), TokenError> {
+        let owner = self.check_authorized(caller, id)?;
+        if &owner == to {
+            return Err(TokenError::SelfTransfer);
+        }
+        self.approvals.remove(&id);
+        self.owners.insert(id, to.clone());
+        self.decrement(&owner);
+        *self.balances.entry(to.clone()).or_default() += 1;
+        Ok(())
+    }
+
+    /// Lets `spender` move token `id`, or withdraws the approval with
+    /// `None`; the owner or one of its operators may.
+    pub fn approve(
+        &mut self,
+        caller: &Address,
+        spender: Option<&Address>,
+        id: NftId,
+    ) -> Result<(), TokenError> {
+        let owner = self.owners.get(&id).ok_or(TokenError::UnknownToken)?;
+        if caller != owner && !self.is_approved_for_all(owner, caller) {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        match spender {
+            Some(spender) => self.approvals.insert(id, spender.clone()),
+            None => self.approvals.remove(&id),
+        };
+        Ok(())
+    }
+
+    /// Lets `operator` move every token `owner` holds now or later, or
+    /// stops it.
+    pub fn set_approval_for_all(
+        &mut self,
+        owner: &Address,
+        operator: &Address,
+        approved: bool,
+    ) -> Result<(), TokenError> {
+        if owner == operator {
+            return Err(TokenError::SelfApproval);
+        }
+        let key = (owner.clone(), operator.clone());
+        if approved {
+            self.operators.insert(key);
+        } else {
+            self.operators.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Sets token `id`'s metadata URI; only the minter may.
+    pub fn set_token_uri(
+        &mut self,
+        caller: &Address,
+        id: NftId,
+        uri: impl Into<String>,
+    ) -> Result<(), TokenError> {
+        self.check_minter(caller)?;
+        if !self.owners.contains_key(&id) {
+            return Err(TokenError::UnknownToken);
+        }
+        self.uris.insert(id, uri.into());
+        Ok(())
+    }
+
+    fn check_minter(&self, caller: &Address) -> Result<(), TokenError> {
+        if caller != &self.minter {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    // 소유자, 토큰 단위 승인, operator 중 하나인지 확인하고 소유자 반환
+    fn check_authorized(&self, caller: &Address, id: NftId) -> Result<Address, TokenError> {
+        let owner = self.owners.get(&id).ok_or(TokenError::UnknownToken)?;
+        let authorized = caller == owner
+            || self.approvals.get(&id) == Some(caller)
+            || self.is_approved_for_all(owner, caller);
+        if !authorized {
+            return Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            });
+        }
+        Ok(owner.clone())
+    }
+
+    fn decrement(&mut self, owner: &Address) {
+        let count = self.balances.get_mut(owner).expect("owner holds the token");
+        *count -= 1;
+        if *count == 0 {
+            self.balances.remove(owner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::addresses;
+
+    fn setup() -> (NftState, [Address; 3]) {
+        let [alice, bob, carol] = addresses(["alice", "bob", "carol"]);
+        let mut nfts = NftState::new(alice.clone());
+        nfts.mint(&alice, &bob, NftId(1)).unwrap();
+        nfts.mint(&alice, &bob, NftId(2)).unwrap();
+        (nfts, [alice, bob, carol])
+    }
+
+    #[test]
+    fn test_mint_transfer_and_metadata() {
+        let (mut nfts, [alice, bob, carol]) = setup();
+
+        nfts.set_token_uri(&alice, NftId(1), "ipfs://one").unwrap();
+        nfts.transfer(&bob, &carol, NftId(1)).unwrap();
+        let again = nfts.mint(&alice, &carol, NftId(2));
+        let stranger = nfts.set_token_uri(&bob, NftId(2), "ipfs://two");
+
+        assert_eq!(nfts.owner_of(NftId(1)), Some(&carol));
+        assert_eq!(nfts.balance_of(&bob), 1);
+        assert_eq!(nfts.balance_of(&carol), 1);
+        assert_eq!(nfts.token_uri(NftId(1)), Some("ipfs://one"));
+        assert_eq!(again, Err(TokenError::TokenAlreadyMinted));
+        assert_eq!(stranger, Err(TokenError::Unauthorized { caller: bob }));
+    }
+
+    #[test]
+    fn test_single_approval_is_spent_by_the_move() {
+        let (mut nfts, [alice, bob, carol]) = setup();
+
+        let before = nfts.transfer(&carol, &carol, NftId(1));
+        nfts.approve(&bob, Some(&carol), NftId(1)).unwrap();
+        nfts.transfer(&carol, &alice, NftId(1)).unwrap();
+        let other = nfts.transfer(&carol, &carol, NftId(2));
+
+        let denied = Err(TokenError::Unauthorized {
+            caller: carol.clone(),
+        });
+        assert_eq!(before, denied);
+        assert_eq!(other, denied);
+        assert_eq!(nfts.owner_of(NftId(1)), Some(&alice));
+        assert_eq!(nfts.get_approved(NftId(1)), None);
+    }
+
+    #[test]
+    fn test_operator_moves_and_burns_every_token() {
+        let (mut nfts, [alice, bob, carol]) = setup();
+        nfts.set_approval_for_all(&bob, &carol, true).unwrap();
+
+        nfts.transfer(&carol, &alice, NftId(1)).unwrap();
+        nfts.burn(&carol, NftId(2)).unwrap();
+        nfts.set_approval_for_all(&bob, &carol, false).unwrap();
+
+        assert_eq!(nfts.owner_of(NftId(2)), None);
+        assert_eq!(nfts.balance_of(&bob), 0);
+        assert_eq!(nfts.total_supply(), 1);
+        assert!(!nfts.is_approved_for_all(&bob, &carol));
+        assert_eq!(
+            nfts.transfer(&carol, &carol, NftId(2)),
+            Err(TokenError::UnknownToken)
+        );
+    }
+}
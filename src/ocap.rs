@@ -0,0 +1,341 @@
+//! Capability tokens: bearer authority instead of signatures.
+//!
+//! An account grants a [`Capability`] with [`TokenState::grant_capability`]:
+//! the right to move up to `limit` of the issuer's tokens, optionally only
+//! to a fixed set of recipients and only until an expiry read from the
+//! token's [`Clock`](crate::clock::Clock). Whoever holds the handle can
+//! spend it with [`TokenState::redeem_capability`]; no address or signature
+//! is checked. The issuer can withdraw it at any time with
+//! [`TokenState::revoke_capability`].
+//!
+//! A handle is unforgeable because it carries a 32-byte secret chosen by
+//! the issuer, which should come from a cryptographic random source. The
+//! ledger stores only the secret's SHA-256 digest, so snapshots and grant
+//! operations don't reveal it; a redemption does, as it has to present
+//! the handle. Grants, remaining limits and revocations are ledger state
+//! and are saved in snapshots.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::sha256::Sha256;
+use crate::{Address, Balance, Settlement, TokenError, TokenState};
+
+/// Identifies a capability grant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilityId(pub u64);
+
+impl fmt::Display for CapabilityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "capability #{}", self.0)
+    }
+}
+
+/// Bearer handle to a capability granted by [`TokenState::grant_capability`].
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capability {
+    pub id: CapabilityId,
+    pub secret: [u8; 32],
+}
+
+impl Capability {
+    fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.secret);
+        hasher.finish()
+    }
+}
+
+// 비밀값은 로그에 남기지 않음
+impl fmt::Debug for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Capability")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What a capability still allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapabilityGrant {
+    /// Account whose tokens the capability spends
+    pub issuer: Address,
+    /// Amount left to redeem
+    pub remaining: Balance,
+    /// Permitted recipients; empty means any
+    pub recipients: BTreeSet<Address>,
+    /// Last instant the capability can be redeemed, by the token's clock
+    pub expires_at: Option<u64>,
+    /// SHA-256 of the handle's secret
+    pub digest: [u8; 32],
+}
+
+impl TokenState {
+    /// Lets the bearer of the returned handle send up to `limit` of
+    /// `issuer`'s tokens to `recipients` (anyone, if empty) until
+    /// `expires_at`.
+    pub fn grant_capability(
+        &mut self,
+        issuer: &Address,
+        limit: Balance,
+        recipients: impl IntoIterator<Item = Address>,
+        expires_at: Option<u64>,
+        secret: [u8; 32],
+    ) -> Result<Capability, TokenError> {
+        let capability = Capability {
+            id: CapabilityId(self.next_capability_id),
+            secret,
+        };
+        self.execute(Operation::GrantCapability {
+            issuer: issuer.clone(),
+            limit,
+            recipients: recipients.into_iter().collect(),
+            expires_at,
+            digest: capability.digest(),
+        })?;
+        Ok(capability)
+    }
+
+    /// Sends `amount` of the issuer's tokens to `to` under `capability`.
+    ///
+    /// Fails with [`TokenError::InvalidCapability`] for an unknown, revoked
+    /// or forged handle. The issuer must not be frozen, and the transfer
+    /// follows `transfer_from`'s rules: no dust sweep.
+    pub fn redeem_capability(
+        &mut self,
+        capability: &Capability,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::RedeemCapability {
+            capability: capability.clone(),
+            to: to.clone(),
+            amount,
+        })
+        .map(|_| ())
+    }
+
+    /// Withdraws capability `id`; only its issuer may.
+    pub fn revoke_capability(
+        &mut self,
+        issuer: &Address,
+        id: CapabilityId,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::RevokeCapability {
+            issuer: issuer.clone(),
+            id,
+        })
+        .map(|_| ())
+    }
+
+    /// The live grant behind capability `id`, if any.
+    pub fn capability(&self, id: CapabilityId) -> Option<&CapabilityGrant> {
+        self.capabilities.get(&id)
+    }
+
+    pub(crate) fn apply_grant_capability(
+        &mut self,
+        issuer: &Address,
+        limit: Balance,
+        recipients: &BTreeSet<Address>,
+        expires_at: Option<u64>,
+        digest: [u8; 32],
+    ) {
+        let id = CapabilityId(self.next_capability_id);
+        self.journal_capability(id);
+        self.capabilities.insert(
+            id,
+            CapabilityGrant {
+                issuer: issuer.clone(),
+                remaining: limit,
+                recipients: recipients.clone(),
+                expires_at,
+                digest,
+            },
+        );
+        self.next_capability_id += 1;
+        self.emit(TokenEvent::CapabilityGranted {
+            id,
+            issuer: issuer.clone(),
+            limit,
+        });
+    }
+
+    pub(crate) fn apply_redeem_capability(
+        &mut self,
+        capability: &Capability,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<Settlement, TokenError> {
+        let id = capability.id;
+        let grant = match self.capabilities.get(&id) {
+            Some(grant) if grant.digest == capability.digest() => grant,
+            _ => return Err(TokenError::InvalidCapability),
+        };
+        if let Some(expires_at) = grant.expires_at {
+            let now = self.now();
+            if now > expires_at {
+                return Err(TokenError::CapabilityExpired { expires_at, now });
+            }
+        }
+        if !grant.recipients.is_empty() && !grant.recipients.contains(to) {
+            return Err(TokenError::RecipientNotPermitted { to: to.clone() });
+        }
+        if grant.remaining < amount {
+            return Err(TokenError::CapabilityExhausted {
+                required: amount,
+                available: grant.remaining,
+            });
+        }
+        let issuer = grant.issuer.clone();
+        if self.frozen.contains(&issuer) {
+            return Err(TokenError::AccountFrozen { address: issuer });
+        }
+
+        if self.check_edge_cases(&issuer, to, amount)? {
+            return Ok(Settlement::default());
+        }
+        let issuer_bal = self.check_spendable(&issuer, amount)?;
+        let settled = if &issuer != to {
+            self.settle(&issuer, to, issuer_bal, amount, false)?
+        } else {
+            Settlement::default()
+        };
+
+        self.journal_capability(id);
+        let grant = self.capabilities.get_mut(&id).unwrap();
+        grant.remaining -= amount;
+        let remaining = grant.remaining;
+        self.emit(TokenEvent::CapabilityRedeemed {
+            id,
+            to: to.clone(),
+            amount,
+            remaining,
+        });
+        Ok(settled)
+    }
+
+    pub(crate) fn apply_revoke_capability(
+        &mut self,
+        issuer: &Address,
+        id: CapabilityId,
+    ) -> Result<(), TokenError> {
+        match self.capabilities.get(&id) {
+            Some(grant) if &grant.issuer == issuer => {}
+            Some(_) => {
+                return Err(TokenError::Unauthorized {
+                    caller: issuer.clone(),
+                });
+            }
+            None => return Err(TokenError::InvalidCapability),
+        }
+        self.journal_capability(id);
+        self.capabilities.remove(&id);
+        self.emit(TokenEvent::CapabilityRevoked {
+            id,
+            issuer: issuer.clone(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[test]
+    fn test_bearer_redeems_within_scope() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let cap = token
+            .grant_capability(&alice, 100, [bob.clone()], None, [1; 32])
+            .unwrap();
+
+        token.redeem_capability(&cap, &bob, 60).unwrap();
+        let over = token.redeem_capability(&cap, &bob, 50);
+        let elsewhere = token.redeem_capability(&cap, &carol, 10);
+
+        assert_eq!(token.balance_of(&bob), 60);
+        assert_eq!(token.capability(cap.id).unwrap().remaining, 40);
+        assert_eq!(
+            over,
+            Err(TokenError::CapabilityExhausted {
+                required: 50,
+                available: 40
+            })
+        );
+        assert_eq!(
+            elsewhere,
+            Err(TokenError::RecipientNotPermitted { to: carol })
+        );
+    }
+
+    #[test]
+    fn test_forged_expired_and_revoked_handles_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_clock(Box::new(FixedClock(10)));
+        let cap = token
+            .grant_capability(&alice, 100, [], Some(20), [1; 32])
+            .unwrap();
+        let forged = Capability {
+            id: cap.id,
+            secret: [2; 32],
+        };
+
+        let bad_secret = token.redeem_capability(&forged, &bob, 1);
+        let stranger = token.revoke_capability(&bob, cap.id);
+        token.set_clock(Box::new(FixedClock(21)));
+        let late = token.redeem_capability(&cap, &bob, 1);
+        token.revoke_capability(&alice, cap.id).unwrap();
+        token.set_clock(Box::new(FixedClock(10)));
+        let revoked = token.redeem_capability(&cap, &bob, 1);
+
+        assert_eq!(bad_secret, Err(TokenError::InvalidCapability));
+        assert_eq!(
+            stranger,
+            Err(TokenError::Unauthorized {
+                caller: bob.clone()
+            })
+        );
+        assert_eq!(
+            late,
+            Err(TokenError::CapabilityExpired {
+                expires_at: 20,
+                now: 21
+            })
+        );
+        assert_eq!(revoked, Err(TokenError::InvalidCapability));
+        assert_eq!(token.balance_of(&bob), 0);
+    }
+
+    #[test]
+    fn test_capabilities_survive_snapshot() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let cap = token
+            .grant_capability(&alice, 100, [], None, [1; 32])
+            .unwrap();
+        token.redeem_capability(&cap, &bob, 30).unwrap();
+
+        let mut restored = TokenState::from_bytes(&token.to_bytes()).unwrap();
+        restored.redeem_capability(&cap, &bob, 70).unwrap();
+        let next = restored
+            .grant_capability(&alice, 1, [], None, [3; 32])
+            .unwrap();
+
+        assert_eq!(restored.balance_of(&bob), 100);
+        assert_eq!(restored.capability(cap.id).unwrap().remaining, 0);
+        assert_eq!(next.id, CapabilityId(cap.id.0 + 1));
+    }
+}
@@ -0,0 +1,640 @@
+//! Typed operations and the single `execute` entry point.
+//!
+//! Every state change is expressible as an [`Operation`], and
+//! [`TokenState::execute`] is the one place they are applied. The familiar
+//! methods (`transfer`, `approve`, ...) are thin wrappers that build an
+//! operation and execute it, so interceptors and anything else layered on
+//! `execute` see all mutations uniformly.
+
+use std::collections::BTreeSet;
+
+use crate::config::ConfigUpdateId;
+use crate::escrow::EscrowId;
+use crate::ocap::{Capability, CapabilityId};
+use crate::shielded::Note;
+use crate::{
+    Address, Balance, FeeDestination, FeePolicy, Role, Settlement, TokenConfig, TokenError,
+    TokenState,
+};
+
+/// A state-changing request against [`TokenState`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    Transfer {
+        from: Address,
+        to: Address,
+        amount: Balance,
+    },
+    /// A transfer authorized by a signature, checked against `from`'s nonce
+    NoncedTransfer {
+        from: Address,
+        to: Address,
+        amount: Balance,
+        nonce: u64,
+    },
+    Approve {
+        owner: Address,
+        spender: Address,
+        amount: Balance,
+    },
+    /// An approval authorized by the owner's signature
+    Permit {
+        owner: Address,
+        spender: Address,
+        amount: Balance,
+        nonce: u64,
+    },
+    /// An approval that lapses after `expires_at` by the token's clock
+    ApproveUntil {
+        owner: Address,
+        spender: Address,
+        amount: Balance,
+        expires_at: u64,
+    },
+    ApproveIf {
+        owner: Address,
+        spender: Address,
+        expected_current: Balance,
+        new_amount: Balance,
+    },
+    IncreaseAllowance {
+        owner: Address,
+        spender: Address,
+        added: Balance,
+    },
+    DecreaseAllowance {
+        owner: Address,
+        spender: Address,
+        subtracted: Balance,
+    },
+    /// Deletes the allowance rather than setting it to 0
+    RevokeAllowance {
+        owner: Address,
+        spender: Address,
+    },
+    RevokeAll {
+        owner: Address,
+    },
+    TransferFrom {
+        spender: Address,
+        from: Address,
+        to: Address,
+        amount: Balance,
+    },
+    /// Creates a capability whose secret hashes to `digest`
+    GrantCapability {
+        issuer: Address,
+        limit: Balance,
+        recipients: BTreeSet<Address>,
+        expires_at: Option<u64>,
+        digest: [u8; 32],
+    },
+    RedeemCapability {
+        capability: Capability,
+        to: Address,
+        amount: Balance,
+    },
+    RevokeCapability {
+        issuer: Address,
+        id: CapabilityId,
+    },
+    Shield {
+        from: Address,
+        amount: Balance,
+    },
+    Unshield {
+        to: Address,
+        note: Note,
+        proof: Vec<u8>,
+    },
+    BulkCredit {
+        caller: Address,
+        entries: Vec<(Address, Balance)>,
+    },
+    Mint {
+        caller: Address,
+        to: Address,
+        amount: Balance,
+    },
+    Burn {
+        from: Address,
+        amount: Balance,
+    },
+    /// Several `TransferFrom`s by one spender, applied all-or-nothing
+    TransferFromBatch {
+        spender: Address,
+        legs: Vec<(Address, Address, Balance)>,
+    },
+    BurnFrom {
+        spender: Address,
+        from: Address,
+        amount: Balance,
+    },
+    /// Holds part of `owner`'s balance until `unlock_at` by the token's clock
+    Lock {
+        owner: Address,
+        amount: Balance,
+        unlock_at: u64,
+    },
+    Unlock {
+        owner: Address,
+    },
+    EscrowCreate {
+        payer: Address,
+        payee: Address,
+        amount: Balance,
+    },
+    /// Pays an escrow out to its payee, by the payer
+    EscrowRelease {
+        caller: Address,
+        id: EscrowId,
+    },
+    /// Returns an escrow to its payer, by the payee
+    EscrowRefund {
+        caller: Address,
+        id: EscrowId,
+    },
+    MarkNonCirculating {
+        caller: Address,
+        address: Address,
+    },
+    UnmarkNonCirculating {
+        caller: Address,
+        address: Address,
+    },
+    /// Replaces the configuration at once; refused under an update delay
+    UpdateConfig {
+        caller: Address,
+        config: TokenConfig,
+    },
+    QueueConfigUpdate {
+        caller: Address,
+        config: TokenConfig,
+    },
+    ExecuteConfigUpdate {
+        caller: Address,
+        id: ConfigUpdateId,
+    },
+    CancelConfigUpdate {
+        caller: Address,
+        id: ConfigUpdateId,
+    },
+    Pause {
+        caller: Address,
+    },
+    Unpause {
+        caller: Address,
+    },
+    TransferOwnership {
+        caller: Address,
+        new_owner: Address,
+    },
+    AcceptOwnership {
+        caller: Address,
+    },
+    RenounceOwnership {
+        caller: Address,
+    },
+    FreezeAccount {
+        caller: Address,
+        address: Address,
+    },
+    UnfreezeAccount {
+        caller: Address,
+        address: Address,
+    },
+    GrantRole {
+        caller: Address,
+        role: Role,
+        account: Address,
+    },
+    RevokeRole {
+        caller: Address,
+        role: Role,
+        account: Address,
+    },
+}
+
+/// What a successfully executed operation did.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// Tokens that actually changed hands or were credited.
+    ///
+    /// May exceed the requested amount when a dust sweep applies, and is 0
+    /// for approvals, admin operations, and no-op transfers.
+    pub moved: Balance,
+    /// Tokens credited to the recipients of a transfer, after fees.
+    ///
+    /// 0 for other operations, no-op transfers, and transfers into a
+    /// burn address.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub credited: Balance,
+    /// The note created by a `Shield` operation
+    pub note: Option<Note>,
+    /// Position of this operation in the token's history.
+    ///
+    /// Assigned consecutively from 0 to successful operations only, so a
+    /// consumer that sees a jump has missed a receipt.
+    pub seq: u64,
+}
+
+impl From<Settlement> for Receipt {
+    fn from(settled: Settlement) -> Self {
+        Receipt {
+            moved: settled.moved,
+            credited: settled.credited,
+            ..Receipt::default()
+        }
+    }
+}
+
+impl Operation {
+    // 잔액이 줄어들 수 있는 주소 (실패할 연산도 보수적으로 포함)
+    pub(crate) fn senders(&self) -> BTreeSet<&Address> {
+        match self {
+            Operation::Transfer { from, .. }
+            | Operation::NoncedTransfer { from, .. }
+            | Operation::TransferFrom { from, .. }
+            | Operation::Shield { from, .. }
+            | Operation::EscrowCreate { payer: from, .. }
+            | Operation::Burn { from, .. }
+            | Operation::BurnFrom { from, .. } => [from].into(),
+            Operation::TransferFromBatch { legs, .. } => legs.iter().map(|(f, _, _)| f).collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    // 잔액이 늘어나거나 새로 생길 수 있는 주소 (에스크로 정산은 원장에서 수령인 조회)
+    pub(crate) fn recipients<'a>(&'a self, token: &'a TokenState) -> BTreeSet<&'a Address> {
+        // 수수료가 붙는 전송은 treasury에도 입금될 수 있음
+        let treasury = match (&token.config.fee, &token.config.fee_destination) {
+            (FeePolicy::None, _) | (_, FeeDestination::Burn) => None,
+            (_, FeeDestination::Treasury(treasury)) => Some(treasury),
+        };
+        match self {
+            Operation::Transfer { to, .. }
+            | Operation::NoncedTransfer { to, .. }
+            | Operation::TransferFrom { to, .. }
+            | Operation::RedeemCapability { to, .. } => [to].into_iter().chain(treasury).collect(),
+            Operation::Unshield { to, .. } | Operation::Mint { to, .. } => [to].into(),
+            Operation::BulkCredit { entries, .. } => entries.iter().map(|(a, _)| a).collect(),
+            Operation::TransferFromBatch { legs, .. } => {
+                legs.iter().map(|(_, t, _)| t).chain(treasury).collect()
+            }
+            Operation::EscrowRelease { id, .. } => {
+                token.escrow(*id).map(|e| &e.payee).into_iter().collect()
+            }
+            Operation::EscrowRefund { id, .. } => {
+                token.escrow(*id).map(|e| &e.payer).into_iter().collect()
+            }
+            _ => BTreeSet::new(),
+        }
+    }
+}
+
+impl TokenState {
+    /// Applies `op`, running registered interceptors around it.
+    pub fn execute(&mut self, op: Operation) -> Result<Receipt, TokenError> {
+        self.intercept(&op, |s| {
+            let mut receipt = s.dispatch(&op)?;
+            receipt.seq = s.next_seq;
+            s.next_seq += 1;
+            Ok(receipt)
+        })
+    }
+
+    /// Sequence number the next successful operation will receive.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    fn dispatch(&mut self, op: &Operation) -> Result<Receipt, TokenError> {
+        self.check_not_paused(op)?;
+        self.check_not_frozen(op)?;
+        self.check_transferable(op)?;
+        let moved = match op {
+            Operation::Transfer { from, to, amount } => {
+                return self.apply_transfer(from, to, *amount).map(Receipt::from);
+            }
+            Operation::NoncedTransfer {
+                from,
+                to,
+                amount,
+                nonce,
+            } => {
+                return self
+                    .apply_nonced_transfer(from, to, *amount, *nonce)
+                    .map(Receipt::from);
+            }
+            Operation::Approve {
+                owner,
+                spender,
+                amount,
+            } => {
+                self.apply_approve(owner, spender, *amount)?;
+                0
+            }
+            Operation::Permit {
+                owner,
+                spender,
+                amount,
+                nonce,
+            } => {
+                self.apply_permit(owner, spender, *amount, *nonce)?;
+                0
+            }
+            Operation::ApproveUntil {
+                owner,
+                spender,
+                amount,
+                expires_at,
+            } => {
+                self.apply_approve_until(owner, spender, *amount, *expires_at)?;
+                0
+            }
+            Operation::ApproveIf {
+                owner,
+                spender,
+                expected_current,
+                new_amount,
+            } => {
+                self.apply_approve_if(owner, spender, *expected_current, *new_amount)?;
+                0
+            }
+            Operation::IncreaseAllowance {
+                owner,
+                spender,
+                added,
+            } => {
+                self.apply_increase_allowance(owner, spender, *added)?;
+                0
+            }
+            Operation::DecreaseAllowance {
+                owner,
+                spender,
+                subtracted,
+            } => {
+                self.apply_decrease_allowance(owner, spender, *subtracted)?;
+                0
+            }
+            Operation::RevokeAllowance { owner, spender } => {
+                self.apply_revoke_allowance(owner, spender);
+                0
+            }
+            Operation::RevokeAll { owner } => {
+                self.apply_revoke_all(owner);
+                0
+            }
+            Operation::TransferFrom {
+                spender,
+                from,
+                to,
+                amount,
+            } => {
+                return self
+                    .apply_transfer_from(spender, from, to, *amount)
+                    .map(Receipt::from);
+            }
+            Operation::TransferFromBatch { spender, legs } => {
+                return self
+                    .apply_transfer_from_batch(spender, legs)
+                    .map(Receipt::from);
+            }
+            Operation::GrantCapability {
+                issuer,
+                limit,
+                recipients,
+                expires_at,
+                digest,
+            } => {
+                self.apply_grant_capability(issuer, *limit, recipients, *expires_at, *digest);
+                0
+            }
+            Operation::RedeemCapability {
+                capability,
+                to,
+                amount,
+            } => {
+                return self
+                    .apply_redeem_capability(capability, to, *amount)
+                    .map(Receipt::from);
+            }
+            Operation::RevokeCapability { issuer, id } => {
+                self.apply_revoke_capability(issuer, *id)?;
+                0
+            }
+            Operation::Shield { from, amount } => {
+                let note = self.apply_shield(from, *amount)?;
+                return Ok(Receipt {
+                    moved: *amount,
+                    note: Some(note),
+                    ..Receipt::default()
+                });
+            }
+            Operation::Unshield { to, note, proof } => self.apply_unshield(to, note, proof)?,
+            Operation::BulkCredit { caller, entries } => self.apply_bulk_credit(caller, entries)?,
+            Operation::Mint { caller, to, amount } => self.apply_mint(caller, to, *amount)?,
+            Operation::Burn { from, amount } => self.apply_burn(from, *amount)?,
+            Operation::BurnFrom {
+                spender,
+                from,
+                amount,
+            } => self.apply_burn_from(spender, from, *amount)?,
+            Operation::Lock {
+                owner,
+                amount,
+                unlock_at,
+            } => {
+                self.apply_lock(owner, *amount, *unlock_at)?;
+                0
+            }
+            Operation::Unlock { owner } => {
+                self.apply_unlock(owner)?;
+                0
+            }
+            Operation::EscrowCreate {
+                payer,
+                payee,
+                amount,
+            } => self.apply_escrow_create(payer, payee, *amount)?,
+            Operation::EscrowRelease { caller, id } => {
+                self.apply_escrow_settle(caller, *id, true)?
+            }
+            Operation::EscrowRefund { caller, id } => {
+                self.apply_escrow_settle(caller, *id, false)?
+            }
+            Operation::MarkNonCirculating { caller, address } => {
+                self.apply_mark_non_circulating(caller, address)?;
+                0
+            }
+            Operation::UnmarkNonCirculating { caller, address } => {
+                self.apply_unmark_non_circulating(caller, address)?;
+                0
+            }
+            Operation::UpdateConfig { caller, config } => {
+                self.apply_update_config(caller, config)?;
+                0
+            }
+            Operation::QueueConfigUpdate { caller, config } => {
+                self.apply_queue_config_update(caller, config)?;
+                0
+            }
+            Operation::ExecuteConfigUpdate { caller, id } => {
+                self.apply_execute_config_update(caller, *id)?;
+                0
+            }
+            Operation::CancelConfigUpdate { caller, id } => {
+                self.apply_cancel_config_update(caller, *id)?;
+                0
+            }
+            Operation::Pause { caller } => {
+                self.apply_set_paused(caller, true)?;
+                0
+            }
+            Operation::Unpause { caller } => {
+                self.apply_set_paused(caller, false)?;
+                0
+            }
+            Operation::TransferOwnership { caller, new_owner } => {
+                self.apply_transfer_ownership(caller, new_owner)?;
+                0
+            }
+            Operation::AcceptOwnership { caller } => {
+                self.apply_accept_ownership(caller)?;
+                0
+            }
+            Operation::RenounceOwnership { caller } => {
+                self.apply_renounce_ownership(caller)?;
+                0
+            }
+            Operation::FreezeAccount { caller, address } => {
+                self.apply_set_frozen(caller, address, true)?;
+                0
+            }
+            Operation::UnfreezeAccount { caller, address } => {
+                self.apply_set_frozen(caller, address, false)?;
+                0
+            }
+            Operation::GrantRole {
+                caller,
+                role,
+                account,
+            } => {
+                self.apply_set_role(caller, *role, account, true)?;
+                0
+            }
+            Operation::RevokeRole {
+                caller,
+                role,
+                account,
+            } => {
+                self.apply_set_role(caller, *role, account, false)?;
+                0
+            }
+        };
+        Ok(Receipt {
+            moved,
+            ..Receipt::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute_transfer() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let receipt = token
+            .execute(Operation::Transfer {
+                from: alice.clone(),
+                to: bob.clone(),
+                amount: 100,
+            })
+            .unwrap();
+
+        assert_eq!(receipt.moved, 100);
+        assert_eq!(token.balance_of(&bob), 100);
+    }
+
+    #[test]
+    fn test_execute_returns_error_unchanged() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let result = token.execute(Operation::Approve {
+            owner: alice.clone(),
+            spender: alice.clone(),
+            amount: 1,
+        });
+
+        assert_eq!(result.unwrap_err(), TokenError::SelfApproval);
+    }
+
+    #[test]
+    fn test_execute_shield_receipt_carries_note() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let receipt = token
+            .execute(Operation::Shield {
+                from: alice.clone(),
+                amount: 40,
+            })
+            .unwrap();
+
+        assert_eq!(receipt.moved, 40);
+        assert_eq!(receipt.note.map(|n| n.amount), Some(40));
+    }
+
+    #[test]
+    fn test_execute_reports_swept_amount() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = crate::TokenConfig {
+            existential_deposit: 10,
+            dust_policy: crate::DustPolicy::Sweep,
+            ..crate::TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 100, config);
+
+        let receipt = token
+            .execute(Operation::Transfer {
+                from: alice.clone(),
+                to: bob.clone(),
+                amount: 95,
+            })
+            .unwrap();
+
+        assert_eq!(receipt.moved, 100);
+    }
+
+    #[test]
+    fn test_sequence_numbers_skip_failed_operations() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let first = token.execute(Operation::Transfer {
+            from: alice.clone(),
+            to: bob.clone(),
+            amount: 10,
+        });
+        token.transfer(&alice, &bob, 5000).unwrap_err();
+        let second = token.execute(Operation::Approve {
+            owner: alice.clone(),
+            spender: bob.clone(),
+            amount: 10,
+        });
+
+        assert_eq!(first.unwrap().seq, 0);
+        assert_eq!(second.unwrap().seq, 1);
+        assert_eq!(token.next_seq(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+//! Signature-based approvals in the style of ERC-2612.
+//!
+//! [`TokenState::permit`] sets an allowance on the strength of the owner's
+//! off-ledger signature, so the owner never has to submit an approval
+//! themselves. The signed bytes, [`TokenState::permit_message`], bind the
+//! ledger's domain separator, the owner's current nonce and a deadline
+//! read against the token's [`Clock`](crate::clock::Clock). Permits and
+//! signed transfers draw on the same per-account nonce and the same domain
+//! separator (see the `tx` module), so each signature is usable once and
+//! only on the ledger it was made for; a ledger built without a separator
+//! accepts no permits.
+
+use crate::operation::Operation;
+use crate::tx::SignatureVerifier;
+use crate::{Address, Balance, TokenError, TokenState};
+
+impl TokenState {
+    /// Bytes `owner` signs to permit `spender` to spend `amount` until
+    /// `deadline`, using the owner's current nonce.
+    ///
+    /// Fails with [`TokenError::NoDomainSeparator`] on a ledger built
+    /// without a separator.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn permit_message(
+        &self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+        deadline: u64,
+    ) -> Result<Vec<u8>, TokenError> {
+        let separator = self.domain_separator.ok_or(TokenError::NoDomainSeparator)?;
+        let mut message = b"TKPM".to_vec();
+        message.extend_from_slice(&separator);
+        for address in [owner, spender] {
+            message.extend_from_slice(&(address.as_str().len() as u32).to_le_bytes());
+            message.extend_from_slice(address.as_str().as_bytes());
+        }
+        message.extend_from_slice(&(amount as u128).to_le_bytes());
+        message.extend_from_slice(&self.nonce(owner).to_le_bytes());
+        message.extend_from_slice(&deadline.to_le_bytes());
+        Ok(message)
+    }
+
+    /// Sets `owner`'s allowance to `spender` if `verifier` accepts
+    /// `signature` over [`TokenState::permit_message`] and `deadline` has
+    /// not passed.
+    ///
+    /// Fails with [`TokenError::PermitExpired`],
+    /// [`TokenError::NoDomainSeparator`] or [`TokenError::InvalidSignature`]
+    /// without using up the nonce.
+    pub fn permit(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+        deadline: u64,
+        signature: &[u8],
+        verifier: &dyn SignatureVerifier,
+    ) -> Result<(), TokenError> {
+        let now = self.now();
+        if now > deadline {
+            return Err(TokenError::PermitExpired { deadline, now });
+        }
+        let message = self.permit_message(owner, spender, amount, deadline)?;
+        if !verifier.verify(owner, &message, signature) {
+            return Err(TokenError::InvalidSignature);
+        }
+        self.execute(Operation::Permit {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount,
+            nonce: self.nonce(owner),
+        })
+        .map(|_| ())
+    }
+
+    pub(crate) fn apply_permit(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+        nonce: u64,
+    ) -> Result<(), TokenError> {
+        self.check_nonce(owner, nonce)?;
+        self.apply_approve(owner, spender, amount)?;
+        self.bump_nonce(owner);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::test_util::Reversed;
+
+    fn sign(message: Result<Vec<u8>, TokenError>) -> Vec<u8> {
+        message.unwrap().into_iter().rev().collect()
+    }
+
+    #[test]
+    fn test_permit_sets_allowance_once() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000).with_domain_separator([1; 32]);
+        token.set_clock(Box::new(FixedClock(100)));
+        let signature = sign(token.permit_message(&alice, &bob, 50, 200));
+
+        token
+            .permit(&alice, &bob, 50, 200, &signature, &Reversed)
+            .unwrap();
+        let replay = token.permit(&alice, &bob, 50, 200, &signature, &Reversed);
+
+        assert_eq!(token.allowance(&alice, &bob), 50);
+        assert_eq!(token.nonce(&alice), 1);
+        assert_eq!(replay, Err(TokenError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_expired_or_foreign_permit_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000).with_domain_separator([1; 32]);
+        token.set_clock(Box::new(FixedClock(300)));
+        let late = sign(token.permit_message(&alice, &bob, 50, 200));
+        let other = TokenState::new(alice.clone(), 1000).with_domain_separator([7; 32]);
+        let foreign = sign(other.permit_message(&alice, &bob, 50, 400));
+
+        let expired = token.permit(&alice, &bob, 50, 200, &late, &Reversed);
+        let wrong_domain = token.permit(&alice, &bob, 50, 400, &foreign, &Reversed);
+
+        assert_eq!(
+            expired,
+            Err(TokenError::PermitExpired {
+                deadline: 200,
+                now: 300
+            })
+        );
+        assert_eq!(wrong_domain, Err(TokenError::InvalidSignature));
+        assert_eq!(token.allowance(&alice, &bob), 0);
+        assert_eq!(token.nonce(&alice), 0);
+    }
+
+    #[test]
+    fn test_permit_needs_domain_separator() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_clock(Box::new(FixedClock(100)));
+
+        let message = token.permit_message(&alice, &bob, 50, 200);
+        let result = token.permit(&alice, &bob, 50, 200, &[], &Reversed);
+
+        assert_eq!(message, Err(TokenError::NoDomainSeparator));
+        assert_eq!(result, Err(TokenError::NoDomainSeparator));
+        assert_eq!(token.allowance(&alice, &bob), 0);
+    }
+}
@@ -0,0 +1,185 @@
+//! Safe transfers to contract-style recipients.
+//!
+//! Some recipients are programs that must agree to take tokens, as ERC-721
+//! and ERC-1155 contracts do. Such a recipient implements [`TokenReceiver`]
+//! and is listed under its address in a [`ReceiverRegistry`] kept by the
+//! host. [`TokenState::safe_transfer`] performs an ordinary `transfer` and
+//! then asks the recipient's receiver whether to keep it; if the receiver
+//! refuses, the transfer is rolled back and fails with
+//! [`TokenError::ReceiverRejected`]. Addresses without a registered receiver
+//! are plain accounts and always accept.
+//!
+//! The receiver is told the amount actually credited, as the transfer's
+//! [`Receipt::credited`](crate::Receipt::credited) reports it: the amount
+//! debited less any transfer fee, even when the recipient is also the fee
+//! treasury, and 0 for a transfer that turned out to be a no-op. Event
+//! sinks hear about the transfer only once the receiver accepts it.
+
+use std::collections::HashMap;
+
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError, TokenState};
+
+pub trait TokenReceiver {
+    /// Called once `amount` from `from` is credited; `false` rejects it.
+    fn on_token_received(&self, from: &Address, amount: Balance) -> bool;
+}
+
+/// Receivers of contract-style recipients, by address.
+#[derive(Default)]
+pub struct ReceiverRegistry {
+    receivers: HashMap<Address, Box<dyn TokenReceiver>>,
+}
+
+impl ReceiverRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes `receiver` decide what `address` accepts, replacing any
+    /// receiver registered for it before.
+    pub fn register(&mut self, address: Address, receiver: Box<dyn TokenReceiver>) {
+        self.receivers.insert(address, receiver);
+    }
+
+    pub fn unregister(&mut self, address: &Address) {
+        self.receivers.remove(address);
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&dyn TokenReceiver> {
+        self.receivers.get(address).map(Box::as_ref)
+    }
+}
+
+impl TokenState {
+    /// Transfers `amount` and keeps it only if `to`'s receiver accepts.
+    pub fn safe_transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+        receivers: &ReceiverRegistry,
+    ) -> Result<(), TokenError> {
+        let mut undo = self.begin_undo();
+        let transfer = Operation::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        };
+        let result = undo
+            .execute(transfer)
+            .and_then(|receipt| match receivers.get(to) {
+                Some(receiver) if !receiver.on_token_received(from, receipt.credited) => {
+                    Err(TokenError::ReceiverRejected { to: to.clone() })
+                }
+                _ => Ok(()),
+            });
+        match result {
+            Ok(()) => undo.commit(),
+            Err(error) => {
+                undo.rollback();
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{addresses, record_events};
+    use crate::{FeeDestination, FeePolicy, TokenConfig};
+
+    /// Accepts deposits up to a limit.
+    struct Vault(Balance);
+
+    impl TokenReceiver for Vault {
+        fn on_token_received(&self, _from: &Address, amount: Balance) -> bool {
+            amount <= self.0
+        }
+    }
+
+    #[test]
+    fn test_rejected_transfer_rolls_back() {
+        let [alice, vault] = addresses(["alice", "vault"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let events = record_events(&mut token);
+        let mut receivers = ReceiverRegistry::new();
+        receivers.register(vault.clone(), Box::new(Vault(100)));
+
+        let rejected = token.safe_transfer(&alice, &vault, 150, &receivers);
+        token
+            .safe_transfer(&alice, &vault, 100, &receivers)
+            .unwrap();
+
+        assert_eq!(
+            rejected,
+            Err(TokenError::ReceiverRejected { to: vault.clone() })
+        );
+        assert_eq!(token.balance_of(&alice), 900);
+        assert_eq!(token.balance_of(&vault), 100);
+        assert_eq!(events.borrow().len(), 1);
+        assert_eq!(token.events().len(), 1);
+    }
+
+    #[test]
+    fn test_treasury_recipient_told_amount_net_of_fee() {
+        let [alice, treasury] = addresses(["alice", "treasury"]);
+        let config = TokenConfig {
+            fee: FeePolicy::BasisPoints(1_000),
+            fee_destination: FeeDestination::Treasury(treasury.clone()),
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        let mut receivers = ReceiverRegistry::new();
+        receivers.register(treasury.clone(), Box::new(Vault(90)));
+
+        token
+            .safe_transfer(&alice, &treasury, 100, &receivers)
+            .unwrap();
+
+        assert_eq!(token.balance_of(&treasury), 100);
+    }
+
+    #[test]
+    fn test_noop_transfer_under_flat_fee_credits_nothing() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let config = TokenConfig {
+            fee: FeePolicy::Flat(5),
+            ..TokenConfig::erc20_compatible()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        let mut receivers = ReceiverRegistry::new();
+        // 0만 받아들이는 receiver: 알림 금액이 0인지 확인
+        receivers.register(bob.clone(), Box::new(Vault(0)));
+
+        token.safe_transfer(&alice, &bob, 0, &receivers).unwrap();
+        token
+            .safe_transfer(&alice, &alice, 10, &ReceiverRegistry::new())
+            .unwrap();
+
+        assert_eq!(token.balance_of(&alice), 1000);
+        assert_eq!(token.balance_of(&bob), 0);
+    }
+
+    #[test]
+    fn test_unregistered_recipients_accept() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let mut receivers = ReceiverRegistry::new();
+        receivers.register(bob.clone(), Box::new(Vault(0)));
+        receivers.unregister(&bob);
+
+        token.safe_transfer(&alice, &bob, 300, &receivers).unwrap();
+        let short = token.safe_transfer(&alice, &bob, 800, &receivers);
+
+        assert_eq!(token.balance_of(&bob), 300);
+        assert_eq!(
+            short,
+            Err(TokenError::InsufficientBalance {
+                required: 800,
+                available: 700
+            })
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Withdrawing spending power.
+//!
+//! [`TokenState::revoke_allowance`] deletes an allowance outright, usage
+//! counters included, rather than setting it to zero, and logs
+//! [`TokenEvent::AllowanceRevoked`] so indexers can tell a revocation from
+//! an approval of 0. [`TokenState::revoke_all`] does the same for every
+//! spender of an owner at once, and also withdraws every capability the
+//! owner issued. Revoking is allowed while the token is paused, since it
+//! can only reduce who may move funds.
+//!
+//! Delegated spending comes in two kinds: allowances, held by an address,
+//! and capabilities (see the `ocap` module), held by whoever has the
+//! handle. [`TokenState::spenders`] lists both, so it answers "who can move
+//! my funds right now" in full.
+
+use crate::events::TokenEvent;
+use crate::ocap::CapabilityId;
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError, TokenState};
+
+/// Something that can spend an owner's tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Spender {
+    /// An address holding an allowance
+    Allowance(Address),
+    /// The bearer of a capability, known only by its id
+    Capability(CapabilityId),
+}
+
+impl TokenState {
+    /// Deletes `owner`'s allowance to `spender`; a no-op if there is none.
+    pub fn revoke_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::RevokeAllowance {
+            owner: owner.clone(),
+            spender: spender.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// Deletes every allowance `owner` has granted and every capability it
+    /// has issued.
+    pub fn revoke_all(&mut self, owner: &Address) -> Result<(), TokenError> {
+        self.execute(Operation::RevokeAll {
+            owner: owner.clone(),
+        })
+        .map(|_| ())
+    }
+
+    /// Everything that can currently spend from `owner`, with how much:
+    /// allowances in address order, then unexpired capabilities by id.
+    pub fn spenders(&self, owner: &Address) -> Vec<(Spender, Balance)> {
+        let now = self.now();
+        let allowances = self
+            .allowances
+            .keys()
+            .filter(|(o, _)| o == owner)
+            .map(|(_, spender)| {
+                let amount = self.allowance(owner, spender);
+                (Spender::Allowance(spender.clone()), amount)
+            });
+        let capabilities = self
+            .capabilities
+            .iter()
+            .filter(|(_, grant)| &grant.issuer == owner)
+            .filter(|(_, grant)| grant.expires_at.is_none_or(|t| now <= t))
+            .map(|(id, grant)| (Spender::Capability(*id), grant.remaining));
+        let mut spenders: Vec<_> = allowances
+            .chain(capabilities)
+            .filter(|(_, amount)| *amount > 0)
+            .collect();
+        spenders.sort_unstable();
+        spenders
+    }
+
+    pub(crate) fn apply_revoke_allowance(&mut self, owner: &Address, spender: &Address) {
+        self.journal_allowance(owner, spender);
+        let key = (owner.clone(), spender.clone());
+        self.allowance_usage.remove(&key);
+        if let Some(previous) = self.allowances.remove(&key) {
+            self.emit(TokenEvent::AllowanceRevoked {
+                owner: owner.clone(),
+                spender: spender.clone(),
+                previous,
+            });
+        }
+    }
+
+    pub(crate) fn apply_revoke_all(&mut self, owner: &Address) {
+        // 이벤트 순서를 고정하기 위해 정렬
+        let mut spenders: Vec<_> = self
+            .allowances
+            .keys()
+            .chain(self.allowance_usage.keys())
+            .filter(|(o, _)| o == owner)
+            .map(|(_, spender)| spender.clone())
+            .collect();
+        spenders.sort_unstable();
+        spenders.dedup();
+        for spender in spenders {
+            self.apply_revoke_allowance(owner, &spender);
+        }
+
+        let mut issued: Vec<_> = self
+            .capabilities
+            .iter()
+            .filter(|(_, grant)| &grant.issuer == owner)
+            .map(|(id, _)| *id)
+            .collect();
+        issued.sort_unstable();
+        for id in issued {
+            // 발행자 본인이므로 실패하지 않음
+            let _ = self.apply_revoke_capability(owner, id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[test]
+    fn test_revoke_deletes_and_logs() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 50).unwrap();
+
+        token.revoke_allowance(&alice, &bob).unwrap();
+        let events_after = token.events().len();
+        token.revoke_allowance(&alice, &bob).unwrap();
+
+        assert_eq!(token.allowance_usage(&alice, &bob), (0, 0, 0));
+        assert_eq!(
+            token.events().last().map(|(_, e)| e),
+            Some(&TokenEvent::AllowanceRevoked {
+                owner: alice.clone(),
+                spender: bob.clone(),
+                previous: 50
+            })
+        );
+        assert_eq!(token.events().len(), events_after);
+    }
+
+    #[test]
+    fn test_revoke_all_clears_spenders() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&alice, &carol, 20).unwrap();
+        token.approve(&alice, &bob, 10).unwrap();
+        token.approve(&bob, &carol, 5).unwrap();
+        token.pause(&alice).unwrap();
+
+        let before = token.spenders(&alice);
+        token.revoke_all(&alice).unwrap();
+
+        assert_eq!(
+            before,
+            [
+                (Spender::Allowance(bob.clone()), 10),
+                (Spender::Allowance(carol.clone()), 20)
+            ]
+        );
+        assert_eq!(token.spenders(&alice), []);
+        assert_eq!(token.spenders(&bob), [(Spender::Allowance(carol), 5)]);
+    }
+
+    #[test]
+    fn test_capabilities_count_as_spenders() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000).with_clock(Box::new(FixedClock(10)));
+        token.approve(&alice, &bob, 10).unwrap();
+        let open = token
+            .grant_capability(&alice, 100, [], None, [1; 32])
+            .unwrap();
+        let expired = token
+            .grant_capability(&alice, 50, [], Some(5), [2; 32])
+            .unwrap();
+        token.grant_capability(&bob, 70, [], None, [3; 32]).unwrap();
+
+        let before = token.spenders(&alice);
+        token.revoke_all(&alice).unwrap();
+
+        assert_eq!(
+            before,
+            [
+                (Spender::Allowance(bob.clone()), 10),
+                (Spender::Capability(open.id), 100)
+            ]
+        );
+        assert_eq!(token.spenders(&alice), []);
+        assert_eq!(token.capability(open.id), None);
+        assert_eq!(token.capability(expired.id), None);
+        assert_eq!(token.spenders(&bob).len(), 1);
+        assert_eq!(
+            token.redeem_capability(&open, &bob, 1),
+            Err(TokenError::InvalidCapability)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Role-based access control for permissioned deployments.
+//!
+//! Each privileged operation requires a [`Role`]: minting and bulk credits
+//! need [`Role::Minter`], pausing and unpausing [`Role::Pauser`], freezing
+//! accounts [`Role::Freezer`], and granting or revoking roles and changing
+//! the configuration [`Role::Admin`]. The token owner holds every role
+//! implicitly, so a token with no grants behaves as an owner-only token;
+//! ownership itself still moves only through
+//! [`TokenState::transfer_ownership`].
+//!
+//! A caller without the role fails with [`TokenError::Unauthorized`].
+//! Burning one's own tokens is a holder operation and needs no role.
+
+use std::collections::HashSet;
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, TokenError, TokenState};
+
+/// A privilege that can be granted to an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// May mint new tokens
+    Minter,
+    /// May pause and unpause the token
+    Pauser,
+    /// May freeze and unfreeze accounts
+    Freezer,
+    /// May grant and revoke roles and change the configuration
+    Admin,
+}
+
+impl TokenState {
+    /// Whether `account` holds `role`, directly or as the owner.
+    pub fn has_role(&self, role: Role, account: &Address) -> bool {
+        self.owner.as_ref() == Some(account) || self.roles.contains(&(account.clone(), role))
+    }
+
+    /// Every explicit grant, unordered. The owner's implicit roles are not
+    /// listed.
+    pub fn role_grants(&self) -> &HashSet<(Address, Role)> {
+        &self.roles
+    }
+
+    pub fn grant_role(
+        &mut self,
+        caller: &Address,
+        role: Role,
+        account: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::GrantRole {
+            caller: caller.clone(),
+            role,
+            account: account.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub fn revoke_role(
+        &mut self,
+        caller: &Address,
+        role: Role,
+        account: &Address,
+    ) -> Result<(), TokenError> {
+        self.execute(Operation::RevokeRole {
+            caller: caller.clone(),
+            role,
+            account: account.clone(),
+        })
+        .map(|_| ())
+    }
+
+    pub(crate) fn apply_set_role(
+        &mut self,
+        caller: &Address,
+        role: Role,
+        account: &Address,
+        granted: bool,
+    ) -> Result<(), TokenError> {
+        self.check_role(caller, Role::Admin)?;
+        let key = (account.clone(), role);
+        // 이미 같은 상태면 이벤트 없이 성공 (멱등)
+        let changed = if granted {
+            self.roles.insert(key)
+        } else {
+            self.roles.remove(&key)
+        };
+        if changed {
+            let (account, by) = (account.clone(), caller.clone());
+            self.emit(if granted {
+                TokenEvent::RoleGranted { role, account, by }
+            } else {
+                TokenEvent::RoleRevoked { role, account, by }
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_role(&self, caller: &Address, role: Role) -> Result<(), TokenError> {
+        if self.has_role(role, caller) {
+            Ok(())
+        } else {
+            Err(TokenError::Unauthorized {
+                caller: caller.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_granted_role_authorizes_operation() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let denied = token.mint(&bob, &bob, 10);
+        token.grant_role(&alice, Role::Minter, &bob).unwrap();
+        token.mint(&bob, &bob, 10).unwrap();
+
+        assert_eq!(
+            denied,
+            Err(TokenError::Unauthorized {
+                caller: bob.clone()
+            })
+        );
+        assert!(token.has_role(Role::Minter, &bob));
+        assert!(!token.has_role(Role::Pauser, &bob));
+        assert!(token.pause(&bob).is_err());
+        assert_eq!(token.balance_of(&bob), 10);
+    }
+
+    #[test]
+    fn test_revoke_and_admin_delegation() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.grant_role(&alice, Role::Admin, &bob).unwrap();
+
+        token.grant_role(&bob, Role::Pauser, &carol).unwrap();
+        token.pause(&carol).unwrap();
+        token.revoke_role(&bob, Role::Pauser, &carol).unwrap();
+
+        assert!(token.unpause(&carol).is_err());
+        assert!(token.grant_role(&carol, Role::Admin, &carol).is_err());
+        assert_eq!(
+            token.events().last().unwrap().1,
+            TokenEvent::RoleRevoked {
+                role: Role::Pauser,
+                account: carol.clone(),
+                by: bob.clone()
+            }
+        );
+    }
+
+    #[test]
+    fn test_ownerless_token_has_no_privileges() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::from_balances([(alice.clone(), 10)]).unwrap();
+
+        assert!(token.mint(&alice, &alice, 1).is_err());
+        assert!(token.grant_role(&alice, Role::Minter, &alice).is_err());
+        assert!(!token.has_role(Role::Admin, &alice));
+    }
+}
@@ -0,0 +1,601 @@
+//! Transfer policies written in [rhai](https://rhai.rs), loaded at runtime.
+//!
+//! A [`ScriptPolicy`] is an [`Interceptor`] whose `before` hook is a rhai
+//! function, so compliance rules can change without rebuilding the
+//! service. The script must define `check(op)`, where `op` is a map with a
+//! `kind` string (the [`Operation`] variant name) plus that variant's
+//! fields. Addresses are strings; amounts are integers, saturating at
+//! `i64::MAX`. Capability secrets and digests are withheld: a redemption
+//! shows only the capability's `id`, and an absent expiry is left out.
+//!
+//! `check` returns `true` (or nothing) to allow the operation, `false` to
+//! reject it, or a string to reject it with that reason:
+//!
+//! ```text
+//! fn check(op) {
+//!     if op.kind == "Transfer" && op.amount > 1_000_000 {
+//!         return "transfers above 1M need manual review";
+//!     }
+//!     true
+//! }
+//! ```
+//!
+//! Scripts are sandboxed: rhai has no file, network or process access, and
+//! `eval`, `print` and `debug` are disabled here. They are also metered:
+//! each call is limited to [`ScriptPolicy::DEFAULT_MAX_OPERATIONS`] rhai
+//! operations (see [`ScriptPolicy::with_max_operations`]), along with caps
+//! on recursion, string, array and map sizes. A script that errors or runs
+//! out of budget rejects the operation rather than letting it through.
+//!
+//! Once the policy is registered, a [`ScriptHandle`] from
+//! [`ScriptPolicy::handle`] swaps in a new script. The replacement is
+//! compiled and checked first; on error the running script stays in place.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+use rhai::{AST, Dynamic, Engine, INT, Map, Scope};
+
+use crate::interceptor::Interceptor;
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError};
+
+/// A policy script that failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError(String);
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Interceptor that asks a rhai script whether each operation may run.
+pub struct ScriptPolicy {
+    engine: Engine,
+    ast: Rc<RefCell<AST>>,
+}
+
+impl ScriptPolicy {
+    /// Operation budget per `check` call unless overridden.
+    pub const DEFAULT_MAX_OPERATIONS: u64 = 100_000;
+
+    /// Compiles `source`, which must define `check(op)`.
+    pub fn new(source: &str) -> Result<Self, ScriptError> {
+        let engine = sandboxed_engine(Self::DEFAULT_MAX_OPERATIONS);
+        let ast = compile(&engine, source)?;
+        Ok(Self {
+            engine,
+            ast: Rc::new(RefCell::new(ast)),
+        })
+    }
+
+    /// Reads and compiles the script at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        Self::new(&read(path.as_ref())?)
+    }
+
+    /// A handle for replacing this policy's script after registration.
+    pub fn handle(&self) -> ScriptHandle {
+        ScriptHandle {
+            ast: self.ast.clone(),
+        }
+    }
+
+    /// Replaces the per-call operation budget.
+    pub fn with_max_operations(mut self, max: u64) -> Self {
+        self.engine.set_max_operations(max);
+        self
+    }
+}
+
+impl Interceptor for ScriptPolicy {
+    fn before(&mut self, op: &Operation) -> Result<(), TokenError> {
+        let verdict = self
+            .engine
+            .call_fn::<Dynamic>(
+                &mut Scope::new(),
+                &self.ast.borrow(),
+                "check",
+                (to_map(op),),
+            )
+            .map_err(|e| TokenError::PolicyRejected(format!("policy script failed: {e}")))?;
+
+        if verdict.is_unit() || verdict.as_bool() == Ok(true) {
+            Ok(())
+        } else if verdict.is_string() {
+            Err(TokenError::PolicyRejected(verdict.to_string()))
+        } else if verdict.as_bool() == Ok(false) {
+            Err(TokenError::PolicyRejected("rejected by policy".into()))
+        } else {
+            Err(TokenError::PolicyRejected(format!(
+                "policy script returned {}",
+                verdict.type_name()
+            )))
+        }
+    }
+}
+
+/// Replaces the script of a registered [`ScriptPolicy`].
+#[derive(Clone)]
+pub struct ScriptHandle {
+    ast: Rc<RefCell<AST>>,
+}
+
+impl ScriptHandle {
+    /// Compiles `source` and, if it is valid, makes it the active script.
+    pub fn reload(&self, source: &str) -> Result<(), ScriptError> {
+        let ast = compile(&sandboxed_engine(0), source)?;
+        *self.ast.borrow_mut() = ast;
+        Ok(())
+    }
+
+    /// [`ScriptHandle::reload`] from the file at `path`.
+    pub fn reload_file(&self, path: impl AsRef<Path>) -> Result<(), ScriptError> {
+        self.reload(&read(path.as_ref())?)
+    }
+}
+
+fn compile(engine: &Engine, source: &str) -> Result<AST, ScriptError> {
+    let ast = engine
+        .compile(source)
+        .map_err(|e| ScriptError(format!("compile error: {e}")))?;
+    let has_check = ast
+        .iter_functions()
+        .any(|f| f.name == "check" && f.params.len() == 1);
+    if !has_check {
+        return Err(ScriptError("script does not define check(op)".into()));
+    }
+    Ok(ast)
+}
+
+fn read(path: &Path) -> Result<String, ScriptError> {
+    std::fs::read_to_string(path).map_err(|e| ScriptError(format!("{}: {e}", path.display())))
+}
+
+fn sandboxed_engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(4_096);
+    engine.set_max_array_size(1_024);
+    engine.set_max_map_size(1_024);
+    engine.disable_symbol("eval");
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine
+}
+
+fn to_map(op: &Operation) -> Map {
+    let mut map = Map::new();
+    let mut put = |key: &str, value: Dynamic| {
+        map.insert(key.into(), value);
+    };
+    let addr = |a: &Address| Dynamic::from(a.as_str().to_string());
+    let amount = |b: &Balance| Dynamic::from(INT::try_from(*b).unwrap_or(INT::MAX));
+
+    let kind = match op {
+        Operation::Transfer {
+            from,
+            to,
+            amount: a,
+        } => {
+            put("from", addr(from));
+            put("to", addr(to));
+            put("amount", amount(a));
+            "Transfer"
+        }
+        Operation::NoncedTransfer {
+            from,
+            to,
+            amount: a,
+            nonce,
+        } => {
+            put("from", addr(from));
+            put("to", addr(to));
+            put("amount", amount(a));
+            put(
+                "nonce",
+                Dynamic::from(INT::try_from(*nonce).unwrap_or(INT::MAX)),
+            );
+            "NoncedTransfer"
+        }
+        Operation::Approve {
+            owner,
+            spender,
+            amount: a,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("amount", amount(a));
+            "Approve"
+        }
+        Operation::Permit {
+            owner,
+            spender,
+            amount: a,
+            nonce,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("amount", amount(a));
+            put(
+                "nonce",
+                Dynamic::from(INT::try_from(*nonce).unwrap_or(INT::MAX)),
+            );
+            "Permit"
+        }
+        Operation::ApproveUntil {
+            owner,
+            spender,
+            amount: a,
+            expires_at,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("amount", amount(a));
+            put(
+                "expires_at",
+                Dynamic::from(INT::try_from(*expires_at).unwrap_or(INT::MAX)),
+            );
+            "ApproveUntil"
+        }
+        Operation::ApproveIf {
+            owner,
+            spender,
+            expected_current,
+            new_amount,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("expected_current", amount(expected_current));
+            put("new_amount", amount(new_amount));
+            "ApproveIf"
+        }
+        Operation::IncreaseAllowance {
+            owner,
+            spender,
+            added,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("added", amount(added));
+            "IncreaseAllowance"
+        }
+        Operation::DecreaseAllowance {
+            owner,
+            spender,
+            subtracted,
+        } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            put("subtracted", amount(subtracted));
+            "DecreaseAllowance"
+        }
+        Operation::RevokeAllowance { owner, spender } => {
+            put("owner", addr(owner));
+            put("spender", addr(spender));
+            "RevokeAllowance"
+        }
+        Operation::RevokeAll { owner } => {
+            put("owner", addr(owner));
+            "RevokeAll"
+        }
+        Operation::TransferFrom {
+            spender,
+            from,
+            to,
+            amount: a,
+        } => {
+            put("spender", addr(spender));
+            put("from", addr(from));
+            put("to", addr(to));
+            put("amount", amount(a));
+            "TransferFrom"
+        }
+        Operation::GrantCapability {
+            issuer,
+            limit,
+            recipients,
+            expires_at,
+            ..
+        } => {
+            put("issuer", addr(issuer));
+            put("limit", amount(limit));
+            put(
+                "recipients",
+                Dynamic::from_array(recipients.iter().map(addr).collect()),
+            );
+            if let Some(expires_at) = expires_at {
+                put(
+                    "expires_at",
+                    Dynamic::from(INT::try_from(*expires_at).unwrap_or(INT::MAX)),
+                );
+            }
+            "GrantCapability"
+        }
+        Operation::RedeemCapability {
+            capability,
+            to,
+            amount: a,
+        } => {
+            put(
+                "id",
+                Dynamic::from(INT::try_from(capability.id.0).unwrap_or(INT::MAX)),
+            );
+            put("to", addr(to));
+            put("amount", amount(a));
+            "RedeemCapability"
+        }
+        Operation::RevokeCapability { issuer, id } => {
+            put("issuer", addr(issuer));
+            put("id", Dynamic::from(INT::try_from(id.0).unwrap_or(INT::MAX)));
+            "RevokeCapability"
+        }
+        Operation::Shield { from, amount: a } => {
+            put("from", addr(from));
+            put("amount", amount(a));
+            "Shield"
+        }
+        Operation::Unshield { to, note, .. } => {
+            put("to", addr(to));
+            put("amount", amount(&note.amount));
+            "Unshield"
+        }
+        Operation::BulkCredit { caller, entries } => {
+            put("caller", addr(caller));
+            let entries: rhai::Array = entries
+                .iter()
+                .map(|(a, b)| Dynamic::from_array(vec![addr(a), amount(b)]))
+                .collect();
+            put("entries", Dynamic::from_array(entries));
+            "BulkCredit"
+        }
+        Operation::Mint {
+            caller,
+            to,
+            amount: a,
+        } => {
+            put("caller", addr(caller));
+            put("to", addr(to));
+            put("amount", amount(a));
+            "Mint"
+        }
+        Operation::Burn { from, amount: a } => {
+            put("from", addr(from));
+            put("amount", amount(a));
+            "Burn"
+        }
+        Operation::TransferFromBatch { spender, legs } => {
+            let legs: rhai::Array = legs
+                .iter()
+                .map(|(from, to, a)| Dynamic::from_array(vec![addr(from), addr(to), amount(a)]))
+                .collect();
+            put("spender", addr(spender));
+            put("legs", Dynamic::from_array(legs));
+            "TransferFromBatch"
+        }
+        Operation::BurnFrom {
+            spender,
+            from,
+            amount: a,
+        } => {
+            put("spender", addr(spender));
+            put("from", addr(from));
+            put("amount", amount(a));
+            "BurnFrom"
+        }
+        Operation::Lock {
+            owner,
+            amount: a,
+            unlock_at,
+        } => {
+            put("owner", addr(owner));
+            put("amount", amount(a));
+            put(
+                "unlock_at",
+                Dynamic::from(INT::try_from(*unlock_at).unwrap_or(INT::MAX)),
+            );
+            "Lock"
+        }
+        Operation::Unlock { owner } => {
+            put("owner", addr(owner));
+            "Unlock"
+        }
+        Operation::EscrowCreate {
+            payer,
+            payee,
+            amount: a,
+        } => {
+            put("payer", addr(payer));
+            put("payee", addr(payee));
+            put("amount", amount(a));
+            "EscrowCreate"
+        }
+        Operation::EscrowRelease { caller, id } => {
+            put("caller", addr(caller));
+            put("id", Dynamic::from(INT::try_from(id.0).unwrap_or(INT::MAX)));
+            "EscrowRelease"
+        }
+        Operation::EscrowRefund { caller, id } => {
+            put("caller", addr(caller));
+            put("id", Dynamic::from(INT::try_from(id.0).unwrap_or(INT::MAX)));
+            "EscrowRefund"
+        }
+        Operation::MarkNonCirculating { caller, address } => {
+            put("caller", addr(caller));
+            put("address", addr(address));
+            "MarkNonCirculating"
+        }
+        Operation::UnmarkNonCirculating { caller, address } => {
+            put("caller", addr(caller));
+            put("address", addr(address));
+            "UnmarkNonCirculating"
+        }
+        Operation::UpdateConfig { caller, config } => {
+            put("caller", addr(caller));
+            put("existential_deposit", amount(&config.existential_deposit));
+            "UpdateConfig"
+        }
+        Operation::QueueConfigUpdate { caller, config } => {
+            put("caller", addr(caller));
+            put("existential_deposit", amount(&config.existential_deposit));
+            "QueueConfigUpdate"
+        }
+        Operation::ExecuteConfigUpdate { caller, id } => {
+            put("caller", addr(caller));
+            put("id", Dynamic::from(INT::try_from(id.0).unwrap_or(INT::MAX)));
+            "ExecuteConfigUpdate"
+        }
+        Operation::CancelConfigUpdate { caller, id } => {
+            put("caller", addr(caller));
+            put("id", Dynamic::from(INT::try_from(id.0).unwrap_or(INT::MAX)));
+            "CancelConfigUpdate"
+        }
+        Operation::Pause { caller } => {
+            put("caller", addr(caller));
+            "Pause"
+        }
+        Operation::Unpause { caller } => {
+            put("caller", addr(caller));
+            "Unpause"
+        }
+        Operation::TransferOwnership { caller, new_owner } => {
+            put("caller", addr(caller));
+            put("new_owner", addr(new_owner));
+            "TransferOwnership"
+        }
+        Operation::AcceptOwnership { caller } => {
+            put("caller", addr(caller));
+            "AcceptOwnership"
+        }
+        Operation::RenounceOwnership { caller } => {
+            put("caller", addr(caller));
+            "RenounceOwnership"
+        }
+        Operation::FreezeAccount { caller, address } => {
+            put("caller", addr(caller));
+            put("address", addr(address));
+            "FreezeAccount"
+        }
+        Operation::UnfreezeAccount { caller, address } => {
+            put("caller", addr(caller));
+            put("address", addr(address));
+            "UnfreezeAccount"
+        }
+        Operation::GrantRole {
+            caller,
+            role,
+            account,
+        } => {
+            put("caller", addr(caller));
+            put("role", format!("{role:?}").into());
+            put("account", addr(account));
+            "GrantRole"
+        }
+        Operation::RevokeRole {
+            caller,
+            role,
+            account,
+        } => {
+            put("caller", addr(caller));
+            put("role", format!("{role:?}").into());
+            put("account", addr(account));
+            "RevokeRole"
+        }
+    };
+    put("kind", Dynamic::from(kind.to_string()));
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenState;
+
+    const LIMIT: &str = r#"
+        fn check(op) {
+            if op.kind == "Transfer" && op.amount > 500 {
+                return "transfers above 500 need review";
+            }
+            if op.kind == "Approve" { return false; }
+            true
+        }
+    "#;
+
+    #[test]
+    fn test_script_allows_and_rejects() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(ScriptPolicy::new(LIMIT).unwrap()));
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        let big = token.transfer(&alice, &bob, 600);
+        let approve = token.approve(&alice, &bob, 1);
+
+        assert_eq!(
+            big.unwrap_err(),
+            TokenError::PolicyRejected("transfers above 500 need review".into())
+        );
+        assert_eq!(
+            approve.unwrap_err(),
+            TokenError::PolicyRejected("rejected by policy".into())
+        );
+        assert_eq!(token.balance_of(&bob), 100);
+    }
+
+    #[test]
+    fn test_script_must_define_check() {
+        let err = ScriptPolicy::new("fn other(op) { true }").err().unwrap();
+
+        assert_eq!(err.to_string(), "script does not define check(op)");
+        assert!(ScriptPolicy::new("fn check(op) {").is_err());
+    }
+
+    #[test]
+    fn test_runaway_script_is_metered_and_fails_closed() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let policy = ScriptPolicy::new("fn check(op) { loop {} }")
+            .unwrap()
+            .with_max_operations(1_000);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(policy));
+
+        let result = token.transfer(&alice, &bob, 1);
+
+        assert!(
+            matches!(result, Err(TokenError::PolicyRejected(ref e)) if e.starts_with("policy script failed")),
+            "{result:?}"
+        );
+        assert_eq!(token.balance_of(&bob), 0);
+    }
+
+    #[test]
+    fn test_handle_reloads_valid_scripts_only() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let policy = ScriptPolicy::new("fn check(op) { true }").unwrap();
+        let handle = policy.handle();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.add_interceptor(Box::new(policy));
+
+        assert!(handle.reload("fn check(op) {").is_err());
+        token.transfer(&alice, &bob, 600).unwrap();
+        handle.reload(LIMIT).unwrap();
+
+        assert!(token.transfer(&alice, &bob, 600).is_err());
+        assert_eq!(token.balance_of(&bob), 600);
+    }
+
+    #[test]
+    fn test_eval_is_disabled() {
+        assert!(ScriptPolicy::new(r#"fn check(op) { eval("true") }"#).is_err());
+    }
+}
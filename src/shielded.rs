@@ -0,0 +1,340 @@
+//! Shielded sub-pool for studying transparent/shielded supply dynamics.
+//!
+//! Funds moved into the pool leave the public `balances` map and are only
+//! tracked as an aggregate. Releasing them requires a [`Note`] plus a proof
+//! accepted by a pluggable [`ProofVerifier`]. No real cryptography happens
+//! here: the verifier is the trust boundary.
+//!
+//! The pool remembers the amount shielded under each unspent note id, so a
+//! note must name an id it issued and claim exactly that amount; a note
+//! with a made-up id or an inflated amount is rejected before the
+//! verifier's answer matters.
+//!
+//! Both directions follow the existential-deposit rules of a transfer:
+//! shielding may not leave the sender with dust, and unshielding may not
+//! credit a recipient with less than the minimum.
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::{Address, Balance, TokenError, TokenState};
+
+/// A claim on funds held in the shielded pool.
+///
+/// Returned by [`TokenState::shield`] and kept off-ledger by its holder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    /// Unique identifier, used as the nullifier when the note is spent
+    pub id: u64,
+    /// Amount of tokens the note releases
+    pub amount: Balance,
+}
+
+/// Decides whether a proof authorizes releasing a note.
+pub trait ProofVerifier {
+    fn verify(&self, note: &Note, proof: &[u8]) -> bool;
+}
+
+/// Verifier that accepts every proof. For tests and simulations only.
+pub struct AcceptAllVerifier;
+
+impl ProofVerifier for AcceptAllVerifier {
+    fn verify(&self, _note: &Note, _proof: &[u8]) -> bool {
+        true
+    }
+}
+
+impl TokenState {
+    /// Moves `amount` from `from`'s public balance into the shielded pool.
+    pub fn shield(&mut self, from: &Address, amount: Balance) -> Result<Note, TokenError> {
+        let receipt = self.execute(Operation::Shield {
+            from: from.clone(),
+            amount,
+        })?;
+        Ok(receipt.note.expect("shield receipts always carry a note"))
+    }
+
+    pub(crate) fn apply_shield(
+        &mut self,
+        from: &Address,
+        amount: Balance,
+    ) -> Result<Note, TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+
+        let from_bal = self.check_spendable(from, amount)?;
+        let remaining = from_bal - amount;
+        let minimum = self.config.existential_deposit;
+        if remaining > 0 && remaining < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: remaining,
+                minimum,
+            });
+        }
+
+        // pool <= total_supply 이므로 overflow 불가능하지만 방어적으로 검사
+        let pool = self
+            .shielded_pool
+            .checked_add(amount)
+            .ok_or(TokenError::BalanceOverFlow)?;
+
+        self.write_balance(from, remaining);
+        self.shielded_pool = pool;
+
+        let note = Note {
+            id: self.next_note_id,
+            amount,
+        };
+        self.next_note_id += 1;
+        self.journal_note(note.id);
+        self.open_notes.insert(note.id, amount);
+        self.emit(TokenEvent::Shield {
+            from: from.clone(),
+            amount,
+        });
+
+        Ok(note)
+    }
+
+    /// Installs the verifier that checks proofs presented to `unshield`.
+    ///
+    /// Until one is set, every unshield is rejected with `InvalidProof`.
+    pub fn set_proof_verifier(&mut self, verifier: Box<dyn ProofVerifier>) {
+        self.proof_verifier = Some(verifier);
+    }
+
+    /// Releases a shielded note to `to` after the verifier accepts `proof`.
+    pub fn unshield(&mut self, to: &Address, note: &Note, proof: &[u8]) -> Result<(), TokenError> {
+        self.execute(Operation::Unshield {
+            to: to.clone(),
+            note: note.clone(),
+            proof: proof.to_vec(),
+        })
+        .map(|_| ())
+    }
+
+    pub(crate) fn apply_unshield(
+        &mut self,
+        to: &Address,
+        note: &Note,
+        proof: &[u8],
+    ) -> Result<Balance, TokenError> {
+        let accepted = self
+            .proof_verifier
+            .as_ref()
+            .is_some_and(|v| v.verify(note, proof));
+        if !accepted {
+            return Err(TokenError::InvalidProof);
+        }
+        if self.spent_notes.contains(&note.id) {
+            return Err(TokenError::NoteAlreadySpent);
+        }
+        self.check_note(note)?;
+
+        let to_bal = self
+            .balance_of(to)
+            .checked_add(note.amount)
+            .ok_or(TokenError::BalanceOverFlow)?;
+        let minimum = self.config.existential_deposit;
+        if to_bal < minimum {
+            return Err(TokenError::BelowExistentialDeposit {
+                balance: to_bal,
+                minimum,
+            });
+        }
+
+        self.write_balance(to, to_bal);
+        self.shielded_pool -= note.amount;
+        self.journal_note(note.id);
+        self.spent_notes.insert(note.id);
+        self.open_notes.remove(&note.id);
+        self.emit(TokenEvent::Unshield {
+            to: to.clone(),
+            amount: note.amount,
+        });
+
+        Ok(note.amount)
+    }
+
+    // 발행된 id인지, 금액이 발행 당시와 같은지 확인
+    fn check_note(&self, note: &Note) -> Result<(), TokenError> {
+        let Some(&issued) = self.open_notes.get(&note.id) else {
+            return Err(TokenError::UnknownNote);
+        };
+        if issued != note.amount {
+            return Err(TokenError::NoteMismatch {
+                issued,
+                presented: note.amount,
+            });
+        }
+        Ok(())
+    }
+
+    /// Aggregate amount currently held in the shielded pool.
+    pub fn shielded_supply(&self) -> Balance {
+        self.shielded_pool
+    }
+
+    /// Supply visible in public balances (`total_supply - shielded_supply`).
+    pub fn transparent_supply(&self) -> Balance {
+        self.total_supply - self.shielded_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RejectAllVerifier;
+
+    impl ProofVerifier for RejectAllVerifier {
+        fn verify(&self, _note: &Note, _proof: &[u8]) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_shield_moves_funds_into_pool() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        let note = token.shield(&alice, 300).unwrap();
+
+        assert_eq!(note.amount, 300);
+        assert_eq!(token.balance_of(&alice), 700);
+        assert_eq!(token.shielded_supply(), 300);
+        assert_eq!(token.transparent_supply(), 700);
+        assert_eq!(token.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_shield_insufficient_balance() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 100);
+
+        let result = token.shield(&alice, 200);
+
+        assert_eq!(
+            result.unwrap_err(),
+            TokenError::InsufficientBalance {
+                required: 200,
+                available: 100
+            }
+        );
+    }
+
+    #[test]
+    fn test_unshield_success() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+        let note = token.shield(&alice, 300).unwrap();
+
+        let result = token.unshield(&bob, &note, b"proof");
+
+        assert!(result.is_ok());
+        assert_eq!(token.balance_of(&bob), 300);
+        assert_eq!(token.shielded_supply(), 0);
+        assert_eq!(token.transparent_supply(), 1000);
+    }
+
+    #[test]
+    fn test_dust_rules_apply_to_both_sides() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = crate::TokenConfig {
+            existential_deposit: 10,
+            ..crate::TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 100, config);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+
+        let dust_left = token.shield(&alice, 95);
+        let small = token.shield(&alice, 5).unwrap();
+        let dust_paid = token.unshield(&bob, &small, b"proof");
+
+        let below = |balance| {
+            Err(TokenError::BelowExistentialDeposit {
+                balance,
+                minimum: 10,
+            })
+        };
+        assert_eq!(dust_left.map(|_| ()), below(5));
+        assert_eq!(dust_paid, below(5));
+        assert_eq!(token.balance_of(&bob), 0);
+        assert_eq!(token.shielded_supply(), 5);
+    }
+
+    #[test]
+    fn test_unshield_rejected_proof() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(RejectAllVerifier));
+        let note = token.shield(&alice, 300).unwrap();
+
+        let result = token.unshield(&alice, &note, b"bad");
+
+        assert_eq!(result.unwrap_err(), TokenError::InvalidProof);
+        assert_eq!(token.shielded_supply(), 300);
+    }
+
+    #[test]
+    fn test_unshield_double_spend() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+        let note = token.shield(&alice, 100).unwrap();
+        token.shield(&alice, 100).unwrap();
+
+        token.unshield(&bob, &note, b"proof").unwrap();
+        let result = token.unshield(&bob, &note, b"proof");
+
+        assert_eq!(result.unwrap_err(), TokenError::NoteAlreadySpent);
+        assert_eq!(token.balance_of(&bob), 100);
+    }
+
+    #[test]
+    fn test_unshield_forged_notes_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+        let note = token.shield(&alice, 100).unwrap();
+        token.shield(&alice, 400).unwrap();
+        let made_up = Note {
+            id: 42,
+            amount: 100,
+        };
+        let inflated = Note {
+            amount: 500,
+            ..note.clone()
+        };
+
+        let unknown = token.unshield(&alice, &made_up, b"proof");
+        let mismatch = token.unshield(&alice, &inflated, b"proof");
+
+        assert_eq!(unknown.unwrap_err(), TokenError::UnknownNote);
+        assert_eq!(
+            mismatch.unwrap_err(),
+            TokenError::NoteMismatch {
+                issued: 100,
+                presented: 500
+            }
+        );
+        assert_eq!(token.shielded_supply(), 500);
+        assert!(token.unshield(&alice, &note, b"proof").is_ok());
+    }
+
+    #[test]
+    fn test_unshield_without_verifier_rejected() {
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let note = token.shield(&alice, 300).unwrap();
+
+        let result = token.unshield(&alice, &note, b"proof");
+
+        assert_eq!(result.unwrap_err(), TokenError::InvalidProof);
+    }
+}
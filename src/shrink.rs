@@ -0,0 +1,547 @@
+//! Failure minimization and regression-test generation.
+//!
+//! [`shrink`] delta-debugs a failing sequence down to a 1-minimal one: no
+//! single remaining element can be dropped without the failure
+//! disappearing. [`regression_test`] renders a minimized soak reproducer as
+//! a self-contained `#[test]` to paste into `tests/`.
+
+use std::fmt::Write;
+
+use crate::operation::Operation;
+use crate::soak::SoakConfig;
+use crate::{Address, Balance, FeeDestination, FeePolicy, TokenConfig};
+
+/// Removes elements from `items` for as long as `fails` keeps returning
+/// true, returning the smallest failing sequence found.
+///
+/// If `items` does not fail to begin with, it is returned unchanged.
+pub fn shrink<T: Clone>(mut items: Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    if !fails(&items) {
+        return items;
+    }
+
+    // ddmin: 보완집합(청크 제거)만 시도, 실패 시 청크를 절반으로
+    let mut chunks = 2;
+    while items.len() >= 2 {
+        let size = items.len().div_ceil(chunks);
+        let mut reduced = false;
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + size).min(items.len());
+            let candidate: Vec<T> = items[..start]
+                .iter()
+                .chain(&items[end..])
+                .cloned()
+                .collect();
+            if fails(&candidate) {
+                items = candidate;
+                reduced = true;
+            } else {
+                start = end;
+            }
+        }
+
+        if reduced {
+            chunks = (chunks - 1).max(2);
+        } else if chunks >= items.len() {
+            break;
+        } else {
+            chunks = (chunks * 2).min(items.len());
+        }
+    }
+
+    if items.len() == 1 && fails(&[]) {
+        items.clear();
+    }
+    items
+}
+
+/// Shrinks a soak reproducer against [`crate::soak::replay`].
+pub fn shrink_soak(config: &SoakConfig, ops: Vec<Operation>) -> Vec<Operation> {
+    shrink(ops, |ops| crate::soak::replay(config, ops).is_err())
+}
+
+/// Renders `ops` as an integration test asserting that replaying them on
+/// `config`'s genesis state passes every soak check.
+pub fn regression_test(name: &str, config: &SoakConfig, ops: &[Operation]) -> String {
+    let mut out = String::new();
+    out.push_str("use token_standard::*;\n\n");
+    out.push_str("#[test]\n");
+    let _ = writeln!(out, "fn {name}() {{");
+    out.push_str("    let config = soak::SoakConfig {\n");
+    let _ = writeln!(out, "        seed: {},", config.seed);
+    let _ = writeln!(out, "        accounts: {},", config.accounts);
+    let _ = writeln!(out, "        token: {},", config_literal(&config.token));
+    out.push_str("        ..soak::SoakConfig::default()\n");
+    out.push_str("    };\n");
+    out.push_str("    let ops = vec![\n");
+    for op in ops {
+        let _ = writeln!(out, "        {},", literal(op));
+    }
+    out.push_str("    ];\n\n");
+    out.push_str("    assert_eq!(soak::replay(&config, &ops), Ok(()));\n");
+    out.push_str("}\n");
+    out
+}
+
+fn addr(address: &Address) -> String {
+    format!("{:?}.parse().unwrap()", address.as_str())
+}
+
+fn config_literal(config: &TokenConfig) -> String {
+    let mut burn: Vec<_> = config.burn_addresses.iter().collect();
+    burn.sort();
+    format!(
+        "TokenConfig {{ self_transfer: EdgeCaseMode::{:?}, zero_amount: EdgeCaseMode::{:?}, \
+         existential_deposit: {}, dust_policy: DustPolicy::{:?}, burn_addresses: [{}].into(), \
+         fee: {}, fee_destination: {}, soulbound: {}, update_delay: {} }}",
+        config.self_transfer,
+        config.zero_amount,
+        config.existential_deposit,
+        config.dust_policy,
+        list(burn.iter().map(|a| addr(a))),
+        fee_literal(&config.fee),
+        match &config.fee_destination {
+            FeeDestination::Burn => "FeeDestination::Burn".to_string(),
+            FeeDestination::Treasury(treasury) =>
+                format!("FeeDestination::Treasury({})", addr(treasury)),
+        },
+        config.soulbound,
+        config.update_delay
+    )
+}
+
+fn fee_literal(fee: &FeePolicy) -> String {
+    match fee {
+        FeePolicy::None => "FeePolicy::None".to_string(),
+        FeePolicy::Flat(fee) => format!("FeePolicy::Flat({fee})"),
+        FeePolicy::BasisPoints(bps) => format!("FeePolicy::BasisPoints({bps})"),
+        FeePolicy::Tiered(tiers) => {
+            let tiers = tiers.iter().map(|tier| {
+                format!(
+                    "FeeTier {{ min_amount: {}, basis_points: {} }}",
+                    tier.min_amount, tier.basis_points
+                )
+            });
+            format!("FeePolicy::Tiered(vec![{}])", list(tiers))
+        }
+    }
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+fn literal(op: &Operation) -> String {
+    let fields = |name: &str, fields: &[(&str, String)]| {
+        let body = list(fields.iter().map(|(k, v)| format!("{k}: {v}")));
+        format!("Operation::{name} {{ {body} }}")
+    };
+    let n = |amount: &Balance| amount.to_string();
+
+    match op {
+        Operation::Transfer { from, to, amount } => fields(
+            "Transfer",
+            &[
+                ("from", addr(from)),
+                ("to", addr(to)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::NoncedTransfer {
+            from,
+            to,
+            amount,
+            nonce,
+        } => fields(
+            "NoncedTransfer",
+            &[
+                ("from", addr(from)),
+                ("to", addr(to)),
+                ("amount", n(amount)),
+                ("nonce", nonce.to_string()),
+            ],
+        ),
+        Operation::Approve {
+            owner,
+            spender,
+            amount,
+        } => fields(
+            "Approve",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::Permit {
+            owner,
+            spender,
+            amount,
+            nonce,
+        } => fields(
+            "Permit",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("amount", n(amount)),
+                ("nonce", nonce.to_string()),
+            ],
+        ),
+        Operation::ApproveUntil {
+            owner,
+            spender,
+            amount,
+            expires_at,
+        } => fields(
+            "ApproveUntil",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("amount", n(amount)),
+                ("expires_at", expires_at.to_string()),
+            ],
+        ),
+        Operation::ApproveIf {
+            owner,
+            spender,
+            expected_current,
+            new_amount,
+        } => fields(
+            "ApproveIf",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("expected_current", n(expected_current)),
+                ("new_amount", n(new_amount)),
+            ],
+        ),
+        Operation::IncreaseAllowance {
+            owner,
+            spender,
+            added,
+        } => fields(
+            "IncreaseAllowance",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("added", n(added)),
+            ],
+        ),
+        Operation::DecreaseAllowance {
+            owner,
+            spender,
+            subtracted,
+        } => fields(
+            "DecreaseAllowance",
+            &[
+                ("owner", addr(owner)),
+                ("spender", addr(spender)),
+                ("subtracted", n(subtracted)),
+            ],
+        ),
+        Operation::RevokeAllowance { owner, spender } => fields(
+            "RevokeAllowance",
+            &[("owner", addr(owner)), ("spender", addr(spender))],
+        ),
+        Operation::RevokeAll { owner } => fields("RevokeAll", &[("owner", addr(owner))]),
+        Operation::TransferFrom {
+            spender,
+            from,
+            to,
+            amount,
+        } => fields(
+            "TransferFrom",
+            &[
+                ("spender", addr(spender)),
+                ("from", addr(from)),
+                ("to", addr(to)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::GrantCapability {
+            issuer,
+            limit,
+            recipients,
+            expires_at,
+            digest,
+        } => fields(
+            "GrantCapability",
+            &[
+                ("issuer", addr(issuer)),
+                ("limit", n(limit)),
+                (
+                    "recipients",
+                    format!("[{}].into()", list(recipients.iter().map(addr))),
+                ),
+                ("expires_at", format!("{expires_at:?}")),
+                ("digest", format!("{digest:?}")),
+            ],
+        ),
+        Operation::RedeemCapability {
+            capability,
+            to,
+            amount,
+        } => fields(
+            "RedeemCapability",
+            &[
+                (
+                    "capability",
+                    format!(
+                        "Capability {{ id: CapabilityId({}), secret: {:?} }}",
+                        capability.id.0, capability.secret
+                    ),
+                ),
+                ("to", addr(to)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::RevokeCapability { issuer, id } => fields(
+            "RevokeCapability",
+            &[
+                ("issuer", addr(issuer)),
+                ("id", format!("CapabilityId({})", id.0)),
+            ],
+        ),
+        Operation::Shield { from, amount } => {
+            fields("Shield", &[("from", addr(from)), ("amount", n(amount))])
+        }
+        Operation::Unshield { to, note, proof } => fields(
+            "Unshield",
+            &[
+                ("to", addr(to)),
+                (
+                    "note",
+                    format!("Note {{ id: {}, amount: {} }}", note.id, note.amount),
+                ),
+                ("proof", format!("vec!{proof:?}")),
+            ],
+        ),
+        Operation::BulkCredit { caller, entries } => {
+            let entries = list(entries.iter().map(|(a, b)| format!("({}, {b})", addr(a))));
+            fields(
+                "BulkCredit",
+                &[
+                    ("caller", addr(caller)),
+                    ("entries", format!("vec![{entries}]")),
+                ],
+            )
+        }
+        Operation::Mint { caller, to, amount } => fields(
+            "Mint",
+            &[
+                ("caller", addr(caller)),
+                ("to", addr(to)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::Burn { from, amount } => {
+            fields("Burn", &[("from", addr(from)), ("amount", n(amount))])
+        }
+        Operation::TransferFromBatch { spender, legs } => {
+            let legs = list(
+                legs.iter()
+                    .map(|(from, to, amount)| format!("({}, {}, {amount})", addr(from), addr(to))),
+            );
+            fields(
+                "TransferFromBatch",
+                &[
+                    ("spender", addr(spender)),
+                    ("legs", format!("vec![{legs}]")),
+                ],
+            )
+        }
+        Operation::BurnFrom {
+            spender,
+            from,
+            amount,
+        } => fields(
+            "BurnFrom",
+            &[
+                ("spender", addr(spender)),
+                ("from", addr(from)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::Lock {
+            owner,
+            amount,
+            unlock_at,
+        } => fields(
+            "Lock",
+            &[
+                ("owner", addr(owner)),
+                ("amount", n(amount)),
+                ("unlock_at", unlock_at.to_string()),
+            ],
+        ),
+        Operation::Unlock { owner } => fields("Unlock", &[("owner", addr(owner))]),
+        Operation::EscrowCreate {
+            payer,
+            payee,
+            amount,
+        } => fields(
+            "EscrowCreate",
+            &[
+                ("payer", addr(payer)),
+                ("payee", addr(payee)),
+                ("amount", n(amount)),
+            ],
+        ),
+        Operation::EscrowRelease { caller, id } => fields(
+            "EscrowRelease",
+            &[
+                ("caller", addr(caller)),
+                ("id", format!("EscrowId({})", id.0)),
+            ],
+        ),
+        Operation::EscrowRefund { caller, id } => fields(
+            "EscrowRefund",
+            &[
+                ("caller", addr(caller)),
+                ("id", format!("EscrowId({})", id.0)),
+            ],
+        ),
+        Operation::MarkNonCirculating { caller, address } => fields(
+            "MarkNonCirculating",
+            &[("caller", addr(caller)), ("address", addr(address))],
+        ),
+        Operation::UnmarkNonCirculating { caller, address } => fields(
+            "UnmarkNonCirculating",
+            &[("caller", addr(caller)), ("address", addr(address))],
+        ),
+        Operation::UpdateConfig { caller, config } => fields(
+            "UpdateConfig",
+            &[("caller", addr(caller)), ("config", config_literal(config))],
+        ),
+        Operation::QueueConfigUpdate { caller, config } => fields(
+            "QueueConfigUpdate",
+            &[("caller", addr(caller)), ("config", config_literal(config))],
+        ),
+        Operation::ExecuteConfigUpdate { caller, id } => fields(
+            "ExecuteConfigUpdate",
+            &[
+                ("caller", addr(caller)),
+                ("id", format!("ConfigUpdateId({})", id.0)),
+            ],
+        ),
+        Operation::CancelConfigUpdate { caller, id } => fields(
+            "CancelConfigUpdate",
+            &[
+                ("caller", addr(caller)),
+                ("id", format!("ConfigUpdateId({})", id.0)),
+            ],
+        ),
+        Operation::Pause { caller } => fields("Pause", &[("caller", addr(caller))]),
+        Operation::Unpause { caller } => fields("Unpause", &[("caller", addr(caller))]),
+        Operation::TransferOwnership { caller, new_owner } => fields(
+            "TransferOwnership",
+            &[("caller", addr(caller)), ("new_owner", addr(new_owner))],
+        ),
+        Operation::AcceptOwnership { caller } => {
+            fields("AcceptOwnership", &[("caller", addr(caller))])
+        }
+        Operation::RenounceOwnership { caller } => {
+            fields("RenounceOwnership", &[("caller", addr(caller))])
+        }
+        Operation::FreezeAccount { caller, address } => fields(
+            "FreezeAccount",
+            &[("caller", addr(caller)), ("address", addr(address))],
+        ),
+        Operation::UnfreezeAccount { caller, address } => fields(
+            "UnfreezeAccount",
+            &[("caller", addr(caller)), ("address", addr(address))],
+        ),
+        Operation::GrantRole {
+            caller,
+            role,
+            account,
+        } => fields(
+            "GrantRole",
+            &[
+                ("caller", addr(caller)),
+                ("role", format!("Role::{role:?}")),
+                ("account", addr(account)),
+            ],
+        ),
+        Operation::RevokeRole {
+            caller,
+            role,
+            account,
+        } => fields(
+            "RevokeRole",
+            &[
+                ("caller", addr(caller)),
+                ("role", format!("Role::{role:?}")),
+                ("account", addr(account)),
+            ],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shrink_finds_minimal_subset() {
+        let items: Vec<u32> = (0..200).collect();
+
+        let minimal = shrink(items, |xs| xs.contains(&37) && xs.contains(&151));
+
+        assert_eq!(minimal, vec![37, 151]);
+    }
+
+    #[test]
+    fn test_shrink_leaves_passing_input_alone() {
+        let items = vec![1, 2, 3];
+
+        assert_eq!(shrink(items.clone(), |_| false), items);
+    }
+
+    #[test]
+    fn test_shrink_soak_prefix() {
+        let config = SoakConfig {
+            accounts: 10,
+            ..SoakConfig::default()
+        };
+        let ops: Vec<_> = crate::soak::operations(5, 10).take(2_000).collect();
+        let is_big_mint =
+            |op: &Operation| matches!(op, Operation::Mint { amount, .. } if *amount > 9_000);
+        assert!(ops.iter().any(is_big_mint));
+
+        let minimal = shrink(ops, |ops| ops.iter().any(is_big_mint));
+        let kept = shrink_soak(&config, minimal.clone());
+
+        assert_eq!(minimal.len(), 1);
+        assert!(is_big_mint(&minimal[0]));
+        assert_eq!(kept, minimal);
+    }
+
+    #[test]
+    fn test_regression_test_renders_operations() {
+        let config = SoakConfig::default();
+        let ops = vec![
+            Operation::Transfer {
+                from: Address::new("addr1").unwrap(),
+                to: Address::new("addr2").unwrap(),
+                amount: 5,
+            },
+            Operation::BulkCredit {
+                caller: Address::new("addr1").unwrap(),
+                entries: vec![(Address::new("addr3").unwrap(), 7)],
+            },
+        ];
+
+        let source = regression_test("soak_regression_1", &config, &ops);
+
+        assert!(source.contains("fn soak_regression_1() {"));
+        assert!(source.contains(
+            r#"Operation::Transfer { from: "addr1".parse().unwrap(), to: "addr2".parse().unwrap(), amount: 5 },"#
+        ));
+        assert!(source.contains(
+            r#"Operation::BulkCredit { caller: "addr1".parse().unwrap(), entries: vec![("addr3".parse().unwrap(), 7)] },"#
+        ));
+        assert!(source.contains("soak::replay(&config, &ops)"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Deterministic agent-based simulation over a [`TokenState`].
+//!
+//! Each epoch every agent (in a seeded random order) observes the ledger
+//! through a [`View`] and queues [`Action`]s, which are applied immediately
+//! after its turn. Outcomes are collected into [`Metrics`]. Runs with the
+//! same seed and agents produce identical results.
+
+use crate::rng::Rng;
+use crate::{Address, Balance, TokenError, TokenState};
+
+/// A state-changing request queued by an agent on its own behalf.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Transfer {
+        to: Address,
+        amount: Balance,
+    },
+    Approve {
+        spender: Address,
+        amount: Balance,
+    },
+    /// Spend `from`'s allowance granted to the acting agent
+    TransferFrom {
+        from: Address,
+        to: Address,
+        amount: Balance,
+    },
+}
+
+/// Read-only snapshot handed to an agent on each tick.
+pub struct View<'a> {
+    /// Current epoch, starting at 0
+    pub epoch: u64,
+    /// The ledger as it stands before this agent's actions
+    pub token: &'a TokenState,
+    /// Addresses of every agent in the simulation
+    pub agents: &'a [Address],
+}
+
+/// Collects an agent's actions for the current tick.
+pub struct Actions<'a> {
+    queued: Vec<Action>,
+    rng: &'a mut Rng,
+}
+
+impl Actions<'_> {
+    pub fn push(&mut self, action: Action) {
+        self.queued.push(action);
+    }
+
+    /// The simulation's seeded RNG, for agents that behave randomly.
+    pub fn rng(&mut self) -> &mut Rng {
+        self.rng
+    }
+}
+
+/// Behavior of a simulated participant.
+pub trait Agent {
+    fn address(&self) -> &Address;
+    fn on_tick(&mut self, view: &View<'_>, actions: &mut Actions<'_>);
+}
+
+/// Outcome counters for a single epoch.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EpochMetrics {
+    pub attempted: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Sum of amounts moved by successful transfers
+    pub volume: Balance,
+}
+
+/// Per-epoch metrics collected over a run.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metrics {
+    pub epochs: Vec<EpochMetrics>,
+}
+
+impl Metrics {
+    pub fn total(&self) -> EpochMetrics {
+        let mut total = EpochMetrics::default();
+        for e in &self.epochs {
+            total.attempted += e.attempted;
+            total.succeeded += e.succeeded;
+            total.failed += e.failed;
+            total.volume = total.volume.saturating_add(e.volume);
+        }
+        total
+    }
+}
+
+pub struct Simulation {
+    token: TokenState,
+    agents: Vec<Box<dyn Agent>>,
+    rng: Rng,
+    epoch: u64,
+}
+
+impl Simulation {
+    pub fn new(token: TokenState, seed: u64) -> Self {
+        Self {
+            token,
+            agents: Vec::new(),
+            rng: Rng::new(seed),
+            epoch: 0,
+        }
+    }
+
+    pub fn add_agent(&mut self, agent: Box<dyn Agent>) {
+        self.agents.push(agent);
+    }
+
+    pub fn token(&self) -> &TokenState {
+        &self.token
+    }
+
+    pub fn into_token(self) -> TokenState {
+        self.token
+    }
+
+    /// Runs `epochs` epochs and returns their metrics.
+    pub fn run(&mut self, epochs: u64) -> Metrics {
+        let mut metrics = Metrics::default();
+        let addresses: Vec<Address> = self.agents.iter().map(|a| a.address().clone()).collect();
+
+        for _ in 0..epochs {
+            let mut epoch_metrics = EpochMetrics::default();
+            let mut order: Vec<usize> = (0..self.agents.len()).collect();
+            self.rng.shuffle(&mut order);
+
+            for i in order {
+                let mut actions = Actions {
+                    queued: Vec::new(),
+                    rng: &mut self.rng,
+                };
+                let view = View {
+                    epoch: self.epoch,
+                    token: &self.token,
+                    agents: &addresses,
+                };
+                self.agents[i].on_tick(&view, &mut actions);
+
+                let actor = &addresses[i];
+                for action in actions.queued {
+                    epoch_metrics.attempted += 1;
+                    match apply(&mut self.token, actor, &action) {
+                        Ok(moved) => {
+                            epoch_metrics.succeeded += 1;
+                            epoch_metrics.volume = epoch_metrics.volume.saturating_add(moved);
+                        }
+                        Err(_) => epoch_metrics.failed += 1,
+                    }
+                }
+            }
+
+            metrics.epochs.push(epoch_metrics);
+            self.epoch += 1;
+        }
+
+        metrics
+    }
+}
+
+// 성공 시 이동한 토큰 양을 반환 (approve는 0)
+fn apply(token: &mut TokenState, actor: &Address, action: &Action) -> Result<Balance, TokenError> {
+    match action {
+        Action::Transfer { to, amount } => token.transfer(actor, to, *amount).map(|_| *amount),
+        Action::Approve { spender, amount } => token.approve(actor, spender, *amount).map(|_| 0),
+        Action::TransferFrom { from, to, amount } => token
+            .transfer_from(actor, from, to, *amount)
+            .map(|_| *amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends a random slice of its balance to a random other agent.
+    struct RandomSender {
+        address: Address,
+    }
+
+    impl Agent for RandomSender {
+        fn address(&self) -> &Address {
+            &self.address
+        }
+
+        fn on_tick(&mut self, view: &View<'_>, actions: &mut Actions<'_>) {
+            let balance = view.token.balance_of(&self.address);
+            let target = actions.rng().below(view.agents.len() as u64) as usize;
+            let amount = actions.rng().next_u64() as Balance % (balance + 1);
+            actions.push(Action::Transfer {
+                to: view.agents[target].clone(),
+                amount,
+            });
+        }
+    }
+
+    fn agent(i: usize) -> Address {
+        Address::new(format!("a{i}")).unwrap()
+    }
+
+    fn build(seed: u64) -> Simulation {
+        let mut token = TokenState::new(agent(0), 10_000);
+        for i in 1..5 {
+            token.transfer(&agent(0), &agent(i), 2_000).unwrap();
+        }
+        let mut sim = Simulation::new(token, seed);
+        for i in 0..5 {
+            sim.add_agent(Box::new(RandomSender { address: agent(i) }));
+        }
+        sim
+    }
+
+    #[test]
+    fn test_run_is_deterministic() {
+        let mut first = build(42);
+        let mut second = build(42);
+
+        let m1 = first.run(20);
+        let m2 = second.run(20);
+
+        assert_eq!(m1, m2);
+        for i in 0..5 {
+            let addr = agent(i);
+            assert_eq!(
+                first.token().balance_of(&addr),
+                second.token().balance_of(&addr)
+            );
+        }
+    }
+
+    #[test]
+    fn test_metrics_account_for_every_action() {
+        let mut sim = build(7);
+
+        let metrics = sim.run(10);
+        let total = metrics.total();
+
+        assert_eq!(metrics.epochs.len(), 10);
+        assert_eq!(total.attempted, 50);
+        assert_eq!(total.succeeded + total.failed, total.attempted);
+    }
+
+    #[test]
+    fn test_supply_conserved() {
+        let mut sim = build(3);
+
+        sim.run(50);
+        let token = sim.into_token();
+        let held: Balance = (0..5).map(|i| token.balance_of(&agent(i))).sum();
+
+        assert_eq!(held, token.total_supply());
+    }
+}
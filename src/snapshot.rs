@@ -0,0 +1,380 @@
+//! Portable image of a whole [`TokenState`].
+//!
+//! A snapshot captures everything needed to resume the ledger: balances,
+//! allowances with their usage counters and expiries, supply and
+//! shielded-pool accounting with the amount of every open note,
+//! configuration, token metadata, sequence numbers, signed-transfer nonces
+//! with the domain separator they are bound to, capability grants, balance
+//! locks, open escrows, queued configuration updates and the undrained
+//! event log.
+//! Maps are kept in sorted order and allowances as explicit
+//! `{owner, spender, ...}` records, so the same state always encodes to
+//! the same bytes and formats without tuple map keys (JSON) work. Both the
+//! serde impls (`serde` feature) and the binary codec go through it.
+//!
+//! Runtime attachments are not part of the state and are not saved:
+//! interceptors, transfer hooks, event sinks, the proof verifier and the
+//! clock must be registered again after restoring. Restoring recomputes cached aggregates and runs
+//! [`TokenState::check_invariants`], so a tampered or truncated snapshot
+//! is rejected rather than loaded.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::events::TokenEvent;
+use crate::ocap::{CapabilityGrant, CapabilityId};
+use crate::{
+    Address, AllowanceUsage, Balance, ConfigUpdateId, Escrow, EscrowId, Lock, QueuedConfig, Role,
+    TokenConfig, TokenMetadata, TokenState,
+};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Snapshot {
+    pub(crate) config: TokenConfig,
+    pub(crate) total_supply: Balance,
+    pub(crate) balances: BTreeMap<Address, Balance>,
+    pub(crate) allowances: Vec<AllowanceRecord>,
+    pub(crate) shielded_pool: Balance,
+    pub(crate) next_note_id: u64,
+    pub(crate) spent_notes: BTreeSet<u64>,
+    pub(crate) open_notes: BTreeMap<u64, Balance>,
+    pub(crate) non_circulating: BTreeSet<Address>,
+    pub(crate) next_seq: u64,
+    pub(crate) events: Vec<(u64, TokenEvent)>,
+    pub(crate) metadata: Option<TokenMetadata>,
+    #[cfg(feature = "account-stats")]
+    pub(crate) stats: crate::stats::StatsSnapshot,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) owner: Option<Address>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) pending_owner: Option<Address>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) max_supply: Option<Balance>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) paused: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) frozen: BTreeSet<Address>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) roles: BTreeSet<(Address, Role)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) nonces: BTreeMap<Address, u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) domain_separator: Option<[u8; 32]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) capabilities: BTreeMap<u64, CapabilityGrant>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) next_capability_id: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) locks: BTreeMap<Address, Vec<Lock>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) next_escrow_id: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) escrows: BTreeMap<u64, Escrow>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) next_config_update_id: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) config_updates: BTreeMap<u64, QueuedConfig>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AllowanceRecord {
+    pub(crate) owner: Address,
+    pub(crate) spender: Address,
+    pub(crate) amount: Balance,
+    pub(crate) granted: Balance,
+    pub(crate) spent: Balance,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) expires_at: Option<u64>,
+}
+
+impl TokenState {
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        // 키 정렬로 동일 상태 → 동일 바이트
+        let mut pairs: BTreeMap<(&Address, &Address), (Balance, AllowanceUsage)> = BTreeMap::new();
+        for ((owner, spender), amount) in &self.allowances {
+            pairs.entry((owner, spender)).or_default().0 = *amount;
+        }
+        for ((owner, spender), usage) in &self.allowance_usage {
+            pairs.entry((owner, spender)).or_default().1 = *usage;
+        }
+
+        Snapshot {
+            config: self.config.clone(),
+            total_supply: self.total_supply,
+            balances: self.balances.iter().map(|(a, b)| (a.clone(), *b)).collect(),
+            allowances: pairs
+                .into_iter()
+                .map(|((owner, spender), (amount, usage))| AllowanceRecord {
+                    owner: owner.clone(),
+                    spender: spender.clone(),
+                    amount,
+                    granted: usage.granted,
+                    spent: usage.spent,
+                    expires_at: usage.expires_at,
+                })
+                .collect(),
+            shielded_pool: self.shielded_pool,
+            next_note_id: self.next_note_id,
+            spent_notes: self.spent_notes.iter().copied().collect(),
+            open_notes: self.open_notes.iter().map(|(id, a)| (*id, *a)).collect(),
+            non_circulating: self.non_circulating.iter().cloned().collect(),
+            next_seq: self.next_seq,
+            events: self.events.clone(),
+            metadata: self.metadata.clone(),
+            #[cfg(feature = "account-stats")]
+            stats: self.stats.snapshot(),
+            owner: self.owner.clone(),
+            pending_owner: self.pending_owner.clone(),
+            max_supply: self.max_supply,
+            paused: self.paused,
+            frozen: self.frozen.iter().cloned().collect(),
+            roles: self.roles.iter().cloned().collect(),
+            nonces: self.nonces.iter().map(|(a, n)| (a.clone(), *n)).collect(),
+            domain_separator: self.domain_separator,
+            capabilities: self
+                .capabilities
+                .iter()
+                .map(|(id, grant)| (id.0, grant.clone()))
+                .collect(),
+            next_capability_id: self.next_capability_id,
+            locks: self
+                .locks
+                .iter()
+                .map(|(a, locks)| (a.clone(), locks.clone()))
+                .collect(),
+            next_escrow_id: self.next_escrow_id,
+            escrows: self
+                .escrows
+                .iter()
+                .map(|(id, escrow)| (id.0, escrow.clone()))
+                .collect(),
+            next_config_update_id: self.next_config_update_id,
+            config_updates: self
+                .config_updates
+                .iter()
+                .map(|(id, queued)| (id.0, queued.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Snapshot {
+    pub(crate) fn restore(self) -> Result<TokenState, String> {
+        let mut token = TokenState::from_parts(
+            self.balances.into_iter().collect(),
+            self.total_supply,
+            self.config,
+        );
+        for record in self.allowances {
+            let key = (record.owner, record.spender);
+            token.allowances.insert(key.clone(), record.amount);
+            token.allowance_usage.insert(
+                key,
+                AllowanceUsage {
+                    granted: record.granted,
+                    spent: record.spent,
+                    expires_at: record.expires_at,
+                },
+            );
+        }
+        token.shielded_pool = self.shielded_pool;
+        token.next_note_id = self.next_note_id;
+        token.spent_notes = self.spent_notes.into_iter().collect();
+        token.open_notes = self.open_notes.into_iter().collect();
+        token.non_circulating_balance = self
+            .non_circulating
+            .iter()
+            .try_fold(0, |acc: Balance, a| acc.checked_add(token.balance_of(a)))
+            .ok_or("inconsistent snapshot: non-circulating balances overflow")?;
+        token.non_circulating = self.non_circulating.into_iter().collect();
+        token.next_seq = self.next_seq;
+        token.events = self.events;
+        token.metadata = self.metadata;
+        token.owner = self.owner;
+        token.pending_owner = self.pending_owner;
+        token.max_supply = self.max_supply;
+        token.paused = self.paused;
+        token.frozen = self.frozen.into_iter().collect();
+        token.roles = self.roles.into_iter().collect();
+        token.nonces = self.nonces.into_iter().collect();
+        token.domain_separator = self.domain_separator;
+        token.capabilities = self
+            .capabilities
+            .into_iter()
+            .map(|(id, grant)| (CapabilityId(id), grant))
+            .collect();
+        token.next_capability_id = self.next_capability_id;
+        token.locks = self.locks.into_iter().collect();
+        token.next_escrow_id = self.next_escrow_id;
+        token.escrows = self
+            .escrows
+            .into_iter()
+            .map(|(id, escrow)| (EscrowId(id), escrow))
+            .collect();
+        token.next_config_update_id = self.next_config_update_id;
+        token.config_updates = self
+            .config_updates
+            .into_iter()
+            .map(|(id, queued)| (ConfigUpdateId(id), queued))
+            .collect();
+        #[cfg(feature = "account-stats")]
+        token.stats.restore(self.stats);
+
+        token
+            .check_invariants()
+            .map_err(|detail| format!("inconsistent snapshot: {detail}"))?;
+        Ok(token)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TokenState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TokenState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Snapshot::deserialize(deserializer)?
+            .restore()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// TokenConfig::burn_addresses 직렬화 순서 고정
+#[cfg(feature = "serde")]
+pub(crate) fn sorted_set<S: serde::Serializer>(
+    set: &std::collections::HashSet<Address>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    set.iter().collect::<BTreeSet<_>>().serialize(serializer)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{AcceptAllVerifier, BURN_ADDRESS, TokenError};
+
+    fn busy_token() -> TokenState {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let config = TokenConfig {
+            existential_deposit: 2,
+            burn_addresses: [Address::new(BURN_ADDRESS).unwrap()].into(),
+            ..TokenConfig::default()
+        };
+        let mut token = TokenState::with_config(alice.clone(), 1000, config);
+        token.set_proof_verifier(Box::new(AcceptAllVerifier));
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.approve(&alice, &bob, 50).unwrap();
+        token.transfer_from(&bob, &alice, &bob, 20).unwrap();
+        token.mark_non_circulating(&alice, &bob).unwrap();
+        let note = token.shield(&alice, 30).unwrap();
+        token.unshield(&bob, &note, b"proof").unwrap();
+        token.shield(&alice, 40).unwrap();
+        token
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let token = busy_token();
+
+        let json = serde_json::to_string(&token).unwrap();
+        let restored: TokenState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert_eq!(restored.balance_of(&bob), 150);
+        assert_eq!(restored.allowance(&alice, &bob), 30);
+        assert_eq!(restored.allowance_usage(&alice, &bob), (50, 20, 30));
+        assert_eq!(restored.circulating_supply(), token.circulating_supply());
+        assert_eq!(restored.shielded_supply(), 40);
+        assert_eq!(restored.next_seq(), token.next_seq());
+        assert_eq!(restored.events(), token.events());
+    }
+
+    #[test]
+    fn test_restored_state_keeps_working() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let json = serde_json::to_string(&busy_token()).unwrap();
+        let mut restored: TokenState = serde_json::from_str(&json).unwrap();
+
+        let receipt = restored
+            .execute(crate::Operation::Transfer {
+                from: alice.clone(),
+                to: bob.clone(),
+                amount: 1,
+            })
+            .unwrap();
+        let replay = restored.shield(&alice, 1).unwrap();
+        restored.set_proof_verifier(Box::new(AcceptAllVerifier));
+        let open = crate::Note { id: 1, amount: 40 };
+
+        assert_eq!(receipt.seq, busy_token().next_seq());
+        assert_eq!(replay.id, 2);
+        assert_eq!(restored.unshield(&bob, &open, b"proof"), Ok(()));
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let alice = Address::new("alice").unwrap();
+        let metadata = TokenMetadata::new("Réseau", "RSX", 18);
+        let token = TokenState::new_with_metadata(alice, 1000, metadata.clone());
+
+        let json = serde_json::to_string(&token).unwrap();
+        let restored: TokenState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.metadata(), Some(&metadata));
+    }
+
+    #[test]
+    fn test_same_state_same_bytes() {
+        let a = Address::new("a").unwrap();
+        let b = Address::new("b").unwrap();
+        let c = Address::new("c").unwrap();
+        let forward = TokenState::from_balances([(a.clone(), 1), (b.clone(), 2), (c.clone(), 3)]);
+        let backward = TokenState::from_balances([(c, 3), (b, 2), (a, 1)]);
+
+        assert_eq!(
+            serde_json::to_string(&forward.unwrap()).unwrap(),
+            serde_json::to_string(&backward.unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tampered_snapshot_rejected() {
+        let mut value = serde_json::to_value(busy_token()).unwrap();
+        value["total_supply"] = serde_json::json!(5);
+
+        let error = serde_json::from_value::<TokenState>(value).err().unwrap();
+
+        assert!(
+            error.to_string().contains("inconsistent snapshot"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_address_rejected() {
+        let mut value = serde_json::to_value(busy_token()).unwrap();
+        value["balances"]["not valid"] = serde_json::json!(0);
+
+        assert!(serde_json::from_value::<TokenState>(value).is_err());
+    }
+
+    #[test]
+    fn test_error_round_trip() {
+        let error = TokenError::InsufficientAllowance {
+            required: 10,
+            available: 3,
+        };
+
+        let json = serde_json::to_string(&error).unwrap();
+
+        assert_eq!(serde_json::from_str::<TokenError>(&json).unwrap(), error);
+    }
+}
@@ -0,0 +1,431 @@
+//! Long-running randomized consistency soak.
+//!
+//! [`run`] drives a [`TokenState`] with a reproducible stream of mixed
+//! operations, calling [`TokenState::check_invariants`] every
+//! `check_every` operations and doing a full reconcile every
+//! `reconcile_every`. A full reconcile replays the emitted events into an
+//! independent shadow ledger and compares it against every balance.
+//!
+//! The operation stream depends only on the seed and account count, so a
+//! [`SoakFailure`] does not need to keep hours of history in memory: it
+//! regenerates the failing prefix on demand via [`SoakFailure::reproducer`].
+//! The `soak` binary wraps [`run`] for unattended runs.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::events::TokenEvent;
+use crate::operation::Operation;
+use crate::rng::Rng;
+use crate::sim::Action;
+use crate::workload::{Workload, WorkloadConfig};
+use crate::{Address, Balance, TokenConfig, TokenState};
+
+/// Balance every account starts with.
+const GENESIS_BALANCE: Balance = 1_000_000;
+
+/// Shape and cadence of a soak run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoakConfig {
+    pub seed: u64,
+    /// Number of distinct addresses, each funded at genesis
+    pub accounts: usize,
+    /// Stop after this many operations
+    pub max_ops: u64,
+    /// Stop once this much wall-clock time has passed, if set
+    pub duration: Option<Duration>,
+    /// Operations between [`TokenState::check_invariants`] calls
+    pub check_every: u64,
+    /// Operations between full event-replay reconciles
+    pub reconcile_every: u64,
+    /// Configuration of the token under test
+    pub token: TokenConfig,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            accounts: 100,
+            max_ops: 100_000,
+            duration: None,
+            check_every: 1_000,
+            reconcile_every: 10_000,
+            token: TokenConfig::default(),
+        }
+    }
+}
+
+/// Summary of a run that finished without finding a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoakReport {
+    /// Operations executed
+    pub ops: u64,
+    /// Operations that returned `Ok`
+    pub succeeded: u64,
+}
+
+/// A consistency violation, with enough context to reproduce it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoakFailure {
+    pub seed: u64,
+    pub accounts: usize,
+    /// Operations executed before the violation was detected
+    pub ops: u64,
+    pub detail: String,
+}
+
+impl SoakFailure {
+    /// The operation prefix that leads from genesis to the violation.
+    pub fn reproducer(&self) -> Vec<Operation> {
+        operations(self.seed, self.accounts)
+            .take(self.ops as usize)
+            .collect()
+    }
+}
+
+/// Runs the soak described by `config`.
+pub fn run(config: &SoakConfig) -> Result<SoakReport, SoakFailure> {
+    let deadline = config.duration.map(|d| Instant::now() + d);
+    let mut token = genesis(config);
+    let mut shadow = Shadow::new(&token);
+    let mut stream = operations(config.seed, config.accounts);
+    let mut report = SoakReport {
+        ops: 0,
+        succeeded: 0,
+    };
+    let fail = |ops, detail| SoakFailure {
+        seed: config.seed,
+        accounts: config.accounts,
+        ops,
+        detail,
+    };
+
+    while report.ops < config.max_ops {
+        // 시간 확인은 가끔만 (Instant::now 호출 비용 절감)
+        if report.ops.is_multiple_of(1024) && deadline.is_some_and(|d| Instant::now() >= d) {
+            break;
+        }
+        let Some(op) = stream.next() else { break };
+        if token.execute(op).is_ok() {
+            report.succeeded += 1;
+        }
+        report.ops += 1;
+
+        if report.ops.is_multiple_of(config.check_every.max(1)) {
+            token.check_invariants().map_err(|d| fail(report.ops, d))?;
+        }
+        if report.ops.is_multiple_of(config.reconcile_every.max(1)) {
+            shadow
+                .reconcile(&mut token)
+                .map_err(|d| fail(report.ops, d))?;
+        }
+    }
+
+    token.check_invariants().map_err(|d| fail(report.ops, d))?;
+    shadow
+        .reconcile(&mut token)
+        .map_err(|d| fail(report.ops, d))?;
+    Ok(report)
+}
+
+/// Applies `ops` to a fresh genesis state, then runs both checks once.
+///
+/// Used to confirm that a (possibly hand-edited) reproducer still fails.
+pub fn replay(config: &SoakConfig, ops: &[Operation]) -> Result<(), String> {
+    let mut token = genesis(config);
+    let mut shadow = Shadow::new(&token);
+    for op in ops {
+        let _ = token.execute(op.clone());
+    }
+    token.check_invariants()?;
+    shadow.reconcile(&mut token)
+}
+
+/// The state every run and replay starts from.
+pub fn genesis(config: &SoakConfig) -> TokenState {
+    let addresses = addresses(config.accounts);
+    // 첫 계정이 owner: operations()가 만드는 Mint의 caller와 같음
+    let owner = addresses.first().cloned();
+    let balances: HashMap<Address, Balance> = addresses
+        .into_iter()
+        .map(|address| (address, GENESIS_BALANCE))
+        .collect();
+    let supply = GENESIS_BALANCE * config.accounts as Balance;
+    let token = TokenState::from_parts(balances, supply, config.token.clone());
+    match owner {
+        Some(owner) => token.with_owner(owner),
+        None => token,
+    }
+}
+
+/// Endless, reproducible stream of operations over `accounts` addresses
+/// (empty with fewer than two accounts).
+///
+/// Mostly realistic ERC-20 traffic from [`Workload`], mixed with a steady
+/// share of supply, allowance-adjustment, and admin operations.
+pub fn operations(seed: u64, accounts: usize) -> impl Iterator<Item = Operation> {
+    let workload = Workload::new(
+        WorkloadConfig {
+            accounts,
+            ..WorkloadConfig::default()
+        },
+        seed,
+    );
+    let addresses = workload.addresses().to_vec();
+    let mut rng = Rng::new(seed.rotate_left(17) ^ 0x50A4);
+    let mut workload = workload;
+
+    std::iter::from_fn(move || {
+        let (actor, action) = workload.next()?;
+        let pick = |rng: &mut Rng| addresses[rng.below(addresses.len() as u64) as usize].clone();
+        let amount = rng.below(10_000) as Balance;
+
+        let op = match rng.below(100) {
+            0..85 => from_action(actor, action),
+            85..88 => Operation::Mint {
+                caller: addresses[0].clone(),
+                to: actor,
+                amount,
+            },
+            88..91 => Operation::Burn {
+                from: actor,
+                amount,
+            },
+            91..94 => Operation::IncreaseAllowance {
+                owner: actor,
+                spender: pick(&mut rng),
+                added: amount,
+            },
+            94..96 => Operation::DecreaseAllowance {
+                owner: actor,
+                spender: pick(&mut rng),
+                subtracted: amount,
+            },
+            96..98 => Operation::BurnFrom {
+                spender: actor,
+                from: pick(&mut rng),
+                amount,
+            },
+            98 => Operation::MarkNonCirculating {
+                caller: addresses[0].clone(),
+                address: actor,
+            },
+            _ => Operation::UnmarkNonCirculating {
+                caller: addresses[0].clone(),
+                address: actor,
+            },
+        };
+        Some(op)
+    })
+}
+
+fn addresses(accounts: usize) -> Vec<Address> {
+    Workload::new(
+        WorkloadConfig {
+            accounts,
+            ..WorkloadConfig::default()
+        },
+        0,
+    )
+    .addresses()
+    .to_vec()
+}
+
+fn from_action(actor: Address, action: Action) -> Operation {
+    match action {
+        Action::Transfer { to, amount } => Operation::Transfer {
+            from: actor,
+            to,
+            amount,
+        },
+        Action::Approve { spender, amount } => Operation::Approve {
+            owner: actor,
+            spender,
+            amount,
+        },
+        Action::TransferFrom { from, to, amount } => Operation::TransferFrom {
+            spender: actor,
+            from,
+            to,
+            amount,
+        },
+    }
+}
+
+// 이벤트만으로 재구성한 독립 장부: 이벤트 누락/오기록을 잡아냄
+struct Shadow {
+    balances: HashMap<Address, Balance>,
+    supply: Balance,
+}
+
+impl Shadow {
+    fn new(token: &TokenState) -> Self {
+        Self {
+            balances: token.balances.clone(),
+            supply: token.total_supply,
+        }
+    }
+
+    fn reconcile(&mut self, token: &mut TokenState) -> Result<(), String> {
+        for (seq, event) in token.drain_events() {
+            self.apply(&event)
+                .map_err(|d| format!("event #{seq} {event:?}: {d}"))?;
+        }
+
+        if self.supply != token.total_supply {
+            return Err(format!(
+                "events imply supply {}, state has {}",
+                self.supply, token.total_supply
+            ));
+        }
+        for (address, balance) in &token.balances {
+            let expected = self.balances.get(address).copied().unwrap_or(0);
+            if expected != *balance {
+                return Err(format!(
+                    "{address}: events imply {expected}, state has {balance}"
+                ));
+            }
+        }
+        for (address, expected) in &self.balances {
+            if *expected != 0 && !token.balances.contains_key(address) {
+                return Err(format!(
+                    "{address}: events imply {expected}, state has none"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, event: &TokenEvent) -> Result<(), String> {
+        match event {
+            TokenEvent::Transfer { from, to, amount } => {
+                self.debit(from, *amount)?;
+                self.credit(to, *amount);
+            }
+            TokenEvent::Approval { .. }
+            | TokenEvent::AllowanceRevoked { .. }
+            | TokenEvent::AllowanceConsumed { .. }
+            | TokenEvent::CapabilityGranted { .. }
+            | TokenEvent::CapabilityRedeemed { .. }
+            | TokenEvent::CapabilityRevoked { .. }
+            | TokenEvent::Locked { .. }
+            | TokenEvent::Unlocked { .. } => {}
+            TokenEvent::Mint { to, amount } => {
+                self.credit(to, *amount);
+                self.supply += amount;
+            }
+            TokenEvent::Burn { from, amount } => {
+                self.debit(from, *amount)?;
+                self.supply = self.supply.checked_sub(*amount).ok_or("supply underflow")?;
+            }
+            TokenEvent::Shield { from, amount } => self.debit(from, *amount)?,
+            TokenEvent::EscrowCreated { payer, amount, .. } => self.debit(payer, *amount)?,
+            TokenEvent::EscrowReleased { to, amount, .. }
+            | TokenEvent::EscrowRefunded { to, amount, .. } => self.credit(to, *amount),
+            TokenEvent::Unshield { to, amount } => self.credit(to, *amount),
+            TokenEvent::ConfigChanged { .. }
+            | TokenEvent::ConfigUpdateQueued { .. }
+            | TokenEvent::ConfigUpdateExecuted { .. }
+            | TokenEvent::ConfigUpdateCancelled { .. }
+            | TokenEvent::Paused { .. }
+            | TokenEvent::Unpaused { .. }
+            | TokenEvent::OwnershipTransferred { .. }
+            | TokenEvent::OwnershipTransferStarted { .. }
+            | TokenEvent::OwnershipRenounced { .. }
+            | TokenEvent::AccountFrozen { .. }
+            | TokenEvent::AccountUnfrozen { .. }
+            | TokenEvent::RoleGranted { .. }
+            | TokenEvent::RoleRevoked { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn credit(&mut self, address: &Address, amount: Balance) {
+        *self.balances.entry(address.clone()).or_insert(0) += amount;
+    }
+
+    fn debit(&mut self, address: &Address, amount: Balance) -> Result<(), String> {
+        let balance = self.balances.entry(address.clone()).or_insert(0);
+        *balance = balance
+            .checked_sub(amount)
+            .ok_or_else(|| format!("{address} debited {amount} with only {balance}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DustPolicy;
+
+    fn quick(seed: u64) -> SoakConfig {
+        SoakConfig {
+            seed,
+            accounts: 20,
+            max_ops: 5_000,
+            check_every: 100,
+            reconcile_every: 500,
+            ..SoakConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_short_soak_is_clean() {
+        let report = run(&quick(1)).unwrap();
+
+        assert_eq!(report.ops, 5_000);
+        assert!(report.succeeded > 2_500, "{report:?}");
+    }
+
+    #[test]
+    fn test_soak_with_dust_rules_is_clean() {
+        let config = SoakConfig {
+            token: TokenConfig {
+                existential_deposit: 50,
+                dust_policy: DustPolicy::Sweep,
+                ..TokenConfig::default()
+            },
+            ..quick(2)
+        };
+
+        run(&config).unwrap();
+    }
+
+    #[test]
+    fn test_operation_stream_is_reproducible() {
+        let a: Vec<_> = operations(9, 10).take(500).collect();
+        let b: Vec<_> = operations(9, 10).take(500).collect();
+
+        assert_eq!(a, b);
+        assert_eq!(operations(9, 1).count(), 0);
+    }
+
+    #[test]
+    fn test_reproducer_is_the_executed_prefix() {
+        let config = quick(3);
+        let failure = SoakFailure {
+            seed: config.seed,
+            accounts: config.accounts,
+            ops: 250,
+            detail: String::new(),
+        };
+
+        let ops = failure.reproducer();
+
+        assert_eq!(ops.len(), 250);
+        assert_eq!(ops, operations(3, 20).take(250).collect::<Vec<_>>());
+        assert_eq!(replay(&config, &ops), Ok(()));
+    }
+
+    #[test]
+    fn test_shadow_catches_unlogged_change() {
+        let config = quick(4);
+        let mut token = genesis(&config);
+        let mut shadow = Shadow::new(&token);
+
+        token.balances.insert(Address::new("addr0").unwrap(), 1);
+
+        assert!(shadow.reconcile(&mut token).is_err());
+    }
+}
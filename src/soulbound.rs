@@ -0,0 +1,79 @@
+//! Non-transferable (soulbound) tokens.
+//!
+//! With [`TokenConfig::soulbound`](crate::TokenConfig::soulbound) set, tokens
+//! stay with the account they were minted to, as credentials and
+//! reputation scores must. Minting and burning work as usual, but any
+//! operation that would hand tokens to another account fails with
+//! [`TokenError::NonTransferable`]: transfers of every kind, capability
+//! redemption, shielding and opening an escrow. Escrows and notes created
+//! before the switch can still be settled and unshielded, so no funds are
+//! stranded.
+//!
+//! The flag is ordinary configuration: a token can be built soulbound with
+//! [`TokenConfig::soulbound()`](crate::TokenConfig::soulbound) or switched
+//! later with `update_config`.
+
+use crate::operation::Operation;
+use crate::{TokenError, TokenState};
+
+impl TokenState {
+    pub(crate) fn check_transferable(&self, op: &Operation) -> Result<(), TokenError> {
+        let moves_tokens = matches!(
+            op,
+            Operation::Transfer { .. }
+                | Operation::NoncedTransfer { .. }
+                | Operation::TransferFrom { .. }
+                | Operation::TransferFromBatch { .. }
+                | Operation::RedeemCapability { .. }
+                | Operation::Shield { .. }
+                | Operation::EscrowCreate { .. }
+        );
+        if self.config.soulbound && moves_tokens {
+            return Err(TokenError::NonTransferable);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::addresses;
+    use crate::{TokenConfig, TokenError, TokenState};
+
+    #[test]
+    fn test_soulbound_tokens_only_mint_and_burn() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::with_config(alice.clone(), 1000, TokenConfig::soulbound());
+        token.approve(&alice, &bob, 100).unwrap();
+
+        let sent = token.transfer(&alice, &bob, 10);
+        let delegated = token.transfer_from(&bob, &alice, &bob, 10);
+        let escrowed = token.escrow_create(&alice, &bob, 10);
+        token.mint(&alice, &bob, 50).unwrap();
+        token.burn(&bob, 20).unwrap();
+
+        assert_eq!(sent, Err(TokenError::NonTransferable));
+        assert_eq!(delegated, Err(TokenError::NonTransferable));
+        assert_eq!(escrowed.map(|_| ()), Err(TokenError::NonTransferable));
+        assert_eq!(token.balance_of(&alice), 1000);
+        assert_eq!(token.balance_of(&bob), 30);
+    }
+
+    #[test]
+    fn test_open_escrow_settles_after_switch() {
+        let [alice, bob] = addresses(["alice", "bob"]);
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let id = token.escrow_create(&alice, &bob, 100).unwrap();
+
+        token
+            .update_config(&alice, TokenConfig::soulbound())
+            .unwrap();
+        token.escrow_release(&alice, id).unwrap();
+
+        assert_eq!(token.balance_of(&bob), 100);
+        assert_eq!(
+            token.transfer(&bob, &alice, 100),
+            Err(TokenError::NonTransferable)
+        );
+    }
+}
@@ -0,0 +1,1024 @@
+//! Machine-readable description of the ledger as a transition system.
+//!
+//! [`transition_system`] lists the state variables and, for every
+//! [`Operation`], its parameters, the guards that must hold for it to
+//! succeed and its effects on the state; [`to_json`] renders that as a
+//! small JSON IR for model checkers. Guards and effects are written in a
+//! TLA+-flavoured notation (`balances[from]`, `x' = ...`); `locked(a, now)`
+//! is the sum of `a`'s locks whose `unlock_at` is still ahead, and
+//! `fee(x)` is what `config.fee` charges on a transfer of `x`.
+//!
+//! The pause, freeze and soulbound guards are not written by hand: they
+//! are read off the same `check_not_paused`, `check_not_frozen` and
+//! `check_transferable` functions `execute` runs, by probing each
+//! operation against a paused, soulbound ledger and matching the parties
+//! those checks see back to parameter names. Everything else
+//! comes from the exhaustive match in `describe`, which fails to compile
+//! when a variant or field is added or renamed.
+//!
+//! Guards are listed in the order the implementation checks them, after
+//! the pause, freeze and soulbound guards that `execute` applies first.
+
+use crate::config::ConfigUpdateId;
+use crate::escrow::EscrowId;
+use crate::ocap::{Capability, CapabilityId};
+use crate::operation::Operation;
+use crate::shielded::Note;
+use crate::{Address, Role, TokenConfig, TokenState};
+
+/// A variable of the ledger state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateVar {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+/// One operation: when it may fire and what it changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub name: &'static str,
+    pub params: Vec<(&'static str, &'static str)>,
+    pub guards: Vec<String>,
+    pub effects: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionSystem {
+    pub state: Vec<StateVar>,
+    pub transitions: Vec<Transition>,
+}
+
+const STATE: &[(&str, &str)] = &[
+    ("balances", "Address -> Balance"),
+    ("allowances", "Address x Address -> Balance"),
+    ("allowance_expiry", "Address x Address -> u64 | None"),
+    ("total_supply", "Balance"),
+    ("max_supply", "Balance | None"),
+    ("shielded_pool", "Balance"),
+    ("open_notes", "u64 -> Balance"),
+    ("spent_notes", "Set(u64)"),
+    ("next_note_id", "u64"),
+    ("non_circulating", "Set(Address)"),
+    ("config", "TokenConfig"),
+    ("metadata", "TokenMetadata | None"),
+    ("owner", "Address | None"),
+    ("pending_owner", "Address | None"),
+    ("paused", "Bool"),
+    ("frozen", "Set(Address)"),
+    ("roles", "Set(Address x Role)"),
+    ("nonces", "Address -> u64"),
+    ("domain_separator", "Bytes32 | None"),
+    ("capabilities", "CapabilityId -> Capability"),
+    ("next_capability_id", "u64"),
+    ("locks", "Address -> Seq(Balance x u64)"),
+    ("escrows", "EscrowId -> Escrow"),
+    ("next_escrow_id", "u64"),
+    ("config_updates", "ConfigUpdateId -> QueuedConfig"),
+    ("next_config_update_id", "u64"),
+    ("next_seq", "u64"),
+];
+
+// 정적 설명: 이름, 파라미터, (pause/freeze 외) 가드, 효과
+type Description = (
+    &'static str,
+    Vec<(&'static str, &'static str)>,
+    Vec<&'static str>,
+    Vec<&'static str>,
+);
+
+/// Describes every operation the ledger accepts.
+pub fn transition_system() -> TransitionSystem {
+    let probe = probe();
+    TransitionSystem {
+        state: STATE
+            .iter()
+            .map(|&(name, ty)| StateVar { name, ty })
+            .collect(),
+        transitions: samples()
+            .iter()
+            .map(|op| {
+                let (name, params, guards, effects) = describe(op);
+                let mut all = Vec::new();
+                if probe.check_not_paused(op).is_err() {
+                    all.push("~paused".to_string());
+                }
+                for party in op.senders().into_iter().chain(op.recipients(&probe)) {
+                    let guard = format!("{} \\notin frozen", party.as_str());
+                    if !all.contains(&guard) {
+                        all.push(guard);
+                    }
+                }
+                if probe.check_transferable(op).is_err() {
+                    all.push("~config.soulbound".to_string());
+                }
+                all.extend(guards.iter().map(|g| g.to_string()));
+                Transition {
+                    name,
+                    params,
+                    guards: all,
+                    effects,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Renders `system` as JSON.
+pub fn to_json(system: &TransitionSystem) -> String {
+    let strings =
+        |items: &mut dyn Iterator<Item = &str>| items.map(quote).collect::<Vec<_>>().join(", ");
+    let state = system
+        .state
+        .iter()
+        .map(|v| format!("{{\"name\": {}, \"type\": {}}}", quote(v.name), quote(v.ty)))
+        .collect::<Vec<_>>()
+        .join(",\n    ");
+    let transitions = system
+        .transitions
+        .iter()
+        .map(|t| {
+            let params = t
+                .params
+                .iter()
+                .map(|(n, ty)| format!("{{\"name\": {}, \"type\": {}}}", quote(n), quote(ty)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{{\"name\": {}, \"params\": [{params}], \"guards\": [{}], \"effects\": [{}]}}",
+                quote(t.name),
+                strings(&mut t.guards.iter().map(String::as_str)),
+                strings(&mut t.effects.iter().copied()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n    ");
+    format!(
+        "{{\n  \"state\": [\n    {state}\n  ],\n  \"transitions\": [\n    {transitions}\n  ]\n}}\n"
+    )
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// pause 검사가 걸리는지 보기 위한 상태
+fn probe() -> TokenState {
+    let mut token = TokenState::from_parts(Default::default(), 0, TokenConfig::soulbound());
+    token.paused = true;
+    token
+}
+
+// 각 변형의 표본: 주소는 파라미터 이름을 그대로 사용해 freeze 검사 결과를 역추적
+fn samples() -> Vec<Operation> {
+    let a = |name: &str| Address::new(name).unwrap();
+    vec![
+        Operation::Transfer {
+            from: a("from"),
+            to: a("to"),
+            amount: 0,
+        },
+        Operation::NoncedTransfer {
+            from: a("from"),
+            to: a("to"),
+            amount: 0,
+            nonce: 0,
+        },
+        Operation::Approve {
+            owner: a("owner"),
+            spender: a("spender"),
+            amount: 0,
+        },
+        Operation::Permit {
+            owner: a("owner"),
+            spender: a("spender"),
+            amount: 0,
+            nonce: 0,
+        },
+        Operation::ApproveUntil {
+            owner: a("owner"),
+            spender: a("spender"),
+            amount: 0,
+            expires_at: 0,
+        },
+        Operation::ApproveIf {
+            owner: a("owner"),
+            spender: a("spender"),
+            expected_current: 0,
+            new_amount: 0,
+        },
+        Operation::IncreaseAllowance {
+            owner: a("owner"),
+            spender: a("spender"),
+            added: 0,
+        },
+        Operation::DecreaseAllowance {
+            owner: a("owner"),
+            spender: a("spender"),
+            subtracted: 0,
+        },
+        Operation::RevokeAllowance {
+            owner: a("owner"),
+            spender: a("spender"),
+        },
+        Operation::RevokeAll { owner: a("owner") },
+        Operation::TransferFrom {
+            spender: a("spender"),
+            from: a("from"),
+            to: a("to"),
+            amount: 0,
+        },
+        Operation::GrantCapability {
+            issuer: a("issuer"),
+            limit: 0,
+            recipients: [a("recipients_address")].into(),
+            expires_at: None,
+            digest: [0; 32],
+        },
+        Operation::RedeemCapability {
+            capability: Capability {
+                id: CapabilityId(0),
+                secret: [0; 32],
+            },
+            to: a("to"),
+            amount: 0,
+        },
+        Operation::RevokeCapability {
+            issuer: a("issuer"),
+            id: CapabilityId(0),
+        },
+        Operation::Shield {
+            from: a("from"),
+            amount: 0,
+        },
+        Operation::Unshield {
+            to: a("to"),
+            note: Note { id: 0, amount: 0 },
+            proof: Vec::new(),
+        },
+        Operation::BulkCredit {
+            caller: a("caller"),
+            entries: vec![(a("entries_address"), 0)],
+        },
+        Operation::Mint {
+            caller: a("caller"),
+            to: a("to"),
+            amount: 0,
+        },
+        Operation::Burn {
+            from: a("from"),
+            amount: 0,
+        },
+        Operation::TransferFromBatch {
+            spender: a("spender"),
+            legs: vec![(a("legs_from"), a("legs_to"), 0)],
+        },
+        Operation::BurnFrom {
+            spender: a("spender"),
+            from: a("from"),
+            amount: 0,
+        },
+        Operation::Lock {
+            owner: a("owner"),
+            amount: 0,
+            unlock_at: 0,
+        },
+        Operation::Unlock { owner: a("owner") },
+        Operation::EscrowCreate {
+            payer: a("payer"),
+            payee: a("payee"),
+            amount: 0,
+        },
+        Operation::EscrowRelease {
+            caller: a("caller"),
+            id: EscrowId(0),
+        },
+        Operation::EscrowRefund {
+            caller: a("caller"),
+            id: EscrowId(0),
+        },
+        Operation::MarkNonCirculating {
+            caller: a("caller"),
+            address: a("address"),
+        },
+        Operation::UnmarkNonCirculating {
+            caller: a("caller"),
+            address: a("address"),
+        },
+        Operation::UpdateConfig {
+            caller: a("caller"),
+            config: TokenConfig::default(),
+        },
+        Operation::QueueConfigUpdate {
+            caller: a("caller"),
+            config: TokenConfig::default(),
+        },
+        Operation::ExecuteConfigUpdate {
+            caller: a("caller"),
+            id: ConfigUpdateId(0),
+        },
+        Operation::CancelConfigUpdate {
+            caller: a("caller"),
+            id: ConfigUpdateId(0),
+        },
+        Operation::Pause {
+            caller: a("caller"),
+        },
+        Operation::Unpause {
+            caller: a("caller"),
+        },
+        Operation::TransferOwnership {
+            caller: a("caller"),
+            new_owner: a("new_owner"),
+        },
+        Operation::AcceptOwnership {
+            caller: a("caller"),
+        },
+        Operation::RenounceOwnership {
+            caller: a("caller"),
+        },
+        Operation::FreezeAccount {
+            caller: a("caller"),
+            address: a("address"),
+        },
+        Operation::UnfreezeAccount {
+            caller: a("caller"),
+            address: a("address"),
+        },
+        Operation::GrantRole {
+            caller: a("caller"),
+            role: Role::Minter,
+            account: a("account"),
+        },
+        Operation::RevokeRole {
+            caller: a("caller"),
+            role: Role::Minter,
+            account: a("account"),
+        },
+    ]
+}
+
+fn describe(op: &Operation) -> Description {
+    const TRANSFER_EFFECTS: &[&str] = &[
+        "to \\in config.burn_addresses => balances'[from] = balances[from] - amount /\\ total_supply' = total_supply - amount",
+        "to \\notin config.burn_addresses => balances'[from] = balances[from] - amount /\\ balances'[to] = balances[to] + amount - fee(amount)",
+        "to \\notin config.burn_addresses /\\ config.fee_destination = Treasury(t) => balances'[t] = balances[t] + fee(amount)",
+        "to \\notin config.burn_addresses /\\ config.fee_destination = Burn => total_supply' = total_supply - fee(amount)",
+    ];
+    let (name, params, guards, effects): Description = match op {
+        Operation::Transfer {
+            from: _,
+            to: _,
+            amount: _,
+        } => (
+            "Transfer",
+            vec![
+                ("from", "Address"),
+                ("to", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "from /= to \\/ config.self_transfer /= Reject",
+                "amount > 0 \\/ config.zero_amount /= Reject",
+                "balances[from] - locked(from, now) >= amount",
+                "balances[from] - amount = 0 \\/ balances[from] - amount >= config.existential_deposit \\/ (config.dust_policy = Sweep /\\ locked(from, now) = 0)",
+                "to \\in config.burn_addresses \\/ fee(amount) <= amount",
+                "balances[to] + amount - fee(amount) >= config.existential_deposit",
+            ],
+            TRANSFER_EFFECTS.to_vec(),
+        ),
+        Operation::NoncedTransfer {
+            from: _,
+            to: _,
+            amount: _,
+            nonce: _,
+        } => (
+            "NoncedTransfer",
+            vec![
+                ("from", "Address"),
+                ("to", "Address"),
+                ("amount", "Balance"),
+                ("nonce", "u64"),
+            ],
+            vec!["nonce = nonces[from]", "Transfer(from, to, amount) guards"],
+            [TRANSFER_EFFECTS, &["nonces'[from] = nonces[from] + 1"]].concat(),
+        ),
+        Operation::Approve {
+            owner: _,
+            spender: _,
+            amount: _,
+        } => (
+            "Approve",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec!["owner /= spender"],
+            vec![
+                "allowances'[owner, spender] = amount",
+                "allowance_expiry'[owner, spender] = None",
+            ],
+        ),
+        Operation::Permit {
+            owner: _,
+            spender: _,
+            amount: _,
+            nonce: _,
+        } => (
+            "Permit",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("amount", "Balance"),
+                ("nonce", "u64"),
+            ],
+            vec!["nonce = nonces[owner]", "owner /= spender"],
+            vec![
+                "allowances'[owner, spender] = amount",
+                "allowance_expiry'[owner, spender] = None",
+                "nonces'[owner] = nonces[owner] + 1",
+            ],
+        ),
+        Operation::ApproveUntil {
+            owner: _,
+            spender: _,
+            amount: _,
+            expires_at: _,
+        } => (
+            "ApproveUntil",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("amount", "Balance"),
+                ("expires_at", "u64"),
+            ],
+            vec!["owner /= spender"],
+            vec![
+                "allowances'[owner, spender] = amount",
+                "allowance_expiry'[owner, spender] = expires_at",
+            ],
+        ),
+        Operation::ApproveIf {
+            owner: _,
+            spender: _,
+            expected_current: _,
+            new_amount: _,
+        } => (
+            "ApproveIf",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("expected_current", "Balance"),
+                ("new_amount", "Balance"),
+            ],
+            vec![
+                "allowances[owner, spender] = expected_current",
+                "owner /= spender",
+            ],
+            vec![
+                "allowances'[owner, spender] = new_amount",
+                "allowance_expiry'[owner, spender] = None",
+            ],
+        ),
+        Operation::IncreaseAllowance {
+            owner: _,
+            spender: _,
+            added: _,
+        } => (
+            "IncreaseAllowance",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("added", "Balance"),
+            ],
+            vec![
+                "allowance_expiry[owner, spender] = None \\/ now <= allowance_expiry[owner, spender]",
+                "allowances[owner, spender] + added <= MAX",
+                "owner /= spender",
+            ],
+            vec!["allowances'[owner, spender] = allowances[owner, spender] + added"],
+        ),
+        Operation::DecreaseAllowance {
+            owner: _,
+            spender: _,
+            subtracted: _,
+        } => (
+            "DecreaseAllowance",
+            vec![
+                ("owner", "Address"),
+                ("spender", "Address"),
+                ("subtracted", "Balance"),
+            ],
+            vec![
+                "allowance_expiry[owner, spender] = None \\/ now <= allowance_expiry[owner, spender]",
+                "allowances[owner, spender] >= subtracted",
+                "owner /= spender",
+            ],
+            vec!["allowances'[owner, spender] = allowances[owner, spender] - subtracted"],
+        ),
+        Operation::RevokeAllowance {
+            owner: _,
+            spender: _,
+        } => (
+            "RevokeAllowance",
+            vec![("owner", "Address"), ("spender", "Address")],
+            vec![],
+            vec!["allowances'[owner, spender] = 0"],
+        ),
+        Operation::RevokeAll { owner: _ } => (
+            "RevokeAll",
+            vec![("owner", "Address")],
+            vec![],
+            vec![
+                "\\A s: allowances'[owner, s] = 0",
+                "\\A c: capabilities[c].issuer = owner => c \\notin DOMAIN capabilities'",
+            ],
+        ),
+        Operation::TransferFrom {
+            spender: _,
+            from: _,
+            to: _,
+            amount: _,
+        } => (
+            "TransferFrom",
+            vec![
+                ("spender", "Address"),
+                ("from", "Address"),
+                ("to", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "allowance_expiry[from, spender] = None \\/ now <= allowance_expiry[from, spender]",
+                "allowances[from, spender] >= amount",
+                "Transfer(from, to, amount) guards, without dust sweep",
+            ],
+            [
+                TRANSFER_EFFECTS,
+                &["allowances'[from, spender] = allowances[from, spender] - amount"],
+            ]
+            .concat(),
+        ),
+        Operation::GrantCapability {
+            issuer: _,
+            limit: _,
+            recipients: _,
+            expires_at: _,
+            digest: _,
+        } => (
+            "GrantCapability",
+            vec![
+                ("issuer", "Address"),
+                ("limit", "Balance"),
+                ("recipients", "Set(Address)"),
+                ("expires_at", "u64 | None"),
+                ("digest", "Bytes32"),
+            ],
+            vec![],
+            vec![
+                "capabilities'[next_capability_id] = [issuer |-> issuer, remaining |-> limit, recipients |-> recipients, expires_at |-> expires_at, digest |-> digest]",
+                "next_capability_id' = next_capability_id + 1",
+            ],
+        ),
+        Operation::RedeemCapability {
+            capability: _,
+            to: _,
+            amount: _,
+        } => (
+            "RedeemCapability",
+            vec![
+                ("capability", "Capability"),
+                ("to", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "capability.id \\in DOMAIN capabilities /\\ SHA256(capability.secret) = capabilities[capability.id].digest",
+                "capabilities[capability.id].expires_at = None \\/ now <= capabilities[capability.id].expires_at",
+                "capabilities[capability.id].recipients = {} \\/ to \\in capabilities[capability.id].recipients",
+                "capabilities[capability.id].remaining >= amount",
+                "capabilities[capability.id].issuer \\notin frozen",
+                "Transfer(capabilities[capability.id].issuer, to, amount) guards, without dust sweep",
+            ],
+            vec![
+                "Transfer(capabilities[capability.id].issuer, to, amount) effects",
+                "capabilities'[capability.id].remaining = capabilities[capability.id].remaining - amount",
+            ],
+        ),
+        Operation::RevokeCapability { issuer: _, id: _ } => (
+            "RevokeCapability",
+            vec![("issuer", "Address"), ("id", "u64")],
+            vec![
+                "id \\in DOMAIN capabilities",
+                "capabilities[id].issuer = issuer",
+            ],
+            vec!["capabilities' = [i \\in DOMAIN capabilities \\ {id} |-> capabilities[i]]"],
+        ),
+        Operation::Shield { from: _, amount: _ } => (
+            "Shield",
+            vec![("from", "Address"), ("amount", "Balance")],
+            vec![
+                "amount > 0",
+                "balances[from] - locked(from, now) >= amount",
+                "balances[from] - amount = 0 \\/ balances[from] - amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[from] = balances[from] - amount",
+                "shielded_pool' = shielded_pool + amount",
+                "open_notes'[next_note_id] = amount",
+                "next_note_id' = next_note_id + 1",
+            ],
+        ),
+        Operation::Unshield {
+            to: _,
+            note: _,
+            proof: _,
+        } => (
+            "Unshield",
+            vec![("to", "Address"), ("note", "Note"), ("proof", "Bytes")],
+            vec![
+                "verifier accepts (note, proof)",
+                "note.id \\notin spent_notes",
+                "note.id \\in DOMAIN open_notes /\\ open_notes[note.id] = note.amount",
+                "balances[to] + note.amount <= MAX",
+                "balances[to] + note.amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[to] = balances[to] + note.amount",
+                "shielded_pool' = shielded_pool - note.amount",
+                "spent_notes' = spent_notes \\cup {note.id}",
+                "open_notes' = [i \\in DOMAIN open_notes \\ {note.id} |-> open_notes[i]]",
+            ],
+        ),
+        Operation::BulkCredit {
+            caller: _,
+            entries: _,
+        } => (
+            "BulkCredit",
+            vec![("caller", "Address"), ("entries", "Seq(Address x Balance)")],
+            vec![
+                "caller = owner \\/ (caller, Minter) \\in roles",
+                "total_supply + Sum(entries) <= MAX",
+                "max_supply = None \\/ total_supply + Sum(entries) <= max_supply",
+                "\\A a \\in credited(entries): balances[a] + credit(entries, a) >= config.existential_deposit",
+            ],
+            vec![
+                "\\A (a, n) \\in entries: balances'[a] = balances[a] + n",
+                "total_supply' = total_supply + Sum(entries)",
+            ],
+        ),
+        Operation::Mint {
+            caller: _,
+            to: _,
+            amount: _,
+        } => (
+            "Mint",
+            vec![
+                ("caller", "Address"),
+                ("to", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "caller = owner \\/ (caller, Minter) \\in roles",
+                "amount > 0 \\/ config.zero_amount /= Reject",
+                "total_supply + amount <= MAX",
+                "max_supply = None \\/ total_supply + amount <= max_supply",
+                "balances[to] + amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[to] = balances[to] + amount",
+                "total_supply' = total_supply + amount",
+            ],
+        ),
+        Operation::Burn { from: _, amount: _ } => (
+            "Burn",
+            vec![("from", "Address"), ("amount", "Balance")],
+            vec![
+                "amount > 0 \\/ config.zero_amount /= Reject",
+                "balances[from] - locked(from, now) >= amount",
+                "balances[from] - amount = 0 \\/ balances[from] - amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[from] = balances[from] - amount",
+                "total_supply' = total_supply - amount",
+            ],
+        ),
+        Operation::TransferFromBatch {
+            spender: _,
+            legs: _,
+        } => (
+            "TransferFromBatch",
+            vec![
+                ("spender", "Address"),
+                ("legs", "Seq(Address x Address x Balance)"),
+            ],
+            vec!["every leg, applied in order, satisfies TransferFrom(spender, leg) guards"],
+            vec!["TransferFrom(spender, leg) effects for every leg, or none"],
+        ),
+        Operation::BurnFrom {
+            spender: _,
+            from: _,
+            amount: _,
+        } => (
+            "BurnFrom",
+            vec![
+                ("spender", "Address"),
+                ("from", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "amount > 0 \\/ config.zero_amount /= Reject",
+                "allowance_expiry[from, spender] = None \\/ now <= allowance_expiry[from, spender]",
+                "allowances[from, spender] >= amount",
+                "Burn(from, amount) guards",
+            ],
+            vec![
+                "balances'[from] = balances[from] - amount",
+                "total_supply' = total_supply - amount",
+                "allowances'[from, spender] = allowances[from, spender] - amount",
+            ],
+        ),
+        Operation::Lock {
+            owner: _,
+            amount: _,
+            unlock_at: _,
+        } => (
+            "Lock",
+            vec![
+                ("owner", "Address"),
+                ("amount", "Balance"),
+                ("unlock_at", "u64"),
+            ],
+            vec![
+                "amount > 0",
+                "balances[owner] - locked(owner, now) >= amount",
+            ],
+            vec![
+                "locks'[owner] = Append(locks[owner], [amount |-> amount, unlock_at |-> unlock_at])",
+            ],
+        ),
+        Operation::Unlock { owner: _ } => (
+            "Unlock",
+            vec![("owner", "Address")],
+            vec!["\\E l \\in locks[owner]: now >= l.unlock_at"],
+            vec!["locks'[owner] = SelectSeq(locks[owner], LAMBDA l: now < l.unlock_at)"],
+        ),
+        Operation::EscrowCreate {
+            payer: _,
+            payee: _,
+            amount: _,
+        } => (
+            "EscrowCreate",
+            vec![
+                ("payer", "Address"),
+                ("payee", "Address"),
+                ("amount", "Balance"),
+            ],
+            vec![
+                "amount > 0",
+                "payer /= payee",
+                "payee \\notin frozen",
+                "payee \\notin config.burn_addresses",
+                "balances[payer] - locked(payer, now) >= amount",
+                "balances[payer] - amount = 0 \\/ balances[payer] - amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[payer] = balances[payer] - amount",
+                "escrows'[next_escrow_id] = [payer |-> payer, payee |-> payee, amount |-> amount]",
+                "next_escrow_id' = next_escrow_id + 1",
+            ],
+        ),
+        Operation::EscrowRelease { caller: _, id: _ } => (
+            "EscrowRelease",
+            vec![("caller", "Address"), ("id", "u64")],
+            vec![
+                "id \\in DOMAIN escrows",
+                "caller = escrows[id].payer",
+                "escrows[id].payee \\notin frozen",
+                "balances[escrows[id].payee] + escrows[id].amount <= MAX",
+                "balances[escrows[id].payee] + escrows[id].amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[escrows[id].payee] = balances[escrows[id].payee] + escrows[id].amount",
+                "escrows' = [i \\in DOMAIN escrows \\ {id} |-> escrows[i]]",
+            ],
+        ),
+        Operation::EscrowRefund { caller: _, id: _ } => (
+            "EscrowRefund",
+            vec![("caller", "Address"), ("id", "u64")],
+            vec![
+                "id \\in DOMAIN escrows",
+                "caller = escrows[id].payee \\/ (escrows[id].payee \\in frozen /\\ (caller = owner \\/ (caller, Freezer) \\in roles))",
+                "escrows[id].payer \\notin frozen",
+                "balances[escrows[id].payer] + escrows[id].amount <= MAX",
+                "balances[escrows[id].payer] + escrows[id].amount >= config.existential_deposit",
+            ],
+            vec![
+                "balances'[escrows[id].payer] = balances[escrows[id].payer] + escrows[id].amount",
+                "escrows' = [i \\in DOMAIN escrows \\ {id} |-> escrows[i]]",
+            ],
+        ),
+        Operation::MarkNonCirculating {
+            caller: _,
+            address: _,
+        } => (
+            "MarkNonCirculating",
+            vec![("caller", "Address"), ("address", "Address")],
+            vec!["caller = owner \\/ (caller, Admin) \\in roles"],
+            vec!["non_circulating' = non_circulating \\cup {address}"],
+        ),
+        Operation::UnmarkNonCirculating {
+            caller: _,
+            address: _,
+        } => (
+            "UnmarkNonCirculating",
+            vec![("caller", "Address"), ("address", "Address")],
+            vec!["caller = owner \\/ (caller, Admin) \\in roles"],
+            vec!["non_circulating' = non_circulating \\ {address}"],
+        ),
+        Operation::UpdateConfig {
+            caller: _,
+            config: _,
+        } => (
+            "UpdateConfig",
+            vec![("caller", "Address"), ("config", "TokenConfig")],
+            vec![
+                "caller = owner \\/ (caller, Admin) \\in roles",
+                "config.update_delay = 0",
+                "\\A a: balances[a] = 0 \\/ balances[a] >= config.existential_deposit",
+            ],
+            vec!["config' = config"],
+        ),
+        Operation::QueueConfigUpdate {
+            caller: _,
+            config: _,
+        } => (
+            "QueueConfigUpdate",
+            vec![("caller", "Address"), ("config", "TokenConfig")],
+            vec!["caller = owner \\/ (caller, Admin) \\in roles"],
+            vec![
+                "config_updates'[next_config_update_id] = [config |-> config, eta |-> now + config.update_delay]",
+                "next_config_update_id' = next_config_update_id + 1",
+            ],
+        ),
+        Operation::ExecuteConfigUpdate { caller: _, id: _ } => (
+            "ExecuteConfigUpdate",
+            vec![("caller", "Address"), ("id", "ConfigUpdateId")],
+            vec![
+                "caller = owner \\/ (caller, Admin) \\in roles",
+                "id \\in DOMAIN config_updates",
+                "now >= config_updates[id].eta",
+                "\\A a: balances[a] = 0 \\/ balances[a] >= config_updates[id].config.existential_deposit",
+            ],
+            vec![
+                "config' = config_updates[id].config",
+                "id \\notin DOMAIN config_updates'",
+            ],
+        ),
+        Operation::CancelConfigUpdate { caller: _, id: _ } => (
+            "CancelConfigUpdate",
+            vec![("caller", "Address"), ("id", "ConfigUpdateId")],
+            vec![
+                "caller = owner \\/ (caller, Admin) \\in roles",
+                "id \\in DOMAIN config_updates",
+            ],
+            vec!["id \\notin DOMAIN config_updates'"],
+        ),
+        Operation::Pause { caller: _ } => (
+            "Pause",
+            vec![("caller", "Address")],
+            vec!["caller = owner \\/ (caller, Pauser) \\in roles"],
+            vec!["paused' = TRUE"],
+        ),
+        Operation::Unpause { caller: _ } => (
+            "Unpause",
+            vec![("caller", "Address")],
+            vec!["caller = owner \\/ (caller, Pauser) \\in roles"],
+            vec!["paused' = FALSE"],
+        ),
+        Operation::TransferOwnership {
+            caller: _,
+            new_owner: _,
+        } => (
+            "TransferOwnership",
+            vec![("caller", "Address"), ("new_owner", "Address")],
+            vec!["caller = owner"],
+            vec!["pending_owner' = new_owner"],
+        ),
+        Operation::AcceptOwnership { caller: _ } => (
+            "AcceptOwnership",
+            vec![("caller", "Address")],
+            vec!["caller = pending_owner"],
+            vec!["owner' = caller", "pending_owner' = None"],
+        ),
+        Operation::RenounceOwnership { caller: _ } => (
+            "RenounceOwnership",
+            vec![("caller", "Address")],
+            vec!["caller = owner"],
+            vec!["owner' = None", "pending_owner' = None"],
+        ),
+        Operation::FreezeAccount {
+            caller: _,
+            address: _,
+        } => (
+            "FreezeAccount",
+            vec![("caller", "Address"), ("address", "Address")],
+            vec!["caller = owner \\/ (caller, Freezer) \\in roles"],
+            vec!["frozen' = frozen \\cup {address}"],
+        ),
+        Operation::UnfreezeAccount {
+            caller: _,
+            address: _,
+        } => (
+            "UnfreezeAccount",
+            vec![("caller", "Address"), ("address", "Address")],
+            vec!["caller = owner \\/ (caller, Freezer) \\in roles"],
+            vec!["frozen' = frozen \\ {address}"],
+        ),
+        Operation::GrantRole {
+            caller: _,
+            role: _,
+            account: _,
+        } => (
+            "GrantRole",
+            vec![
+                ("caller", "Address"),
+                ("role", "Role"),
+                ("account", "Address"),
+            ],
+            vec!["caller = owner \\/ (caller, Admin) \\in roles"],
+            vec!["roles' = roles \\cup {(account, role)}"],
+        ),
+        Operation::RevokeRole {
+            caller: _,
+            role: _,
+            account: _,
+        } => (
+            "RevokeRole",
+            vec![
+                ("caller", "Address"),
+                ("role", "Role"),
+                ("account", "Address"),
+            ],
+            vec!["caller = owner \\/ (caller, Admin) \\in roles"],
+            vec!["roles' = roles \\ {(account, role)}"],
+        ),
+    };
+    (
+        name,
+        params,
+        guards,
+        [effects, vec!["next_seq' = next_seq + 1"]].concat(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guards_come_from_execute_checks() {
+        let system = transition_system();
+        let find = |name: &str| system.transitions.iter().find(|t| t.name == name).unwrap();
+
+        let transfer = find("Transfer");
+        let batch = find("TransferFromBatch");
+        let revoke = find("RevokeAllowance");
+        let mint = find("Mint");
+
+        assert_eq!(
+            transfer.guards[..3],
+            ["~paused", "from \\notin frozen", "to \\notin frozen"]
+        );
+        assert_eq!(
+            batch.guards[1..3],
+            ["legs_from \\notin frozen", "legs_to \\notin frozen"]
+        );
+        assert!(!revoke.guards.iter().any(|g| g == "~paused"));
+        assert_eq!(transfer.guards[3], "~config.soulbound");
+        assert!(!mint.guards.iter().any(|g| g == "~config.soulbound"));
+    }
+
+    #[test]
+    fn test_every_sample_is_described_once_as_json() {
+        let system = transition_system();
+
+        let json = to_json(&system);
+
+        let mut names: Vec<_> = system.transitions.iter().map(|t| t.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), system.transitions.len());
+        assert!(json.contains("{\"name\": \"Permit\", \"params\": [{\"name\": \"owner\""));
+        assert!(json.contains("\"effects\": [\"paused' = TRUE\", \"next_seq' = next_seq + 1\"]"));
+        // 백슬래시는 이스케이프됨
+        assert!(json.contains("from \\\\notin frozen"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Deterministic digest of ledger state.
+//!
+//! Two nodes that replay the same operations can compare
+//! [`TokenState::state_hash`] values instead of shipping whole states. The
+//! digest is SHA-256 over a canonical encoding:
+//!
+//! ```text
+//! b"token-standard/state/v1"
+//! total_supply                       u128 LE
+//! holder count                       u64 LE
+//!   per holder, sorted by address:   len u64 LE, bytes, balance u128 LE
+//! allowance count                    u64 LE
+//!   per allowance, sorted by (owner, spender):
+//!                                    owner, spender (as above), amount u128 LE
+//! ```
+//!
+//! Zero balances and zero allowances are skipped, so an account that was
+//! reaped hashes the same as one that never existed. Amounts are always
+//! widened to 128 bits, so 64-bit and `u128-balance` builds agree.
+//! Configuration, event logs, usage counters and the shielded pool's
+//! internals are not covered.
+
+use crate::sha256::Sha256;
+use crate::{Address, Balance, TokenState};
+
+const DOMAIN: &[u8] = b"token-standard/state/v1";
+
+impl TokenState {
+    /// SHA-256 over balances, allowances and total supply, independent of
+    /// hash-map iteration order.
+    pub fn state_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(DOMAIN);
+        amount(&mut hasher, self.total_supply);
+
+        let mut holders: Vec<_> = self.balances.iter().filter(|(_, b)| **b > 0).collect();
+        holders.sort_unstable_by_key(|(address, _)| *address);
+        hasher.update(&(holders.len() as u64).to_le_bytes());
+        for (address, balance) in holders {
+            account(&mut hasher, address);
+            amount(&mut hasher, *balance);
+        }
+
+        let mut allowances: Vec<_> = self.allowances.iter().filter(|(_, a)| **a > 0).collect();
+        allowances.sort_unstable_by_key(|(key, _)| *key);
+        hasher.update(&(allowances.len() as u64).to_le_bytes());
+        for ((owner, spender), allowance) in allowances {
+            account(&mut hasher, owner);
+            account(&mut hasher, spender);
+            amount(&mut hasher, *allowance);
+        }
+
+        hasher.finish()
+    }
+}
+
+fn account(hasher: &mut Sha256, address: &Address) {
+    let raw = address.as_str().as_bytes();
+    hasher.update(&(raw.len() as u64).to_le_bytes());
+    hasher.update(raw);
+}
+
+// u64/u128 빌드 간 해시 일치를 위해 항상 128비트로 확장
+#[allow(clippy::unnecessary_cast)]
+fn amount(hasher: &mut Sha256, value: Balance) {
+    hasher.update(&(value as u128).to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenConfig;
+
+    #[test]
+    fn test_same_operations_same_hash() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut a = TokenState::new(alice.clone(), 1000);
+        let mut b = TokenState::new(alice.clone(), 1000);
+        // 다른 삽입 순서 → 다른 HashMap 순회 순서
+        b.bulk_credit(&alice, [(carol.clone(), 0), (bob.clone(), 0)])
+            .unwrap();
+
+        for token in [&mut a, &mut b] {
+            token.transfer(&alice, &carol, 10).unwrap();
+            token.transfer(&alice, &bob, 20).unwrap();
+            token.approve(&bob, &carol, 5).unwrap();
+        }
+
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn test_hash_covers_balances_allowances_and_supply() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let base = TokenState::new(alice.clone(), 1000);
+        let mut moved = TokenState::new(alice.clone(), 1000);
+        moved.transfer(&alice, &bob, 1).unwrap();
+        let mut approved = TokenState::new(alice.clone(), 1000);
+        approved.approve(&alice, &bob, 1).unwrap();
+        let mut burned = TokenState::new(alice.clone(), 1000);
+        burned.burn(&alice, 1).unwrap();
+
+        let hashes = [&base, &moved, &approved, &burned].map(|t| t.state_hash());
+
+        for i in 0..hashes.len() {
+            for j in i + 1..hashes.len() {
+                assert_ne!(hashes[i], hashes[j], "{i} vs {j}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_ignores_event_log_and_config() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut a = TokenState::new(alice.clone(), 1000);
+        a.transfer(&alice, &bob, 100).unwrap();
+        let b = TokenState::from_parts(
+            [(alice.clone(), 900), (bob.clone(), 100)].into(),
+            1000,
+            TokenConfig::erc20_compatible(),
+        );
+
+        a.drain_events();
+
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+}
@@ -0,0 +1,184 @@
+//! Per-address transfer volume counters (`account-stats` feature).
+//!
+//! Counters are updated on every balance-moving transfer so dashboards and
+//! AML heuristics can read them directly. Epoch counters are reset lazily:
+//! [`TokenState::roll_stats_epoch`] only bumps an epoch number, and an
+//! account's epoch counters are cleared the next time it is touched.
+
+use std::collections::HashMap;
+
+use crate::{Address, Balance, TokenState};
+
+/// Sent/received volume for one address.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountStats {
+    /// Total tokens sent since genesis
+    pub sent: Balance,
+    /// Total tokens received since genesis
+    pub received: Balance,
+    /// Tokens sent during the current stats epoch
+    pub epoch_sent: Balance,
+    /// Tokens received during the current stats epoch
+    pub epoch_received: Balance,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsLedger {
+    epoch: u64,
+    // 주소별 (마지막으로 갱신된 epoch, 통계)
+    accounts: HashMap<Address, (u64, AccountStats)>,
+}
+
+/// Portable form of the ledger, in address order.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct StatsSnapshot {
+    pub(crate) epoch: u64,
+    pub(crate) accounts: std::collections::BTreeMap<Address, (u64, AccountStats)>,
+}
+
+impl StatsLedger {
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            epoch: self.epoch,
+            accounts: self.accounts.iter().map(|(a, s)| (a.clone(), *s)).collect(),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, snapshot: StatsSnapshot) {
+        self.epoch = snapshot.epoch;
+        self.accounts = snapshot.accounts.into_iter().collect();
+    }
+
+    // 체크포인트 되돌리기용 (journal 모듈)
+    pub(crate) fn saved(&self, address: &Address) -> Option<(u64, AccountStats)> {
+        self.accounts.get(address).copied()
+    }
+
+    pub(crate) fn put_back(&mut self, address: &Address, saved: Option<(u64, AccountStats)>) {
+        match saved {
+            Some(saved) => self.accounts.insert(address.clone(), saved),
+            None => self.accounts.remove(address),
+        };
+    }
+
+    pub(crate) fn set_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+    }
+
+    fn entry(&mut self, address: &Address) -> &mut AccountStats {
+        let epoch = self.epoch;
+        let (seen, stats) = self
+            .accounts
+            .entry(address.clone())
+            .or_insert((epoch, AccountStats::default()));
+        if *seen != epoch {
+            *seen = epoch;
+            stats.epoch_sent = 0;
+            stats.epoch_received = 0;
+        }
+        stats
+    }
+
+    pub(crate) fn record(&mut self, from: &Address, to: &Address, amount: Balance) {
+        let sender = self.entry(from);
+        sender.sent = sender.sent.saturating_add(amount);
+        sender.epoch_sent = sender.epoch_sent.saturating_add(amount);
+
+        let receiver = self.entry(to);
+        receiver.received = receiver.received.saturating_add(amount);
+        receiver.epoch_received = receiver.epoch_received.saturating_add(amount);
+    }
+}
+
+impl TokenState {
+    pub fn account_stats(&self, address: &Address) -> AccountStats {
+        match self.stats.accounts.get(address) {
+            Some((seen, stats)) if *seen == self.stats.epoch => *stats,
+            Some((_, stats)) => AccountStats {
+                epoch_sent: 0,
+                epoch_received: 0,
+                ..*stats
+            },
+            None => AccountStats::default(),
+        }
+    }
+
+    /// Starts a new stats epoch; per-epoch counters read as zero afterwards.
+    pub fn roll_stats_epoch(&mut self) {
+        self.stats.epoch += 1;
+    }
+
+    pub fn stats_epoch(&self) -> u64 {
+        self.stats.epoch
+    }
+
+    // 저널이 열려 있으면 양쪽 카운터의 이전 값을 먼저 기록
+    pub(crate) fn record_stats(&mut self, from: &Address, to: &Address, amount: Balance) {
+        self.journal_stats(from);
+        self.journal_stats(to);
+        self.stats.record(from, to, amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfers_update_both_sides() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+
+        token.transfer(&alice, &bob, 100).unwrap();
+        token.transfer(&bob, &alice, 40).unwrap();
+
+        let a = token.account_stats(&alice);
+        let b = token.account_stats(&bob);
+        assert_eq!((a.sent, a.received), (100, 40));
+        assert_eq!((b.sent, b.received), (40, 100));
+    }
+
+    #[test]
+    fn test_transfer_from_counts_owner_not_spender() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let charlie = Address::new("charlie").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.approve(&alice, &bob, 100).unwrap();
+
+        token.transfer_from(&bob, &alice, &charlie, 70).unwrap();
+
+        assert_eq!(token.account_stats(&alice).sent, 70);
+        assert_eq!(token.account_stats(&charlie).received, 70);
+        assert_eq!(token.account_stats(&bob), AccountStats::default());
+    }
+
+    #[test]
+    fn test_roll_epoch_resets_epoch_counters_only() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        token.transfer(&alice, &bob, 100).unwrap();
+
+        token.roll_stats_epoch();
+        token.transfer(&alice, &bob, 5).unwrap();
+
+        let a = token.account_stats(&alice);
+        assert_eq!(a.sent, 105);
+        assert_eq!(a.epoch_sent, 5);
+        assert_eq!(token.account_stats(&bob).epoch_received, 5);
+    }
+
+    #[test]
+    fn test_failed_transfer_not_counted() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 100);
+
+        token.transfer(&alice, &bob, 200).unwrap_err();
+
+        assert_eq!(token.account_stats(&alice), AccountStats::default());
+    }
+}
@@ -0,0 +1,299 @@
+//! Stealth-address style one-time recipients (experimental simulation).
+//!
+//! A recipient registers a [`StealthMetaAddress`]: the public halves of a
+//! scan key and a spend key. To pay them, a sender picks a fresh ephemeral
+//! secret, derives a one-time [`Address`] from it and the meta-address,
+//! transfers to that address as usual, and publishes the ephemeral public
+//! key in an [`Announcement`]. Only the recipient can tell which
+//! announcements are theirs: recognizing one takes the scan secret, and
+//! spending from the one-time address takes the spend secret as well.
+//!
+//! The derivation is the usual dual-key one. With scan key `a`, spend key
+//! `b` and ephemeral key `r`, the one-time public key is `H(r·A)·G + B`;
+//! the recipient recomputes it as `H(a·R)·G + B`, and its secret is
+//! `H(a·R) + b`.
+//!
+//! The group arithmetic sits behind [`KeyDerivation`], so the registry is
+//! the same whichever curve backs it. The bundled [`SimulatedKeys`] works
+//! in the integers modulo 2^128, where every public key gives away its
+//! secret, so it is for prototyping the bookkeeping only and hides nothing.
+
+use std::collections::HashMap;
+
+use crate::Address;
+
+/// Secret scalar, sized for a curve scalar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SecretKey(pub [u8; 32]);
+
+/// Public key, sized for a compressed curve point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PublicKey(pub [u8; 32]);
+
+/// Group operations behind stealth derivation.
+pub trait KeyDerivation {
+    /// `secret·G`.
+    fn public_key(&self, secret: &SecretKey) -> PublicKey;
+    /// The Diffie-Hellman point `secret·public`, hashed to a scalar.
+    fn shared_scalar(&self, secret: &SecretKey, public: &PublicKey) -> SecretKey;
+    /// `point + scalar·G`.
+    fn offset(&self, point: &PublicKey, scalar: &SecretKey) -> PublicKey;
+    /// `a + b` in the scalar field.
+    fn add_secrets(&self, a: &SecretKey, b: &SecretKey) -> SecretKey;
+    /// The address that tokens sent to `key` are held under.
+    fn address(&self, key: &PublicKey) -> Address;
+}
+
+/// Transparent stand-in for a real curve. For tests and simulations only.
+///
+/// Scalars and points are both the first 16 bytes read as a little-endian
+/// `u128`, with wrapping arithmetic and a fixed odd generator.
+pub struct SimulatedKeys;
+
+impl SimulatedKeys {
+    const GENERATOR: u128 = 0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c835;
+
+    fn read(bytes: &[u8; 32]) -> u128 {
+        u128::from_le_bytes(bytes[..16].try_into().unwrap())
+    }
+
+    fn write(value: u128) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[..16].copy_from_slice(&value.to_le_bytes());
+        bytes
+    }
+
+    // 해시 대신 쓰는 비트 섞기 (splitmix 계열)
+    fn mix(mut x: u128) -> u128 {
+        x ^= x >> 67;
+        x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9_94d0_49bb_1331_11eb);
+        x ^= x >> 59;
+        x = x.wrapping_mul(0x94d0_49bb_1331_11eb_bf58_476d_1ce4_e5b9);
+        x ^ (x >> 71)
+    }
+}
+
+impl KeyDerivation for SimulatedKeys {
+    fn public_key(&self, secret: &SecretKey) -> PublicKey {
+        PublicKey(Self::write(
+            Self::read(&secret.0).wrapping_mul(Self::GENERATOR),
+        ))
+    }
+
+    fn shared_scalar(&self, secret: &SecretKey, public: &PublicKey) -> SecretKey {
+        let point = Self::read(&secret.0).wrapping_mul(Self::read(&public.0));
+        SecretKey(Self::write(Self::mix(point)))
+    }
+
+    fn offset(&self, point: &PublicKey, scalar: &SecretKey) -> PublicKey {
+        let shift = Self::read(&scalar.0).wrapping_mul(Self::GENERATOR);
+        PublicKey(Self::write(Self::read(&point.0).wrapping_add(shift)))
+    }
+
+    fn add_secrets(&self, a: &SecretKey, b: &SecretKey) -> SecretKey {
+        SecretKey(Self::write(Self::read(&a.0).wrapping_add(Self::read(&b.0))))
+    }
+
+    fn address(&self, key: &PublicKey) -> Address {
+        let hex: String = Self::read(&key.0)
+            .to_be_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        Address::trusted(format!("0x{hex:0>40}"))
+    }
+}
+
+/// A recipient's two secret keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealthKeys {
+    /// Finds payments; may be handed to a watch-only service
+    pub scan: SecretKey,
+    /// Spends them
+    pub spend: SecretKey,
+}
+
+/// The public halves of [`StealthKeys`], which senders derive from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    pub scan: PublicKey,
+    pub spend: PublicKey,
+}
+
+/// Published by a sender next to a payment to a one-time address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    /// One-time address the payment went to
+    pub address: Address,
+    /// The sender's ephemeral public key, `r·G`
+    pub ephemeral: PublicKey,
+}
+
+/// Registered meta-addresses and the derivation they use.
+pub struct StealthRegistry {
+    scheme: Box<dyn KeyDerivation>,
+    meta: HashMap<Address, StealthMetaAddress>,
+}
+
+impl StealthRegistry {
+    pub fn new(scheme: Box<dyn KeyDerivation>) -> Self {
+        Self {
+            scheme,
+            meta: HashMap::new(),
+        }
+    }
+
+    /// The meta-address senders should use to pay the holder of `keys`.
+    pub fn meta_address(&self, keys: &StealthKeys) -> StealthMetaAddress {
+        StealthMetaAddress {
+            scan: self.scheme.public_key(&keys.scan),
+            spend: self.scheme.public_key(&keys.spend),
+        }
+    }
+
+    /// Publishes `meta` under `name`, replacing any earlier registration.
+    pub fn register(&mut self, name: Address, meta: StealthMetaAddress) {
+        self.meta.insert(name, meta);
+    }
+
+    pub fn lookup(&self, name: &Address) -> Option<&StealthMetaAddress> {
+        self.meta.get(name)
+    }
+
+    /// Derives a one-time address for a payment to `name`, using the
+    /// sender's `ephemeral` secret, which must be fresh for every payment.
+    ///
+    /// Returns `None` if `name` has no registered meta-address.
+    pub fn derive(&self, name: &Address, ephemeral: &SecretKey) -> Option<Announcement> {
+        let meta = self.meta.get(name)?;
+        let shared = self.scheme.shared_scalar(ephemeral, &meta.scan);
+        let key = self.scheme.offset(&meta.spend, &shared);
+        Some(Announcement {
+            address: self.scheme.address(&key),
+            ephemeral: self.scheme.public_key(ephemeral),
+        })
+    }
+
+    /// The announcements among `announcements` that pay the holder of
+    /// `keys`, in order.
+    pub fn scan<'a>(
+        &self,
+        keys: &StealthKeys,
+        announcements: &'a [Announcement],
+    ) -> Vec<&'a Announcement> {
+        let spend = self.scheme.public_key(&keys.spend);
+        announcements
+            .iter()
+            .filter(|a| self.one_time_address(&keys.scan, &spend, a) == a.address)
+            .collect()
+    }
+
+    /// The secret key that controls `announcement`'s one-time address.
+    pub fn one_time_secret(&self, keys: &StealthKeys, announcement: &Announcement) -> SecretKey {
+        let shared = self
+            .scheme
+            .shared_scalar(&keys.scan, &announcement.ephemeral);
+        self.scheme.add_secrets(&shared, &keys.spend)
+    }
+
+    /// The address a secret key controls.
+    pub fn address_of(&self, secret: &SecretKey) -> Address {
+        self.scheme.address(&self.scheme.public_key(secret))
+    }
+
+    fn one_time_address(
+        &self,
+        scan: &SecretKey,
+        spend: &PublicKey,
+        announcement: &Announcement,
+    ) -> Address {
+        let shared = self.scheme.shared_scalar(scan, &announcement.ephemeral);
+        self.scheme.address(&self.scheme.offset(spend, &shared))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenState;
+
+    fn key(n: u8) -> SecretKey {
+        SecretKey([n; 32])
+    }
+
+    fn setup() -> (StealthRegistry, StealthKeys) {
+        let mut registry = StealthRegistry::new(Box::new(SimulatedKeys));
+        let bob = StealthKeys {
+            scan: key(1),
+            spend: key(2),
+        };
+        let carol = StealthKeys {
+            scan: key(3),
+            spend: key(4),
+        };
+        registry.register(Address::new("bob").unwrap(), registry.meta_address(&bob));
+        registry.register(
+            Address::new("carol").unwrap(),
+            registry.meta_address(&carol),
+        );
+        (registry, bob)
+    }
+
+    #[test]
+    fn test_recipient_finds_and_controls_payment() {
+        let (registry, bob) = setup();
+        let alice = Address::new("alice").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let to_bob = registry
+            .derive(&Address::new("bob").unwrap(), &key(7))
+            .unwrap();
+        let to_carol = registry
+            .derive(&Address::new("carol").unwrap(), &key(8))
+            .unwrap();
+        token.transfer(&alice, &to_bob.address, 40).unwrap();
+        token.transfer(&alice, &to_carol.address, 60).unwrap();
+        let announcements = [to_carol, to_bob.clone()];
+
+        let found = registry.scan(&bob, &announcements);
+
+        assert_eq!(found, [&to_bob]);
+        assert_eq!(token.balance_of(&found[0].address), 40);
+        let secret = registry.one_time_secret(&bob, found[0]);
+        assert_eq!(registry.address_of(&secret), to_bob.address);
+    }
+
+    #[test]
+    fn test_one_time_addresses_do_not_repeat() {
+        let (registry, bob) = setup();
+        let bob_name = Address::new("bob").unwrap();
+
+        let first = registry.derive(&bob_name, &key(7)).unwrap();
+        let second = registry.derive(&bob_name, &key(9)).unwrap();
+
+        assert_ne!(first.address, second.address);
+        assert_ne!(first.address, registry.address_of(&bob.spend));
+        assert_eq!(registry.scan(&bob, &[first, second]).len(), 2);
+        assert_eq!(
+            registry.derive(&Address::new("dave").unwrap(), &key(7)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wrong_scan_key_finds_nothing() {
+        let (registry, bob) = setup();
+        let announcement = registry
+            .derive(&Address::new("bob").unwrap(), &key(7))
+            .unwrap();
+        let wrong_scan = StealthKeys {
+            scan: key(3),
+            ..bob
+        };
+
+        assert!(registry.scan(&wrong_scan, &[announcement]).is_empty());
+        assert_eq!(
+            registry.lookup(&Address::new("bob").unwrap()),
+            Some(&registry.meta_address(&bob))
+        );
+    }
+}
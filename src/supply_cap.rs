@@ -0,0 +1,88 @@
+//! Fixed-cap tokens.
+//!
+//! A token built with [`TokenState::with_max_supply`] never lets
+//! `total_supply` exceed the cap: a mint or bulk credit that would cross it
+//! fails with [`TokenError::SupplyCapExceeded`] and changes nothing. Burns
+//! free headroom that later mints may use again. The cap is fixed for the
+//! token's lifetime.
+
+use crate::{Balance, TokenError, TokenState};
+
+impl TokenState {
+    /// Caps `total_supply` at `cap`.
+    ///
+    /// Fails if the current supply already exceeds it.
+    pub fn with_max_supply(mut self, cap: Balance) -> Result<Self, TokenError> {
+        check_cap(cap, self.total_supply)?;
+        self.max_supply = Some(cap);
+        Ok(self)
+    }
+
+    pub fn max_supply(&self) -> Option<Balance> {
+        self.max_supply
+    }
+
+    /// Fails if `attempted` would be a total supply above the cap.
+    pub(crate) fn check_supply_cap(&self, attempted: Balance) -> Result<(), TokenError> {
+        match self.max_supply {
+            Some(cap) => check_cap(cap, attempted),
+            None => Ok(()),
+        }
+    }
+}
+
+fn check_cap(cap: Balance, attempted: Balance) -> Result<(), TokenError> {
+    if attempted > cap {
+        return Err(TokenError::SupplyCapExceeded { cap, attempted });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+
+    #[test]
+    fn test_mint_stops_at_cap() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 900)
+            .with_max_supply(1000)
+            .unwrap();
+
+        token.mint(&alice, &bob, 100).unwrap();
+        let result = token.mint(&alice, &bob, 1);
+
+        assert_eq!(
+            result,
+            Err(TokenError::SupplyCapExceeded {
+                cap: 1000,
+                attempted: 1001
+            })
+        );
+        assert_eq!(token.total_supply(), 1000);
+        assert_eq!(token.balance_of(&bob), 100);
+    }
+
+    #[test]
+    fn test_burn_frees_headroom_and_bulk_credit_is_capped() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000)
+            .with_max_supply(1000)
+            .unwrap();
+
+        let credit = token.bulk_credit(&alice, [(bob.clone(), 1)]);
+        token.burn(&alice, 50).unwrap();
+        token.mint(&alice, &bob, 50).unwrap();
+
+        assert!(matches!(credit, Err(TokenError::SupplyCapExceeded { .. })));
+        assert_eq!(token.total_supply(), 1000);
+        assert!(
+            TokenState::new(alice.clone(), 10)
+                .with_max_supply(9)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Synthetic ledgers shaped like real ones.
+//!
+//! Benchmarks and capacity tests need states with realistic holder counts,
+//! balance skew, and allowance density, but shipping real chain data into
+//! CI is not an option. [`StateProfile`] holds the handful of summary
+//! statistics that matter; [`generate`] builds a [`TokenState`] with that
+//! shape, and [`StateProfile::fit`] extracts the statistics from an
+//! existing state, so a profile can be measured once offline and only the
+//! numbers committed.
+//!
+//! Holder balances are log-normal. Each holder grants a number of
+//! allowances averaging `allowance_density`, to spenders chosen by a Zipf
+//! law over holders, the way approvals concentrate on a few routers and
+//! exchanges in practice.
+
+use std::collections::HashMap;
+
+use crate::addrgen::AddressGenerator;
+use crate::rng::Rng;
+use crate::workload::{sample_log_normal, sample_zipf, zipf_cdf};
+use crate::{AllowanceUsage, Balance, TokenConfig, TokenState};
+
+/// Summary statistics of a ledger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateProfile {
+    /// Number of accounts holding a non-zero balance
+    pub holders: usize,
+    /// Mean of `ln(balance)` over holders
+    pub balance_mu: f64,
+    /// Standard deviation of `ln(balance)` over holders
+    pub balance_sigma: f64,
+    /// Average number of allowances granted per holder
+    pub allowance_density: f64,
+    /// Fraction of allowances set to `Balance::MAX` ("unlimited" approvals)
+    pub unlimited_ratio: f64,
+    /// Zipf exponent of spender popularity
+    pub spender_skew: f64,
+}
+
+impl Default for StateProfile {
+    fn default() -> Self {
+        Self {
+            holders: 10_000,
+            balance_mu: 8.0,
+            balance_sigma: 2.5,
+            allowance_density: 0.5,
+            unlimited_ratio: 0.3,
+            spender_skew: 1.2,
+        }
+    }
+}
+
+impl StateProfile {
+    /// Measures the profile of an existing state.
+    ///
+    /// `spender_skew` cannot be recovered reliably from a single state and
+    /// is left at its default.
+    pub fn fit(token: &TokenState) -> Self {
+        let logs: Vec<f64> = token
+            .balances
+            .values()
+            .filter(|b| **b > 0)
+            .map(|b| (*b as f64).ln())
+            .collect();
+        let holders = logs.len();
+        let n = holders.max(1) as f64;
+        let mu = logs.iter().sum::<f64>() / n;
+        let variance = logs.iter().map(|x| (x - mu).powi(2)).sum::<f64>() / n;
+
+        let granted = token.allowances.values().filter(|a| **a > 0);
+        let (count, unlimited) = granted.fold((0usize, 0usize), |(c, u), a| {
+            (c + 1, u + usize::from(*a == Balance::MAX))
+        });
+
+        Self {
+            holders,
+            balance_mu: mu,
+            balance_sigma: variance.sqrt(),
+            allowance_density: count as f64 / n,
+            unlimited_ratio: unlimited as f64 / count.max(1) as f64,
+            ..Self::default()
+        }
+    }
+}
+
+/// Builds a state matching `profile`, reproducibly from `seed`.
+///
+/// The balances are written directly rather than minted, so the result
+/// starts with an empty event log.
+pub fn generate(profile: &StateProfile, seed: u64, config: TokenConfig) -> TokenState {
+    let mut rng = Rng::new(seed);
+    let holders: Vec<_> = AddressGenerator::new(seed).take(profile.holders).collect();
+
+    // 개별 상한을 두어 합계가 Balance를 넘지 않게 함
+    let cap = Balance::MAX / profile.holders.max(1) as Balance;
+    let balances: HashMap<_, _> = holders
+        .iter()
+        .map(|address| {
+            let balance =
+                sample_log_normal(&mut rng, profile.balance_mu, profile.balance_sigma).min(cap);
+            (address.clone(), balance)
+        })
+        .collect();
+    let supply = balances.values().sum();
+    let mut token = TokenState::from_parts(balances, supply, config);
+
+    if holders.len() < 2 {
+        return token;
+    }
+    let cdf = zipf_cdf(holders.len(), profile.spender_skew);
+    let whole = profile.allowance_density.trunc() as usize;
+    let fraction = profile.allowance_density.fract();
+    for (i, owner) in holders.iter().enumerate() {
+        let grants = whole + usize::from(rng.next_f64() < fraction);
+        for _ in 0..grants {
+            let mut j = sample_zipf(&mut rng, &cdf);
+            if j == i {
+                j = (j + 1) % holders.len();
+            }
+            let amount = if rng.next_f64() < profile.unlimited_ratio {
+                Balance::MAX
+            } else {
+                sample_log_normal(&mut rng, profile.balance_mu, profile.balance_sigma)
+            };
+            let key = (owner.clone(), holders[j].clone());
+            token.allowances.insert(key.clone(), amount);
+            token.allowance_usage.insert(
+                key,
+                AllowanceUsage {
+                    granted: amount,
+                    spent: 0,
+                    expires_at: None,
+                },
+            );
+        }
+    }
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_state() {
+        let profile = StateProfile {
+            holders: 500,
+            ..StateProfile::default()
+        };
+
+        let a = generate(&profile, 3, TokenConfig::default());
+        let b = generate(&profile, 3, TokenConfig::default());
+
+        assert_eq!(a.to_bytes(), b.to_bytes());
+        assert_eq!(a.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_fit_recovers_profile() {
+        let profile = StateProfile {
+            holders: 20_000,
+            balance_mu: 10.0,
+            balance_sigma: 2.0,
+            allowance_density: 1.5,
+            unlimited_ratio: 0.25,
+            ..StateProfile::default()
+        };
+
+        let fitted = StateProfile::fit(&generate(&profile, 11, TokenConfig::default()));
+
+        assert_eq!(fitted.holders, 20_000);
+        assert!((fitted.balance_mu - 10.0).abs() < 0.1, "{fitted:?}");
+        assert!((fitted.balance_sigma - 2.0).abs() < 0.1, "{fitted:?}");
+        // 같은 (owner, spender) 쌍이 다시 뽑히면 덮어쓰므로 약간 낮을 수 있음
+        assert!(
+            (1.3..=1.55).contains(&fitted.allowance_density),
+            "{fitted:?}"
+        );
+        assert!((fitted.unlimited_ratio - 0.25).abs() < 0.02, "{fitted:?}");
+    }
+
+    #[test]
+    fn test_spenders_are_skewed() {
+        let profile = StateProfile {
+            holders: 2_000,
+            allowance_density: 2.0,
+            ..StateProfile::default()
+        };
+        let token = generate(&profile, 5, TokenConfig::default());
+        let mut per_spender: HashMap<_, usize> = HashMap::new();
+
+        for (_, spender) in token.allowances.keys() {
+            *per_spender.entry(spender).or_default() += 1;
+        }
+
+        let busiest = per_spender.values().max().unwrap();
+        assert!(*busiest > 4 * token.allowances.len() / per_spender.len());
+    }
+}
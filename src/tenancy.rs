@@ -0,0 +1,513 @@
+//! Many isolated ledgers in one process.
+//!
+//! A [`TenantRegistry`] maps tenant ids to independent [`TokenState`]s, so a
+//! hosted service can run one ledger per customer without one process per
+//! customer. Tenants share nothing: balances, allowances, interceptors and
+//! event logs are all per ledger.
+//!
+//! [`TenantQuota`] bounds how many tenants the registry accepts and, per
+//! ledger, how many accounts it may hold, how many operations it may run
+//! per clock tick, how many writes its checkpoint journal may hold and how
+//! many events may wait in its log undrained. Every limit is checked before
+//! an operation runs and a breach fails with [`TenantError::QuotaExceeded`],
+//! leaving the ledger untouched. The account limit counts only the
+//! recipients an operation could create, so a ledger at its limit can still
+//! move tokens between existing accounts. The journal only grows while the
+//! host holds a checkpoint open, so a simulation that branches without
+//! reverting is stopped once [`TokenState::journal_len`] reaches the limit;
+//! a ledger with too many pending events accepts operations again once the
+//! host drains them. [`TenantRegistry::usage`] reports consumption against
+//! each limit.
+//!
+//! The rate limit is per tick rather than per second: it reads time from
+//! each ledger's own [`Clock`](crate::clock::Clock), so a tick is one unit
+//! of that clock (a second for [`SystemClock`](crate::clock::SystemClock),
+//! a block for a ledger that counts block heights) and a
+//! [`ManualClock`](crate::clock::ManualClock) makes it deterministic.
+//! Quotas are enforced by the registry, not by [`TokenState`] itself; a
+//! single token is held to a quota by registering it as the only tenant.
+//!
+//! Persistence is partitioned by tenant: [`TenantRegistry::snapshot`]
+//! returns one [`TokenState::to_bytes`] image per tenant, ready to be
+//! stored under a per-tenant key, and [`TenantRegistry::restore`] reverses it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::codec::SnapshotError;
+use crate::operation::{Operation, Receipt};
+use crate::{TokenError, TokenState};
+
+/// Limits applied to every tenant in a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// Most tenants the registry will hold
+    pub max_tenants: usize,
+    /// Most accounts any one ledger may hold
+    pub max_accounts: usize,
+    /// Most operations any one ledger may run while its clock reads the
+    /// same time
+    pub max_ops_per_tick: usize,
+    /// Most writes any one ledger's checkpoint journal may hold
+    pub max_journal_entries: usize,
+    /// Most undrained events any one ledger may hold
+    pub max_pending_events: usize,
+}
+
+impl Default for TenantQuota {
+    fn default() -> Self {
+        Self {
+            max_tenants: usize::MAX,
+            max_accounts: usize::MAX,
+            max_ops_per_tick: usize::MAX,
+            max_journal_entries: usize::MAX,
+            max_pending_events: usize::MAX,
+        }
+    }
+}
+
+/// A per-ledger limit in [`TenantQuota`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Accounts,
+    OpsPerTick,
+    JournalEntries,
+    PendingEvents,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Accounts => "accounts",
+            Self::OpsPerTick => "operations per clock tick",
+            Self::JournalEntries => "journal entries",
+            Self::PendingEvents => "pending events",
+        })
+    }
+}
+
+/// One ledger's consumption against its quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantUsage {
+    pub accounts: usize,
+    /// Operations admitted while the clock reads the current time
+    pub ops_this_tick: usize,
+    /// Writes held by the checkpoint journal
+    pub journal_entries: usize,
+    /// Events logged and not yet drained
+    pub pending_events: usize,
+}
+
+/// Why a registry call failed.
+#[derive(Debug, PartialEq)]
+pub enum TenantError {
+    /// No ledger is registered under this id
+    UnknownTenant(String),
+    /// A ledger is already registered under this id
+    DuplicateTenant(String),
+    /// The registry already holds `max_tenants` ledgers
+    TenantLimit { max: usize },
+    /// Running the operation would exceed one of the ledger's limits
+    QuotaExceeded {
+        tenant: String,
+        resource: Resource,
+        limit: usize,
+    },
+    /// The tenant's ledger rejected the operation
+    Token(TokenError),
+    /// A stored partition could not be restored
+    Snapshot {
+        tenant: String,
+        error: SnapshotError,
+    },
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTenant(id) => write!(f, "unknown tenant {id:?}"),
+            Self::DuplicateTenant(id) => write!(f, "tenant {id:?} already exists"),
+            Self::TenantLimit { max } => write!(f, "registry is limited to {max} tenants"),
+            Self::QuotaExceeded {
+                tenant,
+                resource,
+                limit,
+            } => write!(f, "tenant {tenant:?} is limited to {limit} {resource}"),
+            Self::Token(e) => write!(f, "{e:?}"),
+            Self::Snapshot { tenant, error } => write!(f, "tenant {tenant:?}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
+
+/// Tenant id → ledger.
+#[derive(Default)]
+pub struct TenantRegistry {
+    quota: TenantQuota,
+    ledgers: HashMap<String, Tenant>,
+}
+
+struct Tenant {
+    ledger: TokenState,
+    // 원장 clock 기준 고정 윈도우: (윈도우 시각, 윈도우 안에서 허용된 operation 수)
+    window: (u64, usize),
+}
+
+impl Tenant {
+    fn ops_this_tick(&self) -> usize {
+        let (start, ops) = self.window;
+        if self.ledger.now() == start { ops } else { 0 }
+    }
+}
+
+impl TenantRegistry {
+    pub fn new(quota: TenantQuota) -> Self {
+        Self {
+            quota,
+            ledgers: HashMap::new(),
+        }
+    }
+
+    pub fn quota(&self) -> TenantQuota {
+        self.quota
+    }
+
+    /// Registers `ledger` under `tenant`.
+    pub fn create(&mut self, tenant: &str, ledger: TokenState) -> Result<(), TenantError> {
+        if self.ledgers.contains_key(tenant) {
+            return Err(TenantError::DuplicateTenant(tenant.to_string()));
+        }
+        if self.ledgers.len() >= self.quota.max_tenants {
+            return Err(TenantError::TenantLimit {
+                max: self.quota.max_tenants,
+            });
+        }
+        let tenant_state = Tenant {
+            window: (ledger.now(), 0),
+            ledger,
+        };
+        self.ledgers.insert(tenant.to_string(), tenant_state);
+        Ok(())
+    }
+
+    /// Unregisters `tenant`, handing back its ledger.
+    pub fn remove(&mut self, tenant: &str) -> Option<TokenState> {
+        self.ledgers.remove(tenant).map(|t| t.ledger)
+    }
+
+    pub fn get(&self, tenant: &str) -> Option<&TokenState> {
+        self.ledgers.get(tenant).map(|t| &t.ledger)
+    }
+
+    /// Direct access to a tenant's ledger. Bypasses the quota.
+    pub fn get_mut(&mut self, tenant: &str) -> Option<&mut TokenState> {
+        self.ledgers.get_mut(tenant).map(|t| &mut t.ledger)
+    }
+
+    /// Current consumption of `tenant`'s ledger.
+    pub fn usage(&self, tenant: &str) -> Option<TenantUsage> {
+        let t = self.ledgers.get(tenant)?;
+        Some(TenantUsage {
+            accounts: t.ledger.account_count(),
+            ops_this_tick: t.ops_this_tick(),
+            journal_entries: t.ledger.journal_len(),
+            pending_events: t.ledger.events().len(),
+        })
+    }
+
+    /// Tenant ids in sorted order.
+    pub fn tenants(&self) -> Vec<&str> {
+        let mut ids: Vec<_> = self.ledgers.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Runs `op` on `tenant`'s ledger after checking its quota.
+    ///
+    /// Operations the ledger rejects still count toward the rate limit.
+    pub fn execute(&mut self, tenant: &str, op: Operation) -> Result<Receipt, TenantError> {
+        let quota = self.quota;
+        let t = self
+            .ledgers
+            .get_mut(tenant)
+            .ok_or_else(|| TenantError::UnknownTenant(tenant.to_string()))?;
+        let exceeded = |resource, limit| TenantError::QuotaExceeded {
+            tenant: tenant.to_string(),
+            resource,
+            limit,
+        };
+
+        let ops = t.ops_this_tick();
+        if ops >= quota.max_ops_per_tick {
+            return Err(exceeded(Resource::OpsPerTick, quota.max_ops_per_tick));
+        }
+        if t.ledger.journal_len() >= quota.max_journal_entries {
+            return Err(exceeded(
+                Resource::JournalEntries,
+                quota.max_journal_entries,
+            ));
+        }
+        if t.ledger.events().len() >= quota.max_pending_events {
+            return Err(exceeded(Resource::PendingEvents, quota.max_pending_events));
+        }
+        let new_accounts = op
+            .recipients(&t.ledger)
+            .into_iter()
+            .filter(|a| !t.ledger.balances.contains_key(*a))
+            .count();
+        if t.ledger.account_count().saturating_add(new_accounts) > quota.max_accounts {
+            return Err(exceeded(Resource::Accounts, quota.max_accounts));
+        }
+
+        // 시각이 바뀌었으면 ops는 0이므로 새 윈도우가 시작됨
+        t.window = (t.ledger.now(), ops + 1);
+        t.ledger.execute(op).map_err(TenantError::Token)
+    }
+
+    /// One binary image per tenant, keyed by tenant id.
+    pub fn snapshot(&self) -> BTreeMap<String, Vec<u8>> {
+        self.ledgers
+            .iter()
+            .map(|(id, t)| (id.clone(), t.ledger.to_bytes()))
+            .collect()
+    }
+
+    /// Rebuilds a registry from [`TenantRegistry::snapshot`] partitions.
+    ///
+    /// Partitions are restored under `quota`; the first failure aborts.
+    pub fn restore(
+        quota: TenantQuota,
+        partitions: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> Result<Self, TenantError> {
+        let mut registry = Self::new(quota);
+        for (tenant, bytes) in partitions {
+            let ledger = TokenState::from_bytes(&bytes).map_err(|error| TenantError::Snapshot {
+                tenant: tenant.clone(),
+                error,
+            })?;
+            registry.create(&tenant, ledger)?;
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Address;
+    use crate::clock::ManualClock;
+
+    fn transfer(from: &Address, to: &Address, amount: crate::Balance) -> Operation {
+        Operation::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_tenants_are_isolated() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut registry = TenantRegistry::default();
+        registry
+            .create("acme", TokenState::new(alice.clone(), 1000))
+            .unwrap();
+        registry
+            .create("globex", TokenState::new(alice.clone(), 50))
+            .unwrap();
+
+        registry
+            .execute("acme", transfer(&alice, &bob, 100))
+            .unwrap();
+
+        assert_eq!(registry.get("acme").unwrap().balance_of(&bob), 100);
+        assert_eq!(registry.get("globex").unwrap().balance_of(&bob), 0);
+        assert_eq!(registry.tenants(), vec!["acme", "globex"]);
+        assert_eq!(
+            registry.execute("initech", transfer(&alice, &bob, 1)),
+            Err(TenantError::UnknownTenant("initech".into()))
+        );
+    }
+
+    #[test]
+    fn test_quotas() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut registry = TenantRegistry::new(TenantQuota {
+            max_tenants: 1,
+            max_accounts: 2,
+            ..TenantQuota::default()
+        });
+        registry
+            .create("acme", TokenState::new(alice.clone(), 1000))
+            .unwrap();
+
+        registry
+            .execute("acme", transfer(&alice, &bob, 10))
+            .unwrap();
+        let third = registry.execute("acme", transfer(&alice, &carol, 10));
+        registry.execute("acme", transfer(&bob, &alice, 5)).unwrap();
+
+        assert_eq!(
+            third,
+            Err(TenantError::QuotaExceeded {
+                tenant: "acme".into(),
+                resource: Resource::Accounts,
+                limit: 2
+            })
+        );
+        assert_eq!(
+            registry.create("globex", TokenState::new(alice.clone(), 1)),
+            Err(TenantError::TenantLimit { max: 1 })
+        );
+    }
+
+    #[test]
+    fn test_escrow_payout_counts_toward_account_quota() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut registry = TenantRegistry::new(TenantQuota {
+            max_accounts: 1,
+            ..TenantQuota::default()
+        });
+        registry
+            .create("acme", TokenState::new(alice.clone(), 1000))
+            .unwrap();
+        let id = registry
+            .get_mut("acme")
+            .unwrap()
+            .escrow_create(&alice, &bob, 100)
+            .unwrap();
+
+        let release = Operation::EscrowRelease { caller: alice, id };
+
+        assert!(matches!(
+            registry.execute("acme", release),
+            Err(TenantError::QuotaExceeded {
+                resource: Resource::Accounts,
+                ..
+            })
+        ));
+        assert_eq!(registry.get("acme").unwrap().balance_of(&bob), 0);
+    }
+
+    #[test]
+    fn test_rate_and_pending_event_quotas() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut registry = TenantRegistry::new(TenantQuota {
+            max_ops_per_tick: 3,
+            max_pending_events: 2,
+            ..TenantQuota::default()
+        });
+        let clock = ManualClock::new(100);
+        let ledger = TokenState::new(alice.clone(), 1000).with_clock(Box::new(clock.clone()));
+        registry.create("acme", ledger).unwrap();
+
+        registry.execute("acme", transfer(&alice, &bob, 1)).unwrap();
+        registry.execute("acme", transfer(&alice, &bob, 1)).unwrap();
+        let full = registry.execute("acme", transfer(&alice, &bob, 1));
+        registry.get_mut("acme").unwrap().drain_events();
+        registry.execute("acme", transfer(&alice, &bob, 1)).unwrap();
+        // 같은 시각 윈도우 안의 네 번째 호출 (대기 이벤트 초과로 막힌 호출은 세지 않음)
+        let throttled = registry.execute("acme", transfer(&alice, &bob, 1));
+        let usage = registry.usage("acme");
+        clock.advance(1);
+        registry.get_mut("acme").unwrap().drain_events();
+        let next_tick = registry.execute("acme", transfer(&alice, &bob, 1));
+
+        assert!(matches!(
+            full,
+            Err(TenantError::QuotaExceeded {
+                resource: Resource::PendingEvents,
+                ..
+            })
+        ));
+        assert!(matches!(
+            throttled,
+            Err(TenantError::QuotaExceeded {
+                resource: Resource::OpsPerTick,
+                limit: 3,
+                ..
+            })
+        ));
+        assert_eq!(
+            usage,
+            Some(TenantUsage {
+                accounts: 2,
+                ops_this_tick: 3,
+                journal_entries: 0,
+                pending_events: 1
+            })
+        );
+        assert!(next_tick.is_ok());
+        assert_eq!(registry.usage("acme").unwrap().ops_this_tick, 1);
+    }
+
+    #[test]
+    fn test_journal_quota_stops_an_unreverted_branch() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut registry = TenantRegistry::new(TenantQuota {
+            max_journal_entries: 2,
+            ..TenantQuota::default()
+        });
+        registry
+            .create("acme", TokenState::new(alice.clone(), 1000))
+            .unwrap();
+        let checkpoint = registry.get_mut("acme").unwrap().checkpoint();
+        // 승인 한 번에 허용량 하나만 기록됨
+        let approve = |amount| Operation::Approve {
+            owner: alice.clone(),
+            spender: bob.clone(),
+            amount,
+        };
+
+        registry.execute("acme", approve(1)).unwrap();
+        registry.execute("acme", approve(2)).unwrap();
+        let full = registry.execute("acme", approve(3));
+        let usage = registry.usage("acme").unwrap().journal_entries;
+        let ledger = registry.get_mut("acme").unwrap();
+        ledger.revert_to(checkpoint).unwrap();
+
+        assert_eq!(
+            full,
+            Err(TenantError::QuotaExceeded {
+                tenant: "acme".into(),
+                resource: Resource::JournalEntries,
+                limit: 2
+            })
+        );
+        assert_eq!(usage, 2);
+        assert!(registry.execute("acme", approve(3)).is_ok());
+        assert_eq!(registry.get("acme").unwrap().allowance(&alice, &bob), 3);
+    }
+
+    #[test]
+    fn test_snapshot_is_partitioned_per_tenant() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut registry = TenantRegistry::default();
+        registry
+            .create("acme", TokenState::new(alice.clone(), 1000))
+            .unwrap();
+        registry
+            .create("globex", TokenState::new(bob.clone(), 7))
+            .unwrap();
+
+        let partitions = registry.snapshot();
+        let restored = TenantRegistry::restore(TenantQuota::default(), partitions.clone()).unwrap();
+
+        assert_eq!(partitions.keys().collect::<Vec<_>>(), ["acme", "globex"]);
+        assert_eq!(restored.get("globex").unwrap().balance_of(&bob), 7);
+        let mut corrupt = partitions;
+        corrupt.get_mut("acme").unwrap()[0] = 0;
+        assert!(matches!(
+            TenantRegistry::restore(TenantQuota::default(), corrupt),
+            Err(TenantError::Snapshot { ref tenant, .. }) if tenant == "acme"
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+//! Helpers shared by the unit tests.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::events::{EventSink, TokenEvent};
+use crate::interceptor::Interceptor;
+use crate::operation::Operation;
+use crate::tx::SignatureVerifier;
+use crate::{Address, TokenError, TokenState};
+
+/// Lines a [`CallRecorder`] has logged so far.
+pub(crate) type Calls = Rc<RefCell<Vec<String>>>;
+
+/// Logs every call it receives as `"<name> <call>"`, so several recorders
+/// can share one log and their order stays visible.
+pub(crate) struct CallRecorder(pub(crate) &'static str, pub(crate) Calls);
+
+impl Interceptor for CallRecorder {
+    fn before(&mut self, op: &Operation) -> Result<(), TokenError> {
+        let line = format!("{} before {op:?}", self.0);
+        self.1.borrow_mut().push(line);
+        Ok(())
+    }
+
+    fn after(&mut self, _op: &Operation, outcome: Result<(), &TokenError>) {
+        let line = format!("{} after {}", self.0, outcome.is_ok());
+        self.1.borrow_mut().push(line);
+    }
+}
+
+/// Events a [`Recorder`] has heard so far.
+pub(crate) type Heard = Rc<RefCell<Vec<TokenEvent>>>;
+
+/// Event sink that copies every event into a shared vector.
+pub(crate) struct Recorder(pub(crate) Heard);
+
+impl EventSink for Recorder {
+    fn on_event(&mut self, event: &TokenEvent) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+/// Attaches a [`Recorder`] to `token` and returns what it will hear.
+pub(crate) fn record_events(token: &mut TokenState) -> Heard {
+    let heard = Heard::default();
+    token.add_event_sink(Box::new(Recorder(heard.clone())));
+    heard
+}
+
+/// One address per name, in order.
+pub(crate) fn addresses<const N: usize>(names: [&str; N]) -> [Address; N] {
+    names.map(|name| Address::new(name).unwrap())
+}
+
+/// Accepts a signature that is the message's bytes reversed.
+pub(crate) struct Reversed;
+
+impl SignatureVerifier for Reversed {
+    fn verify(&self, _signer: &Address, message: &[u8], signature: &[u8]) -> bool {
+        message.iter().rev().eq(signature)
+    }
+}
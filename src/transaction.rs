@@ -0,0 +1,203 @@
+//! All-or-nothing groups of operations.
+//!
+//! [`TokenState::transaction`] hands a closure a [`Transaction`] through
+//! which it can transfer, approve, mint and burn as usual. Each call runs
+//! as its own [`Operation`], with interceptors, pause and freeze checks and
+//! a sequence number, and is applied to the ledger in place, so later calls
+//! see the effects of earlier ones. If the closure returns `Err` or panics
+//! every change is rolled back through the journal: balances, allowances,
+//! supply, the event log and the sequence counter are all put back and
+//! event sinks hear nothing. Sinks receive the events of a committed
+//! transaction when it ends.
+//!
+//! Interceptors and transfer hooks are not undone; one that keeps its own
+//! state (a rate limit, say) will have counted operations from a
+//! rolled-back transaction, and `after_transfer` will have been told about
+//! its transfers.
+
+use crate::operation::Operation;
+use crate::undo::UndoLog;
+use crate::{Address, Balance, TokenError, TokenState};
+
+/// A ledger inside [`TokenState::transaction`], changed in place until the
+/// transaction commits or rolls back.
+pub struct Transaction<'a> {
+    undo: UndoLog<'a>,
+}
+
+impl TokenState {
+    /// Runs `f` against this ledger, keeping its changes only if it
+    /// returns `Ok`.
+    pub fn transaction<T>(
+        &mut self,
+        f: impl FnOnce(&mut Transaction<'_>) -> Result<T, TokenError>,
+    ) -> Result<T, TokenError> {
+        // f가 패닉하면 tx의 drop이 되돌림
+        let mut tx = Transaction {
+            undo: self.begin_undo(),
+        };
+        match f(&mut tx) {
+            Ok(value) => {
+                tx.undo.commit()?;
+                Ok(value)
+            }
+            Err(error) => {
+                tx.undo.rollback();
+                Err(error)
+            }
+        }
+    }
+}
+
+impl Transaction<'_> {
+    pub fn balance_of(&self, address: &Address) -> Balance {
+        self.undo.balance_of(address)
+    }
+
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> Balance {
+        self.undo.allowance(owner, spender)
+    }
+
+    pub fn transfer(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.run(Operation::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        })
+    }
+
+    pub fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.run(Operation::Approve {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount,
+        })
+    }
+
+    pub fn transfer_from(
+        &mut self,
+        spender: &Address,
+        from: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.run(Operation::TransferFrom {
+            spender: spender.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        })
+    }
+
+    pub fn mint(
+        &mut self,
+        caller: &Address,
+        to: &Address,
+        amount: Balance,
+    ) -> Result<(), TokenError> {
+        self.run(Operation::Mint {
+            caller: caller.clone(),
+            to: to.clone(),
+            amount,
+        })
+    }
+
+    pub fn burn(&mut self, from: &Address, amount: Balance) -> Result<(), TokenError> {
+        self.run(Operation::Burn {
+            from: from.clone(),
+            amount,
+        })
+    }
+
+    fn run(&mut self, op: Operation) -> Result<(), TokenError> {
+        self.undo.execute(op).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::record_events;
+
+    #[test]
+    fn test_committed_transaction_keeps_every_step() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let carol = Address::new("carol").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let heard = record_events(&mut token);
+
+        let result = token.transaction(|tx| {
+            tx.transfer(&alice, &bob, 100)?;
+            tx.approve(&bob, &carol, 40)?;
+            tx.transfer_from(&carol, &bob, &carol, 40)?;
+            Ok(tx.balance_of(&bob))
+        });
+
+        assert_eq!(result, Ok(60));
+        assert_eq!(token.balance_of(&carol), 40);
+        assert_eq!(token.allowance(&bob, &carol), 0);
+        assert_eq!(token.next_seq(), 3);
+        assert_eq!(heard.borrow().len(), 4);
+    }
+
+    #[test]
+    fn test_failed_transaction_rolls_back() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let heard = record_events(&mut token);
+        let hash_before = token.state_hash();
+
+        // 앞의 단계는 성공하지만 마지막 burn에서 잔액 부족
+        let result = token.transaction(|tx| {
+            tx.transfer(&alice, &bob, 100)?;
+            tx.approve(&alice, &bob, 50)?;
+            tx.mint(&alice, &bob, 10)?;
+            tx.burn(&bob, 500)
+        });
+
+        assert!(matches!(
+            result,
+            Err(TokenError::InsufficientBalance { .. })
+        ));
+        assert_eq!(token.state_hash(), hash_before);
+        assert_eq!(token.total_supply(), 1000);
+        assert_eq!(token.allowance(&alice, &bob), 0);
+        assert_eq!(token.next_seq(), 0);
+        assert!(token.events().is_empty());
+        assert!(heard.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_panicking_transaction_rolls_back() {
+        let alice = Address::new("alice").unwrap();
+        let bob = Address::new("bob").unwrap();
+        let mut token = TokenState::new(alice.clone(), 1000);
+        let heard = record_events(&mut token);
+
+        let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            token.transaction(|tx| -> Result<(), TokenError> {
+                tx.transfer(&alice, &bob, 100)?;
+                panic!("caller bug");
+            })
+        }));
+        token.transfer(&alice, &bob, 1).unwrap();
+
+        assert!(unwound.is_err());
+        assert_eq!(token.balance_of(&bob), 1);
+        assert_eq!(token.total_supply(), 1000);
+        // sink는 다시 붙어 있고 되돌린 전송은 듣지 못함
+        assert_eq!(heard.borrow().len(), 1);
+    }
+}
@@ -0,0 +1,114 @@
+//! Undo log for operations made of several steps.
+//!
+//! An [`UndoLog`] is a checkpoint (see the `journal` module) taken on
+//! behalf of a multi-step operation, so a batch or transaction rolls back
+//! through the same journal as [`TokenState::revert_to`]: if a later step
+//! fails, [`UndoLog::rollback`] restores every balance, allowance and note
+//! the steps wrote and drops the events and sequence numbers used since.
+//! Event sinks are detached for the duration, so they only ever hear about
+//! steps that were committed.
+//!
+//! The log borrows the ledger and dereferences to it, so the steps run
+//! through it. Dropping a log that was neither committed nor rolled back
+//! rolls it back, so a step that panics leaves the ledger as it was, with
+//! its sinks reattached and no checkpoint left open. Code that holds
+//! several ledgers at once (netting across a registry) can't lend each to
+//! a log and works with the bare [`UndoMark`] instead.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::events::EventSink;
+use crate::journal::CheckpointId;
+use crate::{TokenError, TokenState};
+
+/// The checkpoint and detached sinks behind an [`UndoLog`].
+pub(crate) struct UndoMark {
+    checkpoint: CheckpointId,
+    events: usize,
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+/// Changes to a ledger that are undone unless committed.
+pub(crate) struct UndoLog<'a> {
+    token: &'a mut TokenState,
+    // commit이나 rollback 전까지 Some
+    mark: Option<UndoMark>,
+}
+
+impl TokenState {
+    /// Starts recording; ends in [`UndoLog::commit`] or a rollback.
+    pub(crate) fn begin_undo(&mut self) -> UndoLog<'_> {
+        let mark = self.mark_undo();
+        UndoLog {
+            token: self,
+            mark: Some(mark),
+        }
+    }
+
+    /// Starts recording without a guard; must end in
+    /// [`TokenState::commit_undo`] or [`TokenState::rollback_undo`].
+    pub(crate) fn mark_undo(&mut self) -> UndoMark {
+        UndoMark {
+            checkpoint: self.checkpoint(),
+            events: self.events.len(),
+            sinks: std::mem::take(&mut self.event_sinks),
+        }
+    }
+
+    /// Keeps every change and delivers the held-back events to the sinks.
+    ///
+    /// Fails with [`TokenError::UnknownCheckpoint`], keeping the changes,
+    /// if a revert to an earlier checkpoint already discarded this mark.
+    pub(crate) fn commit_undo(&mut self, mark: UndoMark) -> Result<(), TokenError> {
+        self.event_sinks = mark.sinks;
+        self.release_checkpoint(mark.checkpoint)?;
+        for (_, event) in self.events.get(mark.events..).unwrap_or_default() {
+            for sink in self.event_sinks.iter_mut() {
+                sink.on_event(event);
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes every change recorded since `mark` was taken.
+    pub(crate) fn rollback_undo(&mut self, mark: UndoMark) {
+        self.event_sinks = mark.sinks;
+        // 더 이른 체크포인트로 이미 되돌렸다면 되돌릴 변경도 남아 있지 않음
+        let _ = self.revert_to(mark.checkpoint);
+    }
+}
+
+impl UndoLog<'_> {
+    /// See [`TokenState::commit_undo`].
+    pub(crate) fn commit(mut self) -> Result<(), TokenError> {
+        match self.mark.take() {
+            Some(mark) => self.token.commit_undo(mark),
+            None => Ok(()),
+        }
+    }
+
+    /// Undoes every change made through the log.
+    pub(crate) fn rollback(self) {}
+}
+
+impl Drop for UndoLog<'_> {
+    fn drop(&mut self) {
+        if let Some(mark) = self.mark.take() {
+            self.token.rollback_undo(mark);
+        }
+    }
+}
+
+impl Deref for UndoLog<'_> {
+    type Target = TokenState;
+
+    fn deref(&self) -> &TokenState {
+        self.token
+    }
+}
+
+impl DerefMut for UndoLog<'_> {
+    fn deref_mut(&mut self) -> &mut TokenState {
+        self.token
+    }
+}
@@ -0,0 +1,244 @@
+//! Seeded generator of realistic operation streams.
+//!
+//! Address popularity follows a Zipf distribution (a few hot accounts, a long
+//! tail of cold ones) and amounts follow a log-normal distribution. The mix
+//! of `approve` / `transfer_from` against plain transfers is configurable.
+//! Output is a stream of `(actor, Action)` pairs usable by benchmarks, fuzz
+//! drivers, and [`crate::sim`] agents.
+
+use crate::rng::Rng;
+use crate::sim::Action;
+use crate::{Address, Balance};
+
+/// Shape of the generated workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadConfig {
+    /// Number of distinct addresses (`addr0` .. `addrN-1`)
+    pub accounts: usize,
+    /// Zipf exponent `s`; 0.0 is uniform, ~1.0 is typical real-world skew
+    pub zipf_exponent: f64,
+    /// Mean of the underlying normal for log-normal amounts
+    pub amount_mu: f64,
+    /// Standard deviation of the underlying normal for log-normal amounts
+    pub amount_sigma: f64,
+    /// Fraction of operations that are `approve`
+    pub approve_ratio: f64,
+    /// Fraction of operations that are `transfer_from`
+    pub transfer_from_ratio: f64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            accounts: 1_000,
+            zipf_exponent: 1.0,
+            amount_mu: 4.0,
+            amount_sigma: 1.5,
+            approve_ratio: 0.05,
+            transfer_from_ratio: 0.05,
+        }
+    }
+}
+
+/// Endless iterator of `(actor, Action)` pairs (empty with fewer than two accounts).
+pub struct Workload {
+    config: WorkloadConfig,
+    rng: Rng,
+    addresses: Vec<Address>,
+    cdf: Vec<f64>,
+    // transfer_from이 실제 승인된 (owner, spender) 쌍을 쓰도록 기록
+    approvals: Vec<(usize, usize)>,
+}
+
+impl Workload {
+    pub fn new(config: WorkloadConfig, seed: u64) -> Self {
+        let addresses = address_set(config.accounts);
+        let cdf = zipf_cdf(config.accounts, config.zipf_exponent);
+
+        Self {
+            config,
+            rng: Rng::new(seed),
+            addresses,
+            cdf,
+            approvals: Vec::new(),
+        }
+    }
+
+    /// Every address the workload can emit, ordered by popularity rank.
+    pub fn addresses(&self) -> &[Address] {
+        &self.addresses
+    }
+
+    fn sample_index(&mut self) -> usize {
+        sample_zipf(&mut self.rng, &self.cdf)
+    }
+
+    fn sample_other(&mut self, not: usize) -> usize {
+        let i = self.sample_index();
+        if i != not {
+            i
+        } else {
+            (i + 1) % self.addresses.len()
+        }
+    }
+
+    fn sample_amount(&mut self) -> Balance {
+        sample_log_normal(
+            &mut self.rng,
+            self.config.amount_mu,
+            self.config.amount_sigma,
+        )
+    }
+}
+
+impl Iterator for Workload {
+    type Item = (Address, Action);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.addresses.len() < 2 {
+            return None;
+        }
+
+        let roll = self.rng.next_f64();
+        let amount = self.sample_amount();
+
+        if roll < self.config.transfer_from_ratio && !self.approvals.is_empty() {
+            let pick = self.rng.below(self.approvals.len() as u64) as usize;
+            let (owner, spender) = self.approvals[pick];
+            let to = self.sample_other(owner);
+            return Some((
+                self.addresses[spender].clone(),
+                Action::TransferFrom {
+                    from: self.addresses[owner].clone(),
+                    to: self.addresses[to].clone(),
+                    amount,
+                },
+            ));
+        }
+
+        let actor = self.sample_index();
+        let other = self.sample_other(actor);
+
+        if roll < self.config.transfer_from_ratio + self.config.approve_ratio {
+            self.approvals.push((actor, other));
+            return Some((
+                self.addresses[actor].clone(),
+                Action::Approve {
+                    spender: self.addresses[other].clone(),
+                    amount: amount.saturating_mul(10),
+                },
+            ));
+        }
+
+        Some((
+            self.addresses[actor].clone(),
+            Action::Transfer {
+                to: self.addresses[other].clone(),
+                amount,
+            },
+        ))
+    }
+}
+
+/// Cumulative Zipf(`exponent`) weights over ranks `1..=n`, normalized to 1.
+pub(crate) fn zipf_cdf(n: usize, exponent: f64) -> Vec<f64> {
+    let mut cdf = Vec::with_capacity(n);
+    let mut acc = 0.0;
+    for rank in 1..=n {
+        acc += 1.0 / (rank as f64).powf(exponent);
+        cdf.push(acc);
+    }
+    for c in &mut cdf {
+        *c /= acc;
+    }
+    cdf
+}
+
+/// Index drawn from a non-empty [`zipf_cdf`].
+pub(crate) fn sample_zipf(rng: &mut Rng, cdf: &[f64]) -> usize {
+    let u = rng.next_f64();
+    cdf.partition_point(|&c| c < u).min(cdf.len() - 1)
+}
+
+/// Log-normal amount, at least 1.
+pub(crate) fn sample_log_normal(rng: &mut Rng, mu: f64, sigma: f64) -> Balance {
+    // Box-Muller 변환으로 표준정규분포 샘플 생성
+    let u1 = 1.0 - rng.next_f64();
+    let u2 = rng.next_f64();
+    let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    let x = (mu + sigma * z).exp();
+    (x.round() as Balance).max(1)
+}
+
+fn address_set(n: usize) -> Vec<Address> {
+    (0..n)
+        .map(|i| Address::trusted(format!("addr{i}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_stream() {
+        let a: Vec<_> = Workload::new(WorkloadConfig::default(), 9)
+            .take(500)
+            .collect();
+        let b: Vec<_> = Workload::new(WorkloadConfig::default(), 9)
+            .take(500)
+            .collect();
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_zipf_favors_low_ranks() {
+        let config = WorkloadConfig {
+            accounts: 100,
+            approve_ratio: 0.0,
+            transfer_from_ratio: 0.0,
+            ..WorkloadConfig::default()
+        };
+        let mut counts = vec![0u32; 100];
+
+        for (actor, _) in Workload::new(config, 1).take(10_000) {
+            let idx: usize = actor.as_str().trim_start_matches("addr").parse().unwrap();
+            counts[idx] += 1;
+        }
+
+        assert!(counts[0] > counts[10]);
+        assert!(counts[10] > counts[99]);
+    }
+
+    #[test]
+    fn test_operation_mix_and_valid_shapes() {
+        let config = WorkloadConfig {
+            accounts: 50,
+            approve_ratio: 0.2,
+            transfer_from_ratio: 0.2,
+            ..WorkloadConfig::default()
+        };
+        let (mut approves, mut pulls) = (0, 0);
+
+        for (actor, action) in Workload::new(config, 5).take(5_000) {
+            match action {
+                Action::Transfer { to, amount } => {
+                    assert_ne!(actor, to);
+                    assert!(amount >= 1);
+                }
+                Action::Approve { spender, .. } => {
+                    assert_ne!(actor, spender);
+                    approves += 1;
+                }
+                Action::TransferFrom { from, to, .. } => {
+                    assert_ne!(from, to);
+                    pulls += 1;
+                }
+            }
+        }
+
+        assert!((800..1200).contains(&approves));
+        assert!((800..1200).contains(&pulls));
+    }
+}